@@ -0,0 +1,253 @@
+//! Выполнение отдельных действий над файлами проекта.
+
+use std::fs;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+use crate::patch;
+use crate::path_policy::PathPolicy;
+use crate::types::{Action, ActionKind};
+
+const TMP_SUFFIX: &str = ".papayu-tmp";
+
+/// Соседний временный путь `.<name>.papayu-tmp` в той же папке (rename в пределах одной ФС атомарен).
+fn tmp_sibling(path: &Path) -> Result<PathBuf, String> {
+    let name = path
+        .file_name()
+        .ok_or_else(|| "path_has_no_file_name".to_string())?
+        .to_string_lossy();
+    Ok(path.with_file_name(format!(".{}{}", name, TMP_SUFFIX)))
+}
+
+/// Ошибка ввода-вывода с тем же префиксом, что у проверок пробного прогона
+/// (`overlay.rs`), чтобы `error_code` при dry-run и apply совпадал.
+fn io_error(e: io::Error) -> String {
+    let prefix = match e.kind() {
+        io::ErrorKind::PermissionDenied => Some("permission_denied"),
+        _ => e.raw_os_error().and_then(os_error_prefix),
+    };
+    match prefix {
+        Some(prefix) => format!("{}: {}", prefix, e),
+        None => e.to_string(),
+    }
+}
+
+// `ErrorKind::{NotADirectory, IsADirectory, StorageFull}` новее rust-version крейта,
+// поэтому эти случаи различаются по коду ошибки ОС.
+#[cfg(unix)]
+fn os_error_prefix(code: i32) -> Option<&'static str> {
+    match code {
+        libc::ENOTDIR => Some("not_a_directory"),
+        libc::EISDIR => Some("is_a_directory"),
+        libc::ENOSPC | libc::EDQUOT => Some("insufficient_disk_space"),
+        _ => None,
+    }
+}
+
+#[cfg(windows)]
+fn os_error_prefix(code: i32) -> Option<&'static str> {
+    // ERROR_HANDLE_DISK_FULL, ERROR_DISK_FULL.
+    match code {
+        39 | 112 => Some("insufficient_disk_space"),
+        _ => None,
+    }
+}
+
+#[cfg(not(any(unix, windows)))]
+fn os_error_prefix(_code: i32) -> Option<&'static str> {
+    None
+}
+
+/// `fs::create_dir_all`; файл на месте одной из папок — `parent_not_directory`, как в overlay.
+fn create_dirs(dir: &Path) -> Result<(), String> {
+    fs::create_dir_all(dir).map_err(|e| match dir.ancestors().find(|p| p.symlink_metadata().is_ok()) {
+        Some(p) if !p.is_dir() => format!("parent_not_directory: {}", p.display()),
+        _ => io_error(e),
+    })
+}
+
+/// Пишет файл через временный файл + rename: при сбое на диске остаётся
+/// либо старое, либо новое содержимое, но не обрезанное.
+pub fn write_atomic(path: &Path, bytes: &[u8]) -> Result<(), String> {
+    if let Some(parent) = path.parent() {
+        create_dirs(parent)?;
+    }
+    let tmp = tmp_sibling(path)?;
+    let result = (|| -> std::io::Result<()> {
+        let mut f = fs::File::create(&tmp)?;
+        f.write_all(bytes)?;
+        f.sync_all()?;
+        if let Ok(meta) = fs::metadata(path) {
+            fs::set_permissions(&tmp, meta.permissions())?;
+        }
+        fs::rename(&tmp, path)
+    })();
+    if result.is_err() {
+        let _ = fs::remove_file(&tmp);
+    }
+    result.map_err(io_error)
+}
+
+/// Папка сначала переименовывается, затем удаляется: из проекта она исчезает разом.
+fn remove_dir_atomic(path: &Path) -> Result<(), String> {
+    let tmp = tmp_sibling(path)?;
+    fs::rename(path, &tmp).map_err(io_error)?;
+    fs::remove_dir_all(&tmp).map_err(io_error)
+}
+
+/// Все пути проекта, которые затрагивает действие: `path` и, для переноса и копии, `to`.
+pub fn touched_paths(a: &Action) -> Vec<&str> {
+    let mut paths = vec![a.path.as_str()];
+    if let Some(to) = a.to.as_deref() {
+        paths.push(to);
+    }
+    paths
+}
+
+/// Проверенный путь `to`: его ещё нет, он не внутри источника; родительские папки создаются.
+fn destination(project_root: &Path, policy: &PathPolicy, a: &Action, source: &Path) -> Result<PathBuf, String> {
+    let to = a.to.as_deref().ok_or_else(|| "destination_missing".to_string())?;
+    let dst = policy.resolve(project_root, to).map_err(|e| e.error)?;
+    if dst.symlink_metadata().is_ok() {
+        return Err(format!("destination_exists: {}", to));
+    }
+    if dst.starts_with(source) {
+        return Err(format!("destination_inside_source: {}", to));
+    }
+    if let Some(parent) = dst.parent() {
+        create_dirs(parent)?;
+    }
+    Ok(dst)
+}
+
+/// Копия через временный файл рядом с целью; `fs::copy` переносит и права доступа.
+fn copy_atomic(src: &Path, dst: &Path) -> Result<(), String> {
+    let tmp = tmp_sibling(dst)?;
+    let result = fs::copy(src, &tmp)
+        .and_then(|_| fs::File::open(&tmp)?.sync_all())
+        .and_then(|_| fs::rename(&tmp, dst));
+    if result.is_err() {
+        let _ = fs::remove_file(&tmp);
+    }
+    result.map_err(io_error)
+}
+
+/// Код ошибки действия по префиксу строки ошибки (`patch_failed: …` → `PATCH_FAILED`).
+pub fn error_code(err: &str) -> &'static str {
+    match err.split(':').next().unwrap_or_default() {
+        "patch_failed" => "PATCH_FAILED",
+        "patch_target_unreadable" | "source_not_found" => "SOURCE_MISSING",
+        "destination_exists" => "DESTINATION_EXISTS",
+        "destination_inside_source" => "DESTINATION_INSIDE_SOURCE",
+        "destination_missing" => "DESTINATION_MISSING",
+        "content_missing" => "CONTENT_MISSING",
+        "search_missing" => "SEARCH_MISSING",
+        "anchor_missing" => "ANCHOR_MISSING",
+        "journal_write_failed" => "JOURNAL_WRITE_FAILED",
+        "permission_denied" => "PERMISSION_DENIED",
+        "parent_not_directory" => "PARENT_NOT_DIRECTORY",
+        "is_a_directory" => "IS_A_DIRECTORY",
+        "not_a_directory" => "NOT_A_DIRECTORY",
+        "insufficient_disk_space" => "INSUFFICIENT_SPACE",
+        e if e.ends_with("_denied") || e.starts_with("path_") || e.starts_with("symlink_") => "PATH_DENIED",
+        _ => "IO_ERROR",
+    }
+}
+
+/// Новое содержимое файла для точечных правок (`apply_patch`, `search_replace`, `insert_after`).
+pub fn patched_content(current: &str, a: &Action) -> Result<String, String> {
+    let content = a
+        .content
+        .as_deref()
+        .ok_or_else(|| "content_missing".to_string())?;
+    match a.kind {
+        ActionKind::ApplyPatch => patch::apply_unified(current, content),
+        ActionKind::SearchReplace => {
+            let search = a.search.as_deref().ok_or_else(|| "search_missing".to_string())?;
+            patch::search_replace(current, search, content)
+        }
+        ActionKind::InsertAfter => {
+            let anchor = a.anchor.as_deref().ok_or_else(|| "anchor_missing".to_string())?;
+            patch::insert_after(current, anchor, content)
+        }
+        _ => Err("not_a_patch_action".into()),
+    }
+}
+
+pub fn execute(project_root: &Path, policy: &PathPolicy, a: &Action) -> Result<(), String> {
+    let abs = policy.resolve(project_root, &a.path).map_err(|e| e.error)?;
+
+    match a.kind {
+        ActionKind::CreateDir => {
+            if abs.symlink_metadata().is_ok() && !abs.is_dir() {
+                return Err(format!("not_a_directory: {}", a.path));
+            }
+            create_dirs(&abs)?;
+        }
+        ActionKind::DeleteDir => {
+            if abs.symlink_metadata().is_ok() && !abs.is_dir() {
+                return Err(format!("not_a_directory: {}", a.path));
+            }
+            if abs.exists() {
+                remove_dir_atomic(&abs)?;
+            }
+        }
+        ActionKind::CreateFile | ActionKind::UpdateFile => {
+            let content = a
+                .content
+                .as_ref()
+                .ok_or_else(|| "content_missing".to_string())?;
+            write_atomic(&abs, content.as_bytes())?;
+        }
+        ActionKind::DeleteFile => {
+            if abs.is_dir() {
+                return Err(format!("is_a_directory: {}", a.path));
+            }
+            if abs.exists() {
+                fs::remove_file(&abs).map_err(io_error)?;
+            }
+        }
+        ActionKind::ApplyPatch | ActionKind::SearchReplace | ActionKind::InsertAfter => {
+            let current = fs::read_to_string(&abs).map_err(|e| format!("patch_target_unreadable: {}", e))?;
+            write_atomic(&abs, patched_content(&current, a)?.as_bytes())?;
+        }
+        ActionKind::MoveFile | ActionKind::CopyFile => {
+            if !abs.is_file() {
+                return Err(format!("source_not_found: {}", a.path));
+            }
+            let dst = destination(project_root, policy, a, &abs)?;
+            if matches!(a.kind, ActionKind::MoveFile) {
+                fs::rename(&abs, &dst).map_err(io_error)?;
+            } else {
+                copy_atomic(&abs, &dst)?;
+            }
+        }
+        ActionKind::MoveDir => {
+            if !abs.is_dir() {
+                return Err(format!("source_not_found: {}", a.path));
+            }
+            let dst = destination(project_root, policy, a, &abs)?;
+            fs::rename(&abs, &dst).map_err(io_error)?;
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn io_errors_map_to_dry_run_codes() {
+        let code = |e: io::Error| error_code(&io_error(e));
+        assert_eq!(code(io::ErrorKind::PermissionDenied.into()), "PERMISSION_DENIED");
+        assert_eq!(code(io::ErrorKind::Other.into()), "IO_ERROR");
+        #[cfg(unix)]
+        {
+            assert_eq!(code(io::Error::from_raw_os_error(libc::ENOSPC)), "INSUFFICIENT_SPACE");
+            assert_eq!(code(io::Error::from_raw_os_error(libc::EISDIR)), "IS_A_DIRECTORY");
+            assert_eq!(code(io::Error::from_raw_os_error(libc::ENOTDIR)), "NOT_A_DIRECTORY");
+        }
+    }
+}
@@ -0,0 +1,178 @@
+//! Задание анализа: id, лимиты по числу файлов и времени, отмена и ход работы.
+//! Обход, глубокий анализ и история git проверяют задание между файлами;
+//! остановка по лимиту или отмене — не ошибка, отчёт строится по тому, что успели.
+
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use crate::progress::{NoProgress, ProgressSink};
+use crate::types::{AnalysisStop, AnalyzeProgress};
+
+/// Прогресс по файлам отправляется не чаще.
+const PROGRESS_INTERVAL: Duration = Duration::from_millis(100);
+
+struct Phase {
+    name: String,
+    message: String,
+    total_estimate: Option<u64>,
+    last_sent: Instant,
+}
+
+pub struct Job {
+    pub id: String,
+    max_files: u64,
+    deadline: Instant,
+    cancelled: AtomicBool,
+    stop: Mutex<Option<AnalysisStop>>,
+    /// Файлов найдено обходом — для лимита.
+    walked: AtomicU64,
+    /// Файлов обработано в текущей фазе.
+    done: AtomicU64,
+    phase: Mutex<Phase>,
+    sink: Arc<dyn ProgressSink>,
+}
+
+impl Job {
+    pub fn new(id: String, max_files: u64, timeout: Duration, sink: Arc<dyn ProgressSink>) -> Job {
+        Job {
+            id,
+            max_files,
+            deadline: Instant::now() + timeout,
+            cancelled: AtomicBool::new(false),
+            stop: Mutex::new(None),
+            walked: AtomicU64::new(0),
+            done: AtomicU64::new(0),
+            phase: Mutex::new(Phase {
+                name: String::new(),
+                message: String::new(),
+                total_estimate: None,
+                last_sent: Instant::now(),
+            }),
+            sink,
+        }
+    }
+
+    /// Без id и прогресса: пробный apply, контекст LLM.
+    pub fn detached(max_files: u64, timeout: Duration) -> Job {
+        Job::new(String::new(), max_files, timeout, Arc::new(NoProgress))
+    }
+
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::Relaxed);
+    }
+
+    pub fn halt(&self, why: AnalysisStop) {
+        self.stop.lock().unwrap_or_else(|e| e.into_inner()).get_or_insert(why);
+    }
+
+    /// Почему задание остановлено (первая причина); `None` — работает.
+    pub fn stopped(&self) -> Option<AnalysisStop> {
+        *self.stop.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Пора остановиться: отмена или истекло время. Лимит файлов останавливает
+    /// только обход (`walk_should_stop`) — найденные файлы разбираются дальше.
+    pub fn should_stop(&self) -> bool {
+        let why = if self.cancelled.load(Ordering::Relaxed) {
+            AnalysisStop::Cancelled
+        } else if Instant::now() > self.deadline {
+            AnalysisStop::Timeout
+        } else {
+            return false;
+        };
+        self.halt(why);
+        true
+    }
+
+    /// Обходу пора остановиться: как `should_stop`, плюс достигнут лимит файлов.
+    pub fn walk_should_stop(&self) -> bool {
+        self.should_stop() || self.walked.load(Ordering::Relaxed) >= self.max_files
+    }
+
+    /// Начало фазы: счётчик файлов сбрасывается, прогресс отправляется сразу.
+    pub fn phase(&self, name: &str, message: &str, total_estimate: Option<u64>) {
+        self.done.store(0, Ordering::Relaxed);
+        let mut phase = self.phase.lock().unwrap_or_else(|e| e.into_inner());
+        phase.name = name.to_string();
+        phase.message = message.to_string();
+        phase.total_estimate = total_estimate;
+        self.send(&mut phase, None);
+    }
+
+    /// Новое сообщение в той же фазе.
+    pub fn message(&self, message: &str) {
+        let mut phase = self.phase.lock().unwrap_or_else(|e| e.into_inner());
+        phase.message = message.to_string();
+        self.send(&mut phase, None);
+    }
+
+    /// Обход прочитал папку с `files` файлами; на лимите задание останавливается.
+    pub fn walked_dir(&self, dir: &Path, files: u64) {
+        let walked = self.walked.fetch_add(files, Ordering::Relaxed) + files;
+        if walked >= self.max_files {
+            self.halt(AnalysisStop::FileLimit);
+        }
+        self.done.fetch_add(files, Ordering::Relaxed);
+        self.tick(dir);
+    }
+
+    /// Файл разобран.
+    pub fn scanned(&self, path: &Path) {
+        self.done.fetch_add(1, Ordering::Relaxed);
+        self.tick(path);
+    }
+
+    fn tick(&self, path: &Path) {
+        // Параллельные потоки не ждут друг друга: кто не взял блокировку, прогресс не шлёт.
+        let Ok(mut phase) = self.phase.try_lock() else { return };
+        if phase.last_sent.elapsed() >= PROGRESS_INTERVAL {
+            self.send(&mut phase, Some(path));
+        }
+    }
+
+    fn send(&self, phase: &mut Phase, path: Option<&Path>) {
+        phase.last_sent = Instant::now();
+        self.sink.analysis(&AnalyzeProgress {
+            job_id: self.id.clone(),
+            phase: phase.name.clone(),
+            message: phase.message.clone(),
+            files_scanned: self.done.load(Ordering::Relaxed),
+            total_estimate: phase.total_estimate,
+            current_path: path.map(|p| p.to_string_lossy().to_string()),
+        });
+    }
+}
+
+static JOBS: Mutex<Option<HashMap<String, Arc<Job>>>> = Mutex::new(None);
+
+pub fn new_id() -> String {
+    static SEQ: AtomicU64 = AtomicU64::new(0);
+    format!("{}-{}", chrono::Utc::now().timestamp_millis(), SEQ.fetch_add(1, Ordering::Relaxed))
+}
+
+/// Задание доступно для `cancel` до `finish`.
+pub fn register(job: Arc<Job>) {
+    let mut jobs = JOBS.lock().unwrap_or_else(|e| e.into_inner());
+    jobs.get_or_insert_with(HashMap::new).insert(job.id.clone(), job);
+}
+
+pub fn finish(id: &str) {
+    if let Some(jobs) = JOBS.lock().unwrap_or_else(|e| e.into_inner()).as_mut() {
+        jobs.remove(id);
+    }
+}
+
+/// `false` — задания с таким id нет (уже завершилось).
+pub fn cancel(id: &str) -> bool {
+    let jobs = JOBS.lock().unwrap_or_else(|e| e.into_inner());
+    match jobs.as_ref().and_then(|j| j.get(id)) {
+        Some(job) => {
+            job.cancel();
+            true
+        }
+        None => false,
+    }
+}
@@ -0,0 +1,272 @@
+//! Индекс проекта между анализами: для каждого файла — размер, время изменения,
+//! sha256 содержимого и находки глубокого анализа. Хранится в
+//! `app_data_dir/analysis/<ключ>.json`, у каждого проекта свой. Повторный анализ
+//! разбирает заново только изменившиеся файлы и сообщает, что изменилось с прошлого раза.
+//! Индекс сохраняется и после остановленного анализа — следующий продолжит с того же места.
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::UNIX_EPOCH;
+
+use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use crate::analysis_job::Job;
+use crate::app_dirs::AppDirs;
+use crate::deep_analysis::{self, DeepAnalysisResult};
+use crate::file_index::{FileIndex, IndexedFile};
+use crate::project_fs::ProjectFs;
+use crate::session;
+use crate::types::{AnalysisConfig, AnalysisDelta, Finding};
+
+const STORE_DIR: &str = "analysis";
+const STORE_VERSION: u32 = 2;
+/// Больше путей в каждом списке `AnalysisDelta` не отдаётся.
+const MAX_DELTA_PATHS: usize = 200;
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct StoredIndex {
+    pub version: u32,
+    /// С другой версией приложения или другими правилами находки разбираются заново.
+    pub app_version: String,
+    #[serde(default)]
+    pub rules: Vec<String>,
+    /// `RuleSet::fingerprint` правил пользователя.
+    #[serde(default)]
+    pub custom_rules: String,
+    /// Канонический путь корня (`session::project_key`).
+    pub project_root: String,
+    pub analyzed_at: String,
+    /// Ключ — путь от корня через `/`.
+    pub files: BTreeMap<String, StoredFile>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StoredFile {
+    pub size: u64,
+    /// Время изменения, мс от Unix epoch.
+    pub modified_ms: Option<u64>,
+    /// Только у файлов, которые читал глубокий анализ.
+    pub sha256: Option<String>,
+    /// Результат глубокого анализа файла; `None` — файл не разбирался.
+    pub deep: Option<DeepAnalysisResult>,
+}
+
+/// Глубокий анализ, индекс для сохранения и разница с прошлым анализом.
+pub struct DeepPass {
+    pub deep: DeepAnalysisResult,
+    pub stored: StoredIndex,
+    pub delta: Option<AnalysisDelta>,
+}
+
+fn store_path(dirs: &AppDirs, project_root: &Path) -> Result<PathBuf, String> {
+    let key = format!("{:x}", Sha256::digest(session::project_key(project_root).as_bytes()));
+    dirs.data_dir().map(|d| d.join(STORE_DIR).join(format!("{}.json", &key[..16])))
+}
+
+/// Индекс прошлого анализа проекта; нет, не читается или другой версии — `None`.
+pub fn load(dirs: &AppDirs, project_root: &Path) -> Option<StoredIndex> {
+    let path = store_path(dirs, project_root).ok()?;
+    let text = fs::read_to_string(&path).ok()?;
+    match serde_json::from_str::<StoredIndex>(&text) {
+        Ok(s) if s.version == STORE_VERSION && s.project_root == session::project_key(project_root) => Some(s),
+        Ok(_) => None,
+        Err(e) => {
+            log::warn!("{}: {}, проект будет разобран заново", path.display(), e);
+            None
+        }
+    }
+}
+
+pub fn save(dirs: &AppDirs, project_root: &Path, index: &StoredIndex) -> Result<(), String> {
+    let path = store_path(dirs, project_root)?;
+    let json = serde_json::to_string(index).map_err(|e| e.to_string())?;
+    crate::actions::write_atomic(&path, json.as_bytes())
+}
+
+enum Scan {
+    Rescanned,
+    Reused,
+    /// Задание остановлено раньше, чем дошло до файла.
+    Skipped,
+}
+
+/// Глубокий анализ с индексом прошлого анализа: файл с прежними размером и временем
+/// изменения не читается, с прежним sha256 — не разбирается заново.
+pub fn deep_pass(
+    fs: &dyn ProjectFs,
+    index: &FileIndex,
+    config: &AnalysisConfig,
+    previous: Option<&StoredIndex>,
+    job: &Job,
+) -> DeepPass {
+    let reusable = previous.filter(|p| {
+        p.app_version == env!("CARGO_PKG_VERSION") && p.rules == config.rules && p.custom_rules == config.rule_set.fingerprint()
+    });
+    let candidates = deep_analysis::candidates(index, &config.limits);
+    job.phase("deep", "Глубокий анализ кода…", Some(candidates.len() as u64));
+    let scanned: Vec<(StoredFile, Scan)> = candidates
+        .par_iter()
+        .map(|f| {
+            let r = scan(fs, f, config, reusable.and_then(|p| p.files.get(&f.rel)), job);
+            job.scanned(&f.path);
+            r
+        })
+        .collect();
+
+    let mut files: BTreeMap<String, StoredFile> = index
+        .files
+        .iter()
+        .map(|f| {
+            let stored = StoredFile {
+                size: f.size,
+                modified_ms: modified_ms(f),
+                sha256: None,
+                deep: None,
+            };
+            (f.rel.clone(), stored)
+        })
+        .collect();
+    let (mut rescanned, mut reused) = (0, 0);
+    let mut per_file = Vec::with_capacity(scanned.len());
+    for (f, (stored, how)) in candidates.iter().zip(scanned) {
+        match how {
+            Scan::Rescanned => rescanned += 1,
+            Scan::Reused => reused += 1,
+            Scan::Skipped => {}
+        }
+        per_file.extend(stored.deep.clone());
+        files.insert(f.rel.clone(), stored);
+    }
+    // Обход не дошёл до конца: про остальные файлы известно то, что было в прошлый раз.
+    if let (Some(_), Some(p)) = (index.stopped, previous) {
+        for (rel, f) in &p.files {
+            files.entry(rel.clone()).or_insert_with(|| f.clone());
+        }
+    }
+
+    let stored = StoredIndex {
+        version: STORE_VERSION,
+        app_version: env!("CARGO_PKG_VERSION").to_string(),
+        rules: config.rules.clone(),
+        custom_rules: config.rule_set.fingerprint().to_string(),
+        project_root: session::project_key(&index.root),
+        analyzed_at: session::now_rfc3339(),
+        files,
+    };
+    let delta = previous.map(|p| delta(p, &stored, rescanned, reused));
+    DeepPass {
+        deep: deep_analysis::merge(per_file),
+        stored,
+        delta,
+    }
+}
+
+fn modified_ms(file: &IndexedFile) -> Option<u64> {
+    let since = file.modified?.duration_since(UNIX_EPOCH).ok()?;
+    Some(since.as_millis() as u64)
+}
+
+fn scan(
+    fs: &dyn ProjectFs,
+    file: &IndexedFile,
+    config: &AnalysisConfig,
+    before: Option<&StoredFile>,
+    job: &Job,
+) -> (StoredFile, Scan) {
+    let modified_ms = modified_ms(file);
+    let entry = |sha256: Option<String>, deep: DeepAnalysisResult| StoredFile {
+        size: file.size,
+        modified_ms,
+        sha256,
+        deep: Some(deep),
+    };
+    let prior = before.and_then(|b| b.deep.as_ref().map(|d| (b, d)));
+
+    if let Some((b, deep)) = prior {
+        if modified_ms.is_some() && b.modified_ms == modified_ms && b.size == file.size {
+            return (entry(b.sha256.clone(), deep.clone()), Scan::Reused);
+        }
+    }
+    if job.should_stop() {
+        let unknown = StoredFile {
+            size: file.size,
+            modified_ms,
+            sha256: None,
+            deep: None,
+        };
+        return (unknown, Scan::Skipped);
+    }
+    let sha256 = file.content(fs).map(|c| format!("{:x}", Sha256::digest(c.as_bytes())));
+    if let Some((b, deep)) = prior {
+        if sha256.is_some() && b.sha256 == sha256 {
+            return (entry(sha256, deep.clone()), Scan::Reused);
+        }
+    }
+    // Нечитаемый файл запоминается как разобранный, с пустым результатом.
+    let deep = deep_analysis::analyze_file(fs, file, config).unwrap_or_default();
+    (entry(sha256, deep), Scan::Rescanned)
+}
+
+/// Содержимое изменилось: по sha256, если он есть у обеих записей, иначе по размеру и времени.
+fn changed(before: &StoredFile, after: &StoredFile) -> bool {
+    match (&before.sha256, &after.sha256) {
+        (Some(b), Some(a)) => b != a,
+        _ => before.size != after.size || before.modified_ms != after.modified_ms,
+    }
+}
+
+fn findings(file: &StoredFile) -> &[Finding] {
+    file.deep.as_ref().map_or(&[], |d| d.findings.as_slice())
+}
+
+fn delta(before: &StoredIndex, after: &StoredIndex, rescanned: u32, reused: u32) -> AnalysisDelta {
+    let mut added = vec![];
+    let mut removed = vec![];
+    let mut modified = vec![];
+    let mut new_findings = vec![];
+    let mut resolved_findings = vec![];
+
+    for (rel, a) in &after.files {
+        match before.files.get(rel) {
+            None => {
+                added.push(rel.clone());
+                new_findings.extend_from_slice(findings(a));
+            }
+            Some(b) => {
+                let is_modified = changed(b, a);
+                if is_modified {
+                    modified.push(rel.clone());
+                }
+                // Находки сравниваются у изменённых файлов и у тех, что в прошлый раз не успели разобрать.
+                if a.deep.is_some() && (is_modified || b.deep.is_none()) {
+                    new_findings.extend(Finding::unmatched(findings(a), findings(b)));
+                    resolved_findings.extend(Finding::unmatched(findings(b), findings(a)));
+                }
+            }
+        }
+    }
+    for (rel, b) in &before.files {
+        if !after.files.contains_key(rel) {
+            removed.push(rel.clone());
+            resolved_findings.extend_from_slice(findings(b));
+        }
+    }
+
+    let truncated = [&added, &removed, &modified].iter().any(|v| v.len() > MAX_DELTA_PATHS);
+    for v in [&mut added, &mut removed, &mut modified] {
+        v.truncate(MAX_DELTA_PATHS);
+    }
+    AnalysisDelta {
+        since: before.analyzed_at.clone(),
+        added,
+        removed,
+        modified,
+        truncated,
+        new_findings,
+        resolved_findings,
+        rescanned,
+        reused,
+    }
+}
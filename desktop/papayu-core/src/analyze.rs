@@ -0,0 +1,921 @@
+//! Анализ проекта: обход, структура, глубокий анализ, история git и отчёт.
+
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::analysis_job::{self, Job};
+use crate::analysis_store::{self, StoredIndex};
+use crate::app_dirs::AppDirs;
+use crate::file_index::{self, FileIndex, IndexedDir, IndexedFile};
+use crate::progress::{NoProgress, ProgressSink};
+use crate::project_config;
+use crate::project_fs::{DiskFs, ProjectFs};
+use crate::rules;
+use crate::types::{
+    Action, ActionKind, AnalysisConfig, AnalysisStop, AnalyzeOptions, AnalyzeReport, Finding, LlmContext, ProjectContext, ProjectSignal,
+    ProjectStructure, Recommendation, ReportStats,
+};
+
+const TOP_EXTENSIONS_N: usize = 15;
+const MAX_DEPTH_WARN: u32 = 6;
+const ROOT_FILES_WARN: u64 = 20;
+/// Рисков в контексте для LLM не больше: находки идут по одной на совпадение.
+const MAX_KEY_RISKS: usize = 20;
+
+/// Находки по проекту в целом: (id правила, категория, описание).
+pub const STRUCTURE_RULES: &[(&str, &str, &str)] = &[
+    ("structure/env-file", "security", "Файл .env в проекте — риск утечки секретов"),
+    ("structure/root-files", "structure", "Много файлов в корне проекта"),
+    ("structure/deep-nesting", "structure", "Глубокая вложенность папок"),
+    ("structure/unknown-project", "structure", "Не найдены манифесты проекта"),
+    ("analysis/config-errors", "analysis", "Ошибки в .papayu.toml"),
+    ("analysis/rule-errors", "analysis", "Ошибки в наборах правил пользователя"),
+    ("analysis/incomplete", "analysis", "Анализ остановлен — отчёт по части проекта"),
+];
+
+const MARKER_README: &[&str] = &["README", "readme", "Readme"];
+const MARKER_VITE: &[&str] = &["vite.config.js", "vite.config.ts", "vite.config.mjs"];
+
+#[derive(Default)]
+struct ScanState {
+    file_count: u64,
+    dir_count: u64,
+    total_size_bytes: u64,
+    extensions: HashMap<String, u64>,
+    has_readme: bool,
+    has_package_json: bool,
+    has_cargo_toml: bool,
+    /// Файлы `.env*`, пути от корня.
+    env_files: Vec<String>,
+    has_docker: bool,
+    has_tsconfig: bool,
+    has_vite: bool,
+    has_next: bool,
+    has_gitignore: bool,
+    has_license: bool,
+    has_eslint: bool,
+    has_prettier: bool,
+    has_tests_dir: bool,
+    has_src: bool,
+    has_components: bool,
+    has_pages: bool,
+    has_requirements_txt: bool,
+    has_pyproject: bool,
+    has_setup_py: bool,
+    package_json_count: u32,
+    cargo_toml_count: u32,
+    root_file_count: u64,
+    root_dirs: HashSet<String>,
+    max_depth: u32,
+}
+
+/// Анализ проекта на диске с индексом прошлого анализа из `dirs`. Пока он идёт,
+/// задание можно остановить через `analysis_job::cancel(job_id)`.
+pub fn run_analysis(
+    sink: Arc<dyn ProgressSink>,
+    dirs: &AppDirs,
+    path: &str,
+    options: &AnalyzeOptions,
+    job_id: Option<String>,
+) -> Result<AnalyzeReport, String> {
+    let root = PathBuf::from(path);
+    if !root.exists() {
+        return Err("Путь не существует".to_string());
+    }
+    if !root.is_dir() {
+        return Err("Путь не является папкой".to_string());
+    }
+
+    let config = load_config(&DiskFs, dirs, &root, options);
+    let job_id = job_id.unwrap_or_else(analysis_job::new_id);
+    let job = Arc::new(new_job(job_id.clone(), &config, sink));
+    analysis_job::register(job.clone());
+    let result = analyze_with_store(dirs, &root, &config, &job);
+    analysis_job::finish(&job_id);
+    result
+}
+
+fn analyze_with_store(dirs: &AppDirs, root: &Path, config: &AnalysisConfig, job: &Job) -> Result<AnalyzeReport, String> {
+    let previous = analysis_store::load(dirs, root);
+    let estimate = previous.as_ref().map(|p| p.files.len() as u64);
+    let index = Arc::new(build_index(&DiskFs, root, config, estimate, job));
+    let (report, stored) = analyze_index(&DiskFs, &index, config, previous.as_ref(), job)?;
+    if let Err(e) = analysis_store::save(dirs, root, &stored) {
+        log::warn!("analysis index not saved: {}", e);
+    }
+    // Неполный индекс не запоминается: контекст LLM обойдёт дерево сам.
+    if index.stopped.is_none() {
+        file_index::remember(&index);
+    }
+    Ok(report)
+}
+
+/// Анализ проекта, читаемого через `fs` (диск или дерево пробного apply); неполный — ошибка.
+pub fn analyze(fs: &dyn ProjectFs, dirs: &AppDirs, path: &str, options: &AnalyzeOptions) -> Result<AnalyzeReport, String> {
+    let mut config = load_config(fs, dirs, Path::new(path), options);
+    // История читается с диска и до и после пробного apply одна и та же.
+    config.git_history = false;
+    let job = new_job(String::new(), &config, Arc::new(NoProgress));
+    let index = build_index(fs, Path::new(path), &config, None, &job);
+    let (report, _) = analyze_index(fs, &index, &config, None, &job)?;
+    match report.stopped {
+        Some(stop) => Err(stop_message(stop).to_string()),
+        None => Ok(report),
+    }
+}
+
+/// Настройки из `.papayu.toml` и профиля; `options.git_history` включает историю в любом профиле.
+/// Правила пользователя — из `dirs` и `.papayu/rules/` проекта.
+pub fn load_config(fs: &dyn ProjectFs, dirs: &AppDirs, root: &Path, options: &AnalyzeOptions) -> AnalysisConfig {
+    let mut config = project_config::load(fs, root, options.profile);
+    config.git_history |= options.git_history;
+    let (rule_set, rule_warnings) = rules::load(fs, root, dirs);
+    config.custom_rules = rule_set.meta();
+    config.rule_warnings = rule_warnings;
+    config.rule_set = rule_set;
+    config
+}
+
+fn new_job(id: String, config: &AnalysisConfig, sink: Arc<dyn ProgressSink>) -> Job {
+    let limits = &config.limits;
+    Job::new(id, limits.max_files, Duration::from_secs(limits.max_duration_secs), sink)
+}
+
+/// Один обход дерева на весь анализ; `total_estimate` — сколько файлов было в прошлый раз.
+pub fn build_index(
+    fs: &dyn ProjectFs,
+    root: &Path,
+    config: &AnalysisConfig,
+    total_estimate: Option<u64>,
+    job: &Job,
+) -> FileIndex {
+    job.phase("walk", "Сканирую структуру…", total_estimate);
+    FileIndex::build(fs, root, job, &config.exclude)
+}
+
+fn stop_message(stop: AnalysisStop) -> &'static str {
+    match stop {
+        AnalysisStop::Timeout => "Превышено время анализа (таймаут)",
+        AnalysisStop::FileLimit => "Превышен лимит количества файлов",
+        AnalysisStop::Cancelled => "Анализ отменён",
+    }
+}
+
+/// Отчёт и индекс для следующего анализа; с `previous` неизменённые файлы не разбираются заново.
+pub fn analyze_index(
+    fs: &dyn ProjectFs,
+    index: &FileIndex,
+    config: &AnalysisConfig,
+    previous: Option<&StoredIndex>,
+    job: &Job,
+) -> Result<(AnalyzeReport, StoredIndex), String> {
+    let root = index.root.clone();
+    let path = root.to_string_lossy().to_string();
+    let state = scan(index);
+
+    job.phase("structure", "Анализирую архитектуру…", None);
+
+    let top_extensions: Vec<(String, u64)> = {
+        let mut v: Vec<_> = state.extensions.iter().map(|(k, v)| (k.clone(), *v)).collect();
+        v.sort_by_key(|e| std::cmp::Reverse(e.1));
+        v.into_iter().take(TOP_EXTENSIONS_N).collect()
+    };
+
+    let stats = ReportStats {
+        file_count: state.file_count,
+        dir_count: state.dir_count,
+        total_size_bytes: state.total_size_bytes,
+        top_extensions,
+        max_depth: state.max_depth as u64,
+    };
+
+    let structure = build_structure(&state);
+    let mut findings: Vec<Finding> = Vec::new();
+    let mut recommendations: Vec<Recommendation> = Vec::new();
+    let mut signals: Vec<ProjectSignal> = Vec::new();
+
+    for env in &state.env_files {
+        findings.push(Finding {
+            severity: "high".to_string(),
+            title: "Риск секретов".to_string(),
+            details: format!("{} — не коммитьте секреты в репозиторий.", env),
+            rule_id: Some("structure/env-file".to_string()),
+            category: Some("security".to_string()),
+            path: Some(env.clone()),
+            ..Default::default()
+        });
+    }
+    if !state.env_files.is_empty() {
+        signals.push(ProjectSignal {
+            category: "security".to_string(),
+            level: "high".to_string(),
+            message: "Есть .env файл — риск утечки секретов.".to_string(),
+        });
+    }
+
+    if !state.has_readme {
+        recommendations.push(Recommendation {
+            title: "Добавить README".to_string(),
+            details: "Опишите проект и инструкцию по запуску.".to_string(),
+            priority: "medium".to_string(),
+            effort: "low".to_string(),
+            impact: "high".to_string(),
+        });
+        signals.push(ProjectSignal {
+            category: "quality".to_string(),
+            level: "warn".to_string(),
+            message: "Нет README.".to_string(),
+        });
+    }
+
+    if !state.has_gitignore {
+        recommendations.push(Recommendation {
+            title: "Добавить .gitignore".to_string(),
+            details: "Исключите артефакты сборки и зависимости из репозитория.".to_string(),
+            priority: "medium".to_string(),
+            effort: "low".to_string(),
+            impact: "medium".to_string(),
+        });
+        signals.push(ProjectSignal {
+            category: "quality".to_string(),
+            level: "warn".to_string(),
+            message: "Нет .gitignore.".to_string(),
+        });
+    }
+
+    if !state.has_license {
+        recommendations.push(Recommendation {
+            title: "Указать лицензию".to_string(),
+            details: "Добавьте LICENSE или LICENSE.md.".to_string(),
+            priority: "low".to_string(),
+            effort: "low".to_string(),
+            impact: "medium".to_string(),
+        });
+        signals.push(ProjectSignal {
+            category: "quality".to_string(),
+            level: "info".to_string(),
+            message: "Нет файла лицензии.".to_string(),
+        });
+    }
+
+    if state.has_src && !state.has_tests_dir {
+        recommendations.push(Recommendation {
+            title: "Добавить тесты".to_string(),
+            details: "Есть src/, но нет папки tests/ — добавьте базовые тесты.".to_string(),
+            priority: "high".to_string(),
+            effort: "medium".to_string(),
+            impact: "high".to_string(),
+        });
+        signals.push(ProjectSignal {
+            category: "structure".to_string(),
+            level: "warn".to_string(),
+            message: "Есть src/, нет tests/.".to_string(),
+        });
+    }
+
+    if state.has_components && !state.has_pages && state.has_package_json {
+        recommendations.push(Recommendation {
+            title: "Проверить структуру фронтенда".to_string(),
+            details: "Есть components/, но нет pages/ — возможно, маршруты или страницы в другом месте.".to_string(),
+            priority: "low".to_string(),
+            effort: "low".to_string(),
+            impact: "low".to_string(),
+        });
+    }
+
+    if state.root_file_count >= ROOT_FILES_WARN {
+        findings.push(Finding {
+            severity: "warn".to_string(),
+            title: "Много файлов в корне".to_string(),
+            details: format!("В корне {} файлов — рассмотрите группировку по папкам.", state.root_file_count),
+            rule_id: Some("structure/root-files".to_string()),
+            category: Some("structure".to_string()),
+            ..Default::default()
+        });
+        signals.push(ProjectSignal {
+            category: "structure".to_string(),
+            level: "warn".to_string(),
+            message: "Слишком много файлов в корне проекта.".to_string(),
+        });
+    }
+
+    if state.max_depth >= MAX_DEPTH_WARN {
+        findings.push(Finding {
+            severity: "warn".to_string(),
+            title: "Глубокая вложенность".to_string(),
+            details: format!("Вложенность до {} уровней — усложняет навигацию.", state.max_depth),
+            rule_id: Some("structure/deep-nesting".to_string()),
+            category: Some("structure".to_string()),
+            ..Default::default()
+        });
+        signals.push(ProjectSignal {
+            category: "structure".to_string(),
+            level: "warn".to_string(),
+            message: "Глубокая вложенность папок.".to_string(),
+        });
+    }
+
+    if state.has_package_json && !state.has_eslint && !state.has_cargo_toml {
+        recommendations.push(Recommendation {
+            title: "Добавить линтер".to_string(),
+            details: "Рекомендуется ESLint (и при необходимости Prettier) для JavaScript/TypeScript.".to_string(),
+            priority: "medium".to_string(),
+            effort: "low".to_string(),
+            impact: "medium".to_string(),
+        });
+    }
+
+    if state.has_cargo_toml && !state.has_eslint {
+        recommendations.push(Recommendation {
+            title: "Использовать Clippy".to_string(),
+            details: "Добавьте в CI или pre-commit: cargo clippy.".to_string(),
+            priority: "medium".to_string(),
+            effort: "low".to_string(),
+            impact: "medium".to_string(),
+        });
+    }
+
+    if !state.has_package_json && !state.has_cargo_toml && !state.has_pyproject && !state.has_requirements_txt {
+        findings.push(Finding {
+            severity: "warn".to_string(),
+            title: "Неопределён тип проекта".to_string(),
+            details: "Не найдены привычные манифесты (package.json, Cargo.toml, pyproject.toml).".to_string(),
+            rule_id: Some("structure/unknown-project".to_string()),
+            category: Some("structure".to_string()),
+            ..Default::default()
+        });
+    }
+
+    if state.file_count > 30_000 || state.dir_count > 5_000 {
+        recommendations.push(Recommendation {
+            title: "Сузить область анализа".to_string(),
+            details: "Очень много файлов или папок — добавьте исключения или выберите подпапку.".to_string(),
+            priority: "medium".to_string(),
+            effort: "low".to_string(),
+            impact: "low".to_string(),
+        });
+    }
+
+    let pass = analysis_store::deep_pass(fs, index, config, previous, job);
+    findings.extend(pass.deep.findings);
+    signals.extend(pass.deep.signals);
+    findings.extend(config.rule_set.check_project(fs, index));
+
+    // История читается с диска: для дерева пробного apply она та же.
+    if config.git_history && !job.should_stop() {
+        job.phase("git_history", "Анализирую историю git…", None);
+        signals.extend(crate::git_history::run_history_analysis(&root, job));
+    }
+
+    job.phase("report", "Формирую вывод…", None);
+    if !config.warnings.is_empty() {
+        findings.push(Finding {
+            severity: "warn".to_string(),
+            title: format!("Ошибки в {}", project_config::CONFIG_FILE),
+            details: format!("{}. Для них действуют значения профиля.", config.warnings.join("; ")),
+            rule_id: Some("analysis/config-errors".to_string()),
+            category: Some("analysis".to_string()),
+            path: Some(project_config::CONFIG_FILE.to_string()),
+            ..Default::default()
+        });
+    }
+    if !config.rule_warnings.is_empty() {
+        findings.push(Finding {
+            severity: "warn".to_string(),
+            title: "Ошибки в правилах пользователя".to_string(),
+            details: format!("{}. Эти правила не применены.", config.rule_warnings.join("; ")),
+            rule_id: Some("analysis/rule-errors".to_string()),
+            category: Some("analysis".to_string()),
+            ..Default::default()
+        });
+    }
+    let stopped = job.stopped();
+    if let Some(stop) = stopped {
+        findings.push(Finding {
+            severity: "warn".to_string(),
+            title: "Анализ неполный".to_string(),
+            details: format!(
+                "{} — отчёт построен по части проекта. Уже разобранные файлы повторный анализ возьмёт из индекса.",
+                stop_message(stop)
+            ),
+            rule_id: Some("analysis/incomplete".to_string()),
+            category: Some("analysis".to_string()),
+            ..Default::default()
+        });
+    }
+
+    let recommendations = enrich_recommendations(recommendations);
+    let project_context = build_project_context(&state, &findings, &signals);
+    let mut actions = build_actions(state.has_readme, state.has_tests_dir, state.has_gitignore);
+    actions.extend(config.rule_set.fixes(&findings));
+
+    let narrative = build_narrative(&state, &structure, &findings, &recommendations);
+
+    let report = AnalyzeReport {
+        path: path.to_string(),
+        narrative: narrative.clone(),
+        stats: stats.clone(),
+        structure: structure.clone(),
+        project_context: project_context.clone(),
+        findings: findings.clone(),
+        recommendations: recommendations.clone(),
+        actions: actions.clone(),
+        signals: signals.clone(),
+        report_md: String::new(),
+        llm_context: LlmContext {
+            concise_summary: String::new(),
+            key_risks: Vec::new(),
+            top_recommendations: Vec::new(),
+            signals: Vec::new(),
+        },
+        changes: pass.delta,
+        stopped,
+        config: config.clone(),
+    };
+    let report_md = build_markdown_report(&report);
+    let llm_context = build_llm_context(&report);
+
+    let report = AnalyzeReport {
+        path: path.to_string(),
+        narrative: report.narrative,
+        stats: report.stats,
+        structure: report.structure,
+        project_context: report.project_context,
+        findings: report.findings,
+        recommendations: report.recommendations,
+        actions: report.actions,
+        signals: report.signals,
+        report_md,
+        llm_context,
+        changes: report.changes,
+        stopped: report.stopped,
+        config: report.config,
+    };
+    Ok((report, pass.stored))
+}
+
+fn build_actions(has_readme: bool, has_tests: bool, has_gitignore: bool) -> Vec<Action> {
+    let mut actions = vec![];
+
+    if !has_readme {
+        actions.push(Action {
+            id: "add-readme".into(),
+            title: "Добавить README.md".into(),
+            description: "В проекте отсутствует README.md".into(),
+            kind: ActionKind::CreateFile,
+            path: "README.md".into(),
+            content: Some("# Project\n\nDescribe your project.\n".into()),
+            search: None,
+            anchor: None,
+            to: None,
+        });
+    }
+
+    if !has_tests {
+        actions.push(Action {
+            id: "add-tests-dir".into(),
+            title: "Создать папку tests/".into(),
+            description: "В проекте нет tests/ (минимальная структура для тестов)".into(),
+            kind: ActionKind::CreateDir,
+            path: "tests".into(),
+            content: None,
+            search: None,
+            anchor: None,
+            to: None,
+        });
+    }
+
+    if !has_gitignore {
+        actions.push(Action {
+            id: "add-gitignore".into(),
+            title: "Добавить .gitignore".into(),
+            description: "Рекомендуется добавить базовый .gitignore".into(),
+            kind: ActionKind::CreateFile,
+            path: ".gitignore".into(),
+            content: Some("node_modules/\ndist/\nbuild/\n.target/\n.DS_Store\n".into()),
+            search: None,
+            anchor: None,
+            to: None,
+        });
+    }
+
+    actions
+}
+
+fn enrich_recommendations(mut recs: Vec<Recommendation>) -> Vec<Recommendation> {
+    for r in &mut recs {
+        let (p, e, i) = if r.title.contains("README") {
+            ("high", "low", "high")
+        } else if r.title.contains("тест") || r.title.contains("тесты") || r.title.contains("Add tests") || r.title.contains("tests") {
+            ("high", "medium", "high")
+        } else if r.title.contains(".gitignore") {
+            ("medium", "low", "medium")
+        } else if r.title.contains(".env") || r.title.contains("секрет") {
+            ("high", "low", "high")
+        } else if r.title.contains("лицензи") {
+            ("low", "low", "medium")
+        } else if r.title.contains("линтер") || r.title.contains("Clippy") {
+            ("medium", "low", "medium")
+        } else {
+            (r.priority.as_str(), r.effort.as_str(), r.impact.as_str())
+        };
+        r.priority = p.to_string();
+        r.effort = e.to_string();
+        r.impact = i.to_string();
+    }
+    recs
+}
+
+fn build_project_context(
+    state: &ScanState,
+    findings: &[Finding],
+    signals: &[ProjectSignal],
+) -> ProjectContext {
+    let risk_level = if !state.env_files.is_empty()
+        || signals.iter().any(|s| s.category == "security" && s.level == "high")
+    {
+        "High"
+    } else if distinct_titles(findings).len() > 5
+        || signals.iter().any(|s| s.level == "warn")
+    {
+        "Medium"
+    } else {
+        "Low"
+    };
+
+    let complexity = if state.file_count > 5000 || state.dir_count > 500 || state.max_depth > 8 {
+        "High"
+    } else if state.file_count > 800 || state.dir_count > 120 {
+        "Medium"
+    } else {
+        "Low"
+    };
+
+    let maturity = if state.has_readme && (state.has_tests_dir || state.has_eslint) {
+        "Production-like"
+    } else if state.has_readme {
+        "MVP"
+    } else {
+        "Prototype"
+    };
+
+    let mut stack = Vec::new();
+    if state.has_package_json {
+        stack.push("Node.js".to_string());
+    }
+    if state.has_cargo_toml {
+        stack.push("Rust".to_string());
+    }
+    if state.has_vite {
+        stack.push("Vite".to_string());
+    }
+    if state.has_next {
+        stack.push("Next.js".to_string());
+    }
+    if state.has_pyproject || state.has_requirements_txt {
+        stack.push("Python".to_string());
+    }
+    if stack.is_empty() {
+        stack.push("Unknown".to_string());
+    }
+
+    let domain = if state.has_next || state.has_vite {
+        "frontend"
+    } else if state.has_cargo_toml {
+        "systems"
+    } else if state.has_package_json {
+        "fullstack"
+    } else {
+        "general"
+    }
+    .to_string();
+
+    ProjectContext {
+        stack,
+        domain,
+        maturity: maturity.to_string(),
+        complexity: complexity.to_string(),
+        risk_level: risk_level.to_string(),
+    }
+}
+
+fn build_markdown_report(report: &AnalyzeReport) -> String {
+    let mut md = String::new();
+    md.push_str("# PAPA YU — отчёт анализа проекта\n\n");
+    md.push_str(&report.narrative);
+    md.push_str("\n\n---\n\n");
+    md.push_str("## Статистика\n\n");
+    md.push_str(&format!(
+        "- Файлов: {}\n- Папок: {}\n- Max depth: {}\n- Размер: {} Б\n\n",
+        report.stats.file_count,
+        report.stats.dir_count,
+        report.stats.max_depth,
+        report.stats.total_size_bytes
+    ));
+    md.push_str("## Контекст проекта\n\n");
+    md.push_str(&format!(
+        "- Стек: {}\n- Зрелость: {}\n- Сложность: {}\n- Риск: {}\n\n",
+        report.project_context.stack.join(", "),
+        report.project_context.maturity,
+        report.project_context.complexity,
+        report.project_context.risk_level
+    ));
+    if let Some(c) = &report.changes {
+        md.push_str(&format!("## Изменения с прошлого анализа ({})\n\n", c.since));
+        md.push_str(&format!(
+            "- Добавлено: {}, удалено: {}, изменено: {}{}\n- Новых находок: {}, ушло: {}\n\n",
+            c.added.len(),
+            c.removed.len(),
+            c.modified.len(),
+            if c.truncated { " (списки обрезаны)" } else { "" },
+            c.new_findings.len(),
+            c.resolved_findings.len()
+        ));
+    }
+    if !report.findings.is_empty() {
+        md.push_str("## Находки\n\n");
+        for f in &report.findings {
+            match &f.snippet {
+                Some(snippet) => md.push_str(&format!("- **{}**: {} — `{}`\n", f.title, f.details, snippet.replace('`', "'"))),
+                None => md.push_str(&format!("- **{}**: {}\n", f.title, f.details)),
+            }
+            if let Some(hint) = &f.fix_hint {
+                md.push_str(&format!("  - Как исправить: {}\n", hint));
+            }
+        }
+        md.push('\n');
+    }
+    if !report.recommendations.is_empty() {
+        md.push_str("## Рекомендации\n\n");
+        for r in &report.recommendations {
+            md.push_str(&format!(
+                "- **{}** [{} / effort:{} / impact:{}]\n  {}\n",
+                r.title, r.priority, r.effort, r.impact, r.details
+            ));
+        }
+    }
+    md
+}
+
+fn build_llm_context(report: &AnalyzeReport) -> LlmContext {
+    let concise_summary = format!(
+        "{}; {}; {} файлов, {} папок. Риск: {}, зрелость: {}.",
+        report.structure.project_type,
+        report.structure.architecture,
+        report.stats.file_count,
+        report.stats.dir_count,
+        report.project_context.risk_level,
+        report.project_context.maturity
+    );
+    let key_risks: Vec<String> = report
+        .findings
+        .iter()
+        .filter(|f| f.severity == "high")
+        .take(MAX_KEY_RISKS)
+        .map(|f| format!("{}: {}", f.title, f.details))
+        .collect();
+    let top_recommendations: Vec<String> = report
+        .recommendations
+        .iter()
+        .take(5)
+        .map(|r| format!("[{}] {}", r.priority, r.title))
+        .collect();
+    LlmContext {
+        concise_summary,
+        key_risks,
+        top_recommendations,
+        signals: report.signals.clone(),
+    }
+}
+
+fn build_structure(state: &ScanState) -> ProjectStructure {
+    let mut project_type = String::new();
+    let mut architecture = String::new();
+    let mut structure_notes: Vec<String> = Vec::new();
+
+    let is_monorepo = state.package_json_count > 1 || state.cargo_toml_count > 1;
+
+    if state.has_cargo_toml {
+        project_type = "Rust / Cargo".to_string();
+        architecture = "Rust-проект".to_string();
+        if is_monorepo {
+            project_type = "Rust monorepo".to_string();
+        }
+    }
+    if state.has_package_json {
+        if !project_type.is_empty() {
+            project_type = format!("{} + Node", project_type);
+        } else if state.has_next {
+            project_type = "Next.js".to_string();
+            architecture = "React (Next.js) fullstack".to_string();
+        } else if state.has_vite {
+            project_type = "React + Vite".to_string();
+            architecture = "Frontend SPA (Vite)".to_string();
+        } else {
+            project_type = "Node.js".to_string();
+            architecture = "Node / frontend или backend".to_string();
+        }
+        if is_monorepo && !project_type.contains("monorepo") {
+            project_type = format!("{} (monorepo)", project_type);
+        }
+    }
+    if state.has_pyproject || state.has_requirements_txt || state.has_setup_py {
+        if !project_type.is_empty() {
+            project_type = format!("{} + Python", project_type);
+        } else {
+            project_type = "Python".to_string();
+            architecture = "Python-проект (Django/FastAPI или скрипты)".to_string();
+        }
+    }
+    if project_type.is_empty() {
+        project_type = "Неопределён".to_string();
+        architecture = "Тип по манифестам не определён".to_string();
+    }
+
+    if state.has_src && state.has_tests_dir {
+        structure_notes.push("Есть src/ и tests/ — хорошее разделение.".to_string());
+    } else if state.has_src && !state.has_tests_dir {
+        structure_notes.push("Есть src/, нет tests/ — стоит добавить тесты.".to_string());
+    }
+    if state.root_file_count >= ROOT_FILES_WARN {
+        structure_notes.push("Много файлов в корне — структура упрощённая.".to_string());
+    }
+    if state.max_depth >= MAX_DEPTH_WARN {
+        structure_notes.push("Глубокая вложенность папок.".to_string());
+    }
+    if structure_notes.is_empty() {
+        structure_notes.push("Структура без явного разделения на домены.".to_string());
+    }
+
+    ProjectStructure {
+        project_type,
+        architecture,
+        structure_notes,
+    }
+}
+
+/// Заголовки находок без повторов: находки идут по одной на совпадение.
+fn distinct_titles(findings: &[Finding]) -> Vec<&str> {
+    let mut titles: Vec<&str> = vec![];
+    for f in findings {
+        if !titles.contains(&f.title.as_str()) {
+            titles.push(&f.title);
+        }
+    }
+    titles
+}
+
+fn build_narrative(
+    state: &ScanState,
+    structure: &ProjectStructure,
+    findings: &[Finding],
+    recommendations: &[Recommendation],
+) -> String {
+    let mut parts = Vec::new();
+    parts.push("Я проанализировал ваш проект.".to_string());
+    parts.push(format!(
+        "Это {} ({}).",
+        structure.project_type.to_lowercase(),
+        structure.architecture
+    ));
+    if !structure.structure_notes.is_empty() {
+        parts.push(structure.structure_notes.join(" "));
+    }
+
+    let size_label = if state.file_count < 50 {
+        "небольшой"
+    } else if state.file_count < 500 {
+        "среднего размера"
+    } else {
+        "крупный"
+    };
+    parts.push(format!(
+        "По размеру — {} проект: {} файлов, {} папок.",
+        size_label, state.file_count, state.dir_count
+    ));
+
+    if !findings.is_empty() {
+        parts.push("".to_string());
+        parts.push("Основные проблемы:".to_string());
+        for title in distinct_titles(findings).into_iter().take(7) {
+            parts.push(format!("– {}", title));
+        }
+    }
+
+    if !recommendations.is_empty() {
+        parts.push("".to_string());
+        parts.push("Я бы рекомендовал начать с:".to_string());
+        for (i, r) in recommendations.iter().take(5).enumerate() {
+            parts.push(format!("{}. {}", i + 1, r.title));
+        }
+    }
+
+    parts.join("\n\n")
+}
+
+fn scan(index: &FileIndex) -> ScanState {
+    let mut state = ScanState::default();
+    for dir in &index.dirs {
+        scan_dir(dir, &mut state);
+    }
+    for file in &index.files {
+        scan_file(file, &mut state);
+    }
+    state
+}
+
+fn scan_dir(dir: &IndexedDir, state: &mut ScanState) {
+    state.dir_count += 1;
+    state.max_depth = state.max_depth.max(dir.depth + 1);
+    let name = dir.path.file_name().and_then(|n| n.to_str()).unwrap_or("");
+    if dir.depth == 0 {
+        state.root_dirs.insert(name.to_lowercase());
+    }
+    let name_lower = name.to_lowercase();
+    if name_lower == "src" {
+        state.has_src = true;
+    }
+    if name_lower == "tests" || name_lower == "test" || name_lower == "__tests__" {
+        state.has_tests_dir = true;
+    }
+    if name_lower == "components" {
+        state.has_components = true;
+    }
+    if name_lower == "pages" || name_lower == "app" {
+        state.has_pages = true;
+    }
+}
+
+fn scan_file(file: &IndexedFile, state: &mut ScanState) {
+    let path = &file.path;
+    state.file_count += 1;
+    if file.depth == 0 {
+        state.root_file_count += 1;
+    }
+
+    state.total_size_bytes = state.total_size_bytes.saturating_add(file.size);
+
+    if let Some(name) = path.file_name().and_then(|n| n.to_str()) {
+        let name_lower = name.to_lowercase();
+        if let Some(ext) = path.extension() {
+            let ext = ext.to_string_lossy().to_string();
+            *state.extensions.entry(ext).or_insert(0) += 1;
+        }
+
+        if name_lower == "package.json" {
+            state.has_package_json = true;
+            state.package_json_count += 1;
+        }
+        if name_lower == "cargo.toml" {
+            state.has_cargo_toml = true;
+            state.cargo_toml_count += 1;
+        }
+        if name_lower == "tsconfig.json" {
+            state.has_tsconfig = true;
+        }
+        if name_lower == "dockerfile" || name_lower == "docker-compose.yml" {
+            state.has_docker = true;
+        }
+        if name_lower.starts_with(".env") {
+            state.env_files.push(file.rel.clone());
+        }
+        if name_lower == ".gitignore" {
+            state.has_gitignore = true;
+        }
+        if name_lower == "license" || name_lower == "license.md" || name_lower.starts_with("license.") {
+            state.has_license = true;
+        }
+        if name_lower == "eslint.config.js" || name_lower == ".eslintrc" || name_lower.starts_with(".eslintrc") {
+            state.has_eslint = true;
+        }
+        if name_lower == ".prettierrc" || name_lower == "prettier.config" || name_lower.starts_with("prettier.config") {
+            state.has_prettier = true;
+        }
+        if name_lower == "next.config.js" || name_lower == "next.config.mjs" || name_lower == "next.config.ts" {
+            state.has_next = true;
+        }
+        if name_lower == "requirements.txt" {
+            state.has_requirements_txt = true;
+        }
+        if name_lower == "pyproject.toml" {
+            state.has_pyproject = true;
+        }
+        if name_lower == "setup.py" {
+            state.has_setup_py = true;
+        }
+        for m in MARKER_README {
+            if name_lower.starts_with(&m.to_lowercase()) {
+                state.has_readme = true;
+                break;
+            }
+        }
+        for m in MARKER_VITE {
+            if name_lower == *m {
+                state.has_vite = true;
+                break;
+            }
+        }
+    }
+}
@@ -0,0 +1,61 @@
+//! Папки приложения: данные (история сессий, индексы анализа) и настройки (политика
+//! путей, проверки после apply). В приложении их даёт Tauri, в CLI — те же пути,
+//! вычисленные по платформе, так что CLI и приложение видят одну историю.
+
+use std::env;
+use std::path::{Path, PathBuf};
+
+/// `identifier` из `tauri.conf.json`: имя папки приложения.
+pub const APP_IDENTIFIER: &str = "com.papa-yu";
+
+#[derive(Debug, Clone, Default)]
+pub struct AppDirs {
+    data: Option<PathBuf>,
+    config: Option<PathBuf>,
+}
+
+impl AppDirs {
+    pub fn new(data: PathBuf, config: PathBuf) -> AppDirs {
+        AppDirs {
+            data: Some(data),
+            config: Some(config),
+        }
+    }
+
+    /// Папки, которые дал Tauri; `None` — недоступна.
+    pub fn from_parts(data: Option<PathBuf>, config: Option<PathBuf>) -> AppDirs {
+        AppDirs { data, config }
+    }
+
+    /// Папки, которые выбрал бы Tauri: `<data>/com.papa-yu` и `<config>/com.papa-yu`.
+    pub fn platform() -> AppDirs {
+        AppDirs {
+            data: platform_dir("XDG_DATA_HOME", ".local/share").map(|d| d.join(APP_IDENTIFIER)),
+            config: platform_dir("XDG_CONFIG_HOME", ".config").map(|d| d.join(APP_IDENTIFIER)),
+        }
+    }
+
+    pub fn data_dir(&self) -> Result<&Path, String> {
+        self.data.as_deref().ok_or_else(|| "app_data_dir_unavailable".to_string())
+    }
+
+    pub fn config_dir(&self) -> Result<&Path, String> {
+        self.config.as_deref().ok_or_else(|| "app_config_dir_unavailable".to_string())
+    }
+}
+
+/// Папка данных или настроек пользователя: XDG в Linux, `Application Support` в macOS,
+/// `%APPDATA%` в Windows.
+fn platform_dir(xdg: &str, linux_default: &str) -> Option<PathBuf> {
+    if cfg!(windows) {
+        return env::var_os("APPDATA").map(PathBuf::from);
+    }
+    let home = env::var_os("HOME").map(PathBuf::from);
+    if cfg!(target_os = "macos") {
+        return home.map(|h| h.join("Library/Application Support"));
+    }
+    env::var_os(xdg)
+        .map(PathBuf::from)
+        .filter(|p| p.is_absolute())
+        .or_else(|| home.map(|h| h.join(linux_default)))
+}
@@ -0,0 +1,502 @@
+//! Apply: проверка действий, snapshot, журнал, проверка проекта, коммит и пробный прогон.
+
+use std::path::{Path, PathBuf};
+
+use crate::actions;
+use crate::app_dirs::AppDirs;
+use crate::file_index;
+use crate::git;
+use crate::journal::{Journal, JournalEntry};
+use crate::overlay::Overlay;
+use crate::path_policy::{self, PathPolicy};
+use crate::progress::ProgressSink;
+use crate::project_fs::DiskFs;
+use crate::session::{self, revert_snapshot, snapshot_paths};
+use crate::types::{
+    Action, ActionResult, ActionStatus, AnalyzeOptions, ApplyMode, ApplyPayload, ApplyResult, DryRunAnalysis, Finding, GitApplyResult,
+    ProjectSignal,
+};
+use crate::validate;
+use crate::verify;
+
+/// Проверка, snapshot, применение, проверка проекта и коммит; `payload.dry_run` — пробный прогон.
+pub fn apply(sink: &dyn ProgressSink, dirs: &AppDirs, payload: ApplyPayload) -> ApplyResult {
+    let project_root = PathBuf::from(&payload.path);
+    if !project_root.exists() || !project_root.is_dir() {
+        return ApplyResult {
+            skipped: action_ids(&payload.actions),
+            ..ApplyResult::failed("path_invalid", "PATH_INVALID")
+        };
+    }
+
+    let policy = path_policy::load(dirs);
+    let payload = match validate::validate(&project_root, &policy, &payload.actions) {
+        Ok(actions) => ApplyPayload { actions, ..payload },
+        Err(issues) => {
+            return ApplyResult {
+                skipped: action_ids(&payload.actions),
+                issues,
+                dry_run: payload.dry_run,
+                ..ApplyResult::failed("validation_failed", "VALIDATION_FAILED")
+            };
+        }
+    };
+
+    if payload.dry_run {
+        return dry_run(sink, dirs, &project_root, &policy, &payload);
+    }
+
+    // Git проверяется до любых изменений: грязное дерево без allow_dirty — отказ.
+    let repo = match &payload.git {
+        Some(opts) => match git::inspect(&project_root) {
+            Ok(repo) if !repo.dirty.is_empty() && !opts.allow_dirty => {
+                let shown: Vec<&str> = repo.dirty.iter().take(10).map(String::as_str).collect();
+                let error = format!("git_worktree_dirty: {}", shown.join(", "));
+                return ApplyResult {
+                    skipped: action_ids(&payload.actions),
+                    git: Some(GitApplyResult {
+                        commit: None,
+                        branch: None,
+                        dirty: repo.dirty,
+                    }),
+                    ..ApplyResult::failed(error, "GIT_DIRTY")
+                };
+            }
+            Ok(repo) => {
+                if !repo.dirty.is_empty() {
+                    sink.message(&format!("Внимание: в git есть незакоммиченные изменения ({} файлов).", repo.dirty.len()));
+                }
+                Some(repo)
+            }
+            Err(e) => {
+                return ApplyResult {
+                    skipped: action_ids(&payload.actions),
+                    ..ApplyResult::failed(e.error, e.code)
+                };
+            }
+        },
+        None => None,
+    };
+
+    let history = match session::history_dir(dirs) {
+        Ok(d) => d,
+        Err(e) => return ApplyResult::failed(e, "APP_DATA_DIR"),
+    };
+
+    let (session_id, session_dir) = match session::create_session_dir(&history) {
+        Ok(v) => v,
+        Err(e) => return ApplyResult::failed(e, "HISTORY_CREATE_FAILED"),
+    };
+
+    let mut journal = match Journal::create(&session_dir).and_then(|mut j| {
+        j.append(&JournalEntry::Begin {
+            project_root: session::project_key(&project_root),
+            started_at: session::now_rfc3339(),
+            payload: payload.clone(),
+        })
+        .map(|_| j)
+    }) {
+        Ok(j) => j,
+        Err(e) => {
+            return ApplyResult {
+                session_id,
+                skipped: action_ids(&payload.actions),
+                ..ApplyResult::failed(e, "JOURNAL_WRITE_FAILED")
+            };
+        }
+    };
+
+    sink.message("Готовлю откат (snapshot)…");
+
+    let targets = session::action_paths(&payload.actions);
+    let before = session::digest_paths(&project_root, &targets);
+
+    let snapshot = snapshot_paths(&session_dir, &project_root, &session::snapshot_targets(&payload.actions))
+        .and_then(|_| journal.append(&JournalEntry::Snapshot { before: before.clone() }));
+    if let Err(e) = snapshot {
+        let _ = journal.append(&JournalEntry::RolledBack);
+        let code = if e.starts_with("snapshot_too_large") {
+            "SNAPSHOT_TOO_LARGE"
+        } else {
+            "SNAPSHOT_FAILED"
+        };
+        return ApplyResult {
+            session_id,
+            skipped: action_ids(&payload.actions),
+            ..ApplyResult::failed(e, code)
+        };
+    }
+
+    sink.message("Применяю изменения…");
+    file_index::forget();
+
+    let best_effort = payload.mode == ApplyMode::BestEffort;
+    let mut results: Vec<ActionResult> = vec![];
+    let mut failed_paths: Vec<&str> = vec![];
+    // Ошибка, из-за которой откатывается весь набор: (текст, код для ApplyResult).
+    let mut fatal: Option<(String, &'static str)> = None;
+
+    for a in &payload.actions {
+        let paths = actions::touched_paths(a);
+        let depends_on_failed = paths
+            .iter()
+            .any(|p| failed_paths.iter().any(|f| session::paths_overlap(p, f)));
+        if depends_on_failed {
+            if let Err(e) = journal.append(&JournalEntry::Skipped {
+                action_id: a.id.clone(),
+            }) {
+                fatal = Some((e, "JOURNAL_WRITE_FAILED"));
+                break;
+            }
+            results.push(action_result(
+                a,
+                ActionStatus::Skipped,
+                Some("depends_on_failed_action".into()),
+                Some("DEPENDENCY_FAILED"),
+            ));
+            continue;
+        }
+
+        match actions::execute(&project_root, &policy, a) {
+            Ok(()) => {
+                if let Err(e) = journal.append(&JournalEntry::Applied {
+                    action_id: a.id.clone(),
+                }) {
+                    results.push(action_result(a, ActionStatus::Applied, None, None));
+                    fatal = Some((e, "JOURNAL_WRITE_FAILED"));
+                    break;
+                }
+                results.push(action_result(a, ActionStatus::Applied, None, None));
+            }
+            Err(e) => {
+                let code = actions::error_code(&e);
+                results.push(action_result(a, ActionStatus::Failed, Some(e.clone()), Some(code)));
+                if !best_effort {
+                    let total = if code == "PATCH_FAILED" {
+                        "PATCH_FAILED_ROLLED_BACK"
+                    } else {
+                        "APPLY_FAILED_ROLLED_BACK"
+                    };
+                    fatal = Some((e, total));
+                    break;
+                }
+                sink.message(&format!("Не удалось: {} — {}", a.title, e));
+                failed_paths.extend(paths);
+                if let Err(e) = journal.append(&JournalEntry::Skipped {
+                    action_id: a.id.clone(),
+                }) {
+                    fatal = Some((e, "JOURNAL_WRITE_FAILED"));
+                    break;
+                }
+            }
+        }
+    }
+
+    let applied: Vec<String> = results
+        .iter()
+        .filter(|r| r.status == ActionStatus::Applied)
+        .map(|r| r.action_id.clone())
+        .collect();
+
+    if fatal.is_some() || applied.is_empty() {
+        sink.message("Обнаружена ошибка. Откатываю изменения…");
+        let _ = revert_snapshot(&session_dir, &project_root);
+        let _ = journal.append(&JournalEntry::RolledBack);
+        for r in results.iter_mut().filter(|r| r.status == ActionStatus::Applied) {
+            r.status = ActionStatus::RolledBack;
+        }
+        for a in payload.actions.iter().skip(results.len()) {
+            results.push(action_result(a, ActionStatus::Skipped, None, None));
+        }
+        let (err, code) = fatal.unwrap_or_else(|| ("no_action_applied".into(), "APPLY_FAILED"));
+        return ApplyResult {
+            session_id,
+            skipped: action_ids(&payload.actions),
+            results,
+            ..ApplyResult::failed(err, code)
+        };
+    }
+
+    let verify_commands = verify::load(dirs, &project_root);
+    let mut verification = vec![];
+    if !verify_commands.is_empty() {
+        sink.message("Проверяю проект после изменений…");
+        verification = verify::run_all(&verify_commands, &project_root, &session_dir, &|line| {
+            sink.message(line);
+        });
+        if let Some(failed) = verification.iter().find(|v| !v.ok) {
+            sink.message("Проверка не прошла. Откатываю изменения…");
+            let error = failed.error.clone().unwrap_or_else(|| "verify_failed".into());
+            let _ = revert_snapshot(&session_dir, &project_root);
+            let _ = journal.append(&JournalEntry::RolledBack);
+            for r in results.iter_mut().filter(|r| r.status == ActionStatus::Applied) {
+                r.status = ActionStatus::RolledBack;
+            }
+            return ApplyResult {
+                session_id,
+                skipped: action_ids(&payload.actions),
+                results,
+                verification,
+                ..ApplyResult::failed(error, "VERIFY_FAILED_ROLLED_BACK")
+            };
+        }
+    }
+
+    let skipped: Vec<String> = payload
+        .actions
+        .iter()
+        .map(|a| a.id.clone())
+        .filter(|id| !applied.contains(id))
+        .collect();
+    let failed = results.iter().filter(|r| r.status == ActionStatus::Failed).count();
+    // Упавшее действие могло успеть что-то изменить; не начатые — нет.
+    let untouched: Vec<String> = results
+        .iter()
+        .filter(|r| r.status == ActionStatus::Skipped)
+        .map(|r| r.action_id.clone())
+        .collect();
+
+    let finished = session::finalize_session(
+        &session_dir,
+        &project_root,
+        &session_id,
+        env!("CARGO_PKG_VERSION"),
+        &payload,
+        before,
+        &skipped,
+        &untouched,
+    )
+    .and_then(|_| journal.append(&JournalEntry::Commit));
+    if let Err(e) = finished {
+        return ApplyResult {
+            ok: true,
+            session_id,
+            applied,
+            skipped,
+            results,
+            verification,
+            ..ApplyResult::failed(e, "SESSION_META_WRITE_FAILED")
+        };
+    }
+
+    // Коммит — последним шагом: если он не удался, изменения остаются применёнными
+    // и откатываются по снимку, как без git.
+    let mut git_error: Option<(String, &'static str)> = None;
+    let git_result = match (&payload.git, repo) {
+        (Some(opts), Some(repo)) => {
+            sink.message("Коммичу изменения в git…");
+            let done: Vec<Action> = payload
+                .actions
+                .iter()
+                .filter(|a| applied.contains(&a.id))
+                .cloned()
+                .collect();
+            let paths: Vec<String> = done
+                .iter()
+                .flat_map(actions::touched_paths)
+                .map(String::from)
+                .collect();
+            let committed = git::commit_session(&project_root, &repo, &session_id, &paths, &done, opts.branch)
+                .and_then(|g| {
+                    let mut info = session::read_session_info(&session_dir)
+                        .map_err(|e| git::GitError { error: e, code: "SESSION_META_WRITE_FAILED" })?;
+                    info.git = Some(g.clone());
+                    session::write_session_info(&session_dir, &info)
+                        .map_err(|e| git::GitError { error: e, code: "SESSION_META_WRITE_FAILED" })?;
+                    Ok(g)
+                });
+            match committed {
+                Ok(g) => Some(GitApplyResult {
+                    commit: Some(g.commit),
+                    branch: g.branch,
+                    dirty: repo.dirty,
+                }),
+                Err(e) => {
+                    sink.message(&format!("Коммит не создан: {}", e.error));
+                    git_error = Some((e.error, e.code));
+                    Some(GitApplyResult {
+                        commit: None,
+                        branch: None,
+                        dirty: repo.dirty,
+                    })
+                }
+            }
+        }
+        _ => None,
+    };
+
+    if skipped.is_empty() {
+        sink.message("Готово. Изменения применены.");
+    } else {
+        sink.message(&format!("Готово. Применено {} из {} изменений.", applied.len(), payload.actions.len()));
+    }
+
+    let (error, error_code) = match git_error {
+        Some((e, code)) => (Some(e), Some(code.to_string())),
+        None if !skipped.is_empty() => (
+            Some(format!("{} failed, {} skipped", failed, skipped.len() - failed)),
+            Some("APPLY_PARTIAL".to_string()),
+        ),
+        None => (None, None),
+    };
+
+    ApplyResult {
+        ok: true,
+        session_id,
+        applied,
+        error,
+        error_code,
+        skipped,
+        undo_available: true,
+        issues: vec![],
+        results,
+        dry_run: false,
+        analysis: None,
+        verification,
+        git: git_result,
+    }
+}
+
+fn action_ids(actions: &[Action]) -> Vec<String> {
+    actions.iter().map(|a| a.id.clone()).collect()
+}
+
+fn action_result(a: &Action, status: ActionStatus, error: Option<String>, code: Option<&str>) -> ActionResult {
+    ActionResult {
+        action_id: a.id.clone(),
+        status,
+        paths: actions::touched_paths(a).into_iter().map(String::from).collect(),
+        error,
+        error_code: code.map(String::from),
+    }
+}
+
+/// Пробный прогон: действия выполняются над `Overlay`, упавшие пропускаются вместе
+/// с зависимыми (как в best effort), чтобы найти все ошибки сразу. Снимок, журнал
+/// и сессия не создаются, проект не меняется.
+fn dry_run(sink: &dyn ProgressSink, dirs: &AppDirs, project_root: &Path, policy: &PathPolicy, payload: &ApplyPayload) -> ApplyResult {
+    sink.message("Пробный прогон изменений…");
+
+    let mut overlay = Overlay::new(project_root);
+    let mut results: Vec<ActionResult> = vec![];
+    let mut failed_paths: Vec<&str> = vec![];
+    for a in &payload.actions {
+        let paths = actions::touched_paths(a);
+        if paths
+            .iter()
+            .any(|p| failed_paths.iter().any(|f| session::paths_overlap(p, f)))
+        {
+            results.push(action_result(
+                a,
+                ActionStatus::Skipped,
+                Some("depends_on_failed_action".into()),
+                Some("DEPENDENCY_FAILED"),
+            ));
+            continue;
+        }
+        match overlay.execute(policy, a) {
+            Ok(()) => results.push(action_result(a, ActionStatus::Applied, None, None)),
+            Err(e) => {
+                let code = actions::error_code(&e);
+                results.push(action_result(a, ActionStatus::Failed, Some(e), Some(code)));
+                failed_paths.extend(paths);
+            }
+        }
+    }
+
+    let applied: Vec<String> = results
+        .iter()
+        .filter(|r| r.status == ActionStatus::Applied)
+        .map(|r| r.action_id.clone())
+        .collect();
+    let skipped: Vec<String> = payload
+        .actions
+        .iter()
+        .map(|a| a.id.clone())
+        .filter(|id| !applied.contains(id))
+        .collect();
+    let failed = results.iter().filter(|r| r.status == ActionStatus::Failed).count();
+
+    // Настоящий apply не начнётся, если snapshot удаляемых и переносимых папок слишком велик.
+    let snapshot_bytes: u64 = session::snapshot_targets(&payload.actions)
+        .iter()
+        .filter(|t| t.deep)
+        .map(|t| session::dir_size(&project_root.join(&t.path)).1)
+        .sum();
+
+    let (mut error, mut error_code) = if failed > 0 {
+        (
+            Some(format!("dry_run_failed: {} of {} actions", failed, payload.actions.len())),
+            Some("DRY_RUN_FAILED".to_string()),
+        )
+    } else if snapshot_bytes > session::MAX_SNAPSHOT_DIR_BYTES {
+        (
+            Some(format!("snapshot_too_large: {} bytes", snapshot_bytes)),
+            Some("SNAPSHOT_TOO_LARGE".to_string()),
+        )
+    } else {
+        (None, None)
+    };
+    let ok = error.is_none();
+
+    let analysis = if payload.analyze {
+        sink.message("Сравниваю анализ проекта до и после…");
+        match compare_analysis(dirs, &payload.path, &overlay) {
+            Ok(a) => Some(a),
+            Err(e) => {
+                if error.is_none() {
+                    error = Some(format!("analysis_failed: {}", e));
+                    error_code = Some("ANALYSIS_FAILED".into());
+                }
+                None
+            }
+        }
+    } else {
+        None
+    };
+
+    sink.message(if ok {
+        "Пробный прогон: все изменения применимы."
+    } else {
+        "Пробный прогон: есть ошибки, проект не изменён."
+    });
+
+    ApplyResult {
+        ok,
+        session_id: String::new(),
+        applied,
+        skipped,
+        error,
+        error_code,
+        undo_available: false,
+        issues: vec![],
+        results,
+        dry_run: true,
+        analysis,
+        verification: vec![],
+        git: None,
+    }
+}
+
+/// Анализ проекта на диске и дерева после пробного прогона; сравниваются находки и сигналы.
+fn compare_analysis(dirs: &AppDirs, path: &str, overlay: &Overlay) -> Result<DryRunAnalysis, String> {
+    let options = AnalyzeOptions::default();
+    let before = crate::analyze::analyze(&DiskFs, dirs, path, &options)?;
+    let after = crate::analyze::analyze(overlay, dirs, path, &options)?;
+
+    let only_signals = |xs: &[ProjectSignal], ys: &[ProjectSignal]| -> Vec<ProjectSignal> {
+        xs.iter().filter(|x| !ys.contains(x)).cloned().collect()
+    };
+
+    Ok(DryRunAnalysis {
+        findings_before: before.findings.len(),
+        findings_after: after.findings.len(),
+        new_findings: Finding::unmatched(&after.findings, &before.findings),
+        resolved_findings: Finding::unmatched(&before.findings, &after.findings),
+        new_signals: only_signals(&after.signals, &before.signals),
+        resolved_signals: only_signals(&before.signals, &after.signals),
+        risk_before: before.project_context.risk_level,
+        risk_after: after.project_context.risk_level,
+        stats_after: after.stats,
+    })
+}
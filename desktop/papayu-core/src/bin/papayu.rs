@@ -0,0 +1,3 @@
+fn main() {
+    std::process::exit(papayu_core::cli::run(std::env::args().skip(1)));
+}
@@ -0,0 +1,377 @@
+//! `papayu` — анализ, отчёты, предпросмотр, apply и откат из терминала, для CI.
+//! Работает с тем же ядром и той же историей сессий, что и приложение, но без окна:
+//! ход работы пишется в stderr, результат — в stdout или в файл `--out`.
+//!
+//! Коды выхода: 0 — успех; 1 — есть находки не ниже `--fail-on` (analyze, report) или
+//! действие не выполнено (preview, apply, undo); 2 — ошибка запуска или ввода.
+
+use std::collections::HashMap;
+use std::fs;
+use std::io::{self, Write};
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+
+use serde::Serialize;
+
+use crate::app_dirs::AppDirs;
+use crate::preview::PreviewPayload;
+use crate::progress::ProgressSink;
+use crate::report_html;
+use crate::sarif;
+use crate::types::{Action, AnalysisProfile, AnalyzeOptions, AnalyzeProgress, AnalyzeReport, ApplyMode, ApplyPayload, Finding, GitApply, UndoMode};
+use crate::undo::SessionPayload;
+
+const EXIT_OK: i32 = 0;
+const EXIT_FAILED: i32 = 1;
+const EXIT_ERROR: i32 = 2;
+
+const USAGE: &str = "\
+Использование: papayu <команда> [параметры]
+
+  analyze <путь>   анализ проекта: сводка в stdout, полный отчёт JSON — в --out
+      --profile quick|standard|exhaustive   профиль вместо указанного в .papayu.toml
+      --git-history                         анализ истории git
+      --fail-on high|warn|info|never        порог находок для кода 1 (по умолчанию high)
+      --out <файл>
+  report <путь> --format md|json|sarif|html
+      --from <report.json>                  отчёт прошлого `analyze --out` вместо нового анализа
+      --profile, --git-history, --fail-on, --out — как у analyze
+  preview <путь> --actions <actions.json>   diff действий, проект не меняется
+  apply <путь> --actions <actions.json>
+      --dry-run [--analyze]                 пробный прогон в памяти [со сравнением анализа]
+      --best-effort                         пропускать ошибочные действия
+      --git [--git-branch] [--allow-dirty]  закоммитить изменения [в новой ветке]
+  undo <путь>                               откат последней сессии проекта
+      --session <id>                        откат указанной сессии
+      --mode abort|force|merge              файлы, изменённые после apply (по умолчанию abort)
+
+Коды выхода: 0 — успех, 1 — находки не ниже порога или действие не выполнено, 2 — ошибка.";
+
+/// Параметры со значением; остальные `--…` — флаги.
+const VALUE_OPTIONS: &[&str] = &["profile", "fail-on", "out", "format", "from", "actions", "session", "mode"];
+
+const FLAGS: &[&str] = &["git-history", "dry-run", "analyze", "best-effort", "git", "git-branch", "allow-dirty", "help"];
+
+struct Args {
+    command: String,
+    path: Option<String>,
+    values: HashMap<String, String>,
+    flags: Vec<String>,
+}
+
+impl Args {
+    fn parse(args: impl IntoIterator<Item = String>) -> Result<Args, String> {
+        let mut it = args.into_iter();
+        let command = it.next().ok_or("не указана команда")?;
+        let mut parsed = Args {
+            command,
+            path: None,
+            values: HashMap::new(),
+            flags: vec![],
+        };
+        while let Some(arg) = it.next() {
+            let Some(name) = arg.strip_prefix("--") else {
+                if parsed.path.replace(arg.clone()).is_some() {
+                    return Err(format!("лишний аргумент: {}", arg));
+                }
+                continue;
+            };
+            let (name, inline) = match name.split_once('=') {
+                Some((n, v)) => (n, Some(v.to_string())),
+                None => (name, None),
+            };
+            if VALUE_OPTIONS.contains(&name) {
+                let value = match inline {
+                    Some(v) => v,
+                    None => it.next().ok_or_else(|| format!("--{}: нет значения", name))?,
+                };
+                parsed.values.insert(name.to_string(), value);
+            } else if FLAGS.contains(&name) && inline.is_none() {
+                parsed.flags.push(name.to_string());
+            } else {
+                return Err(format!("неизвестный параметр: {}", arg));
+            }
+        }
+        Ok(parsed)
+    }
+
+    fn value(&self, name: &str) -> Option<&str> {
+        self.values.get(name).map(String::as_str)
+    }
+
+    fn flag(&self, name: &str) -> bool {
+        self.flags.iter().any(|f| f == name)
+    }
+
+    fn path(&self) -> Result<&str, String> {
+        self.path.as_deref().ok_or_else(|| "не указан путь к проекту".to_string())
+    }
+}
+
+/// Ход работы в stderr: для анализа — строка на каждую фазу, без потока по файлам.
+struct Terminal {
+    phase: Mutex<String>,
+}
+
+impl ProgressSink for Terminal {
+    fn analysis(&self, progress: &AnalyzeProgress) {
+        let mut phase = self.phase.lock().unwrap_or_else(|e| e.into_inner());
+        if *phase != progress.phase {
+            phase.clone_from(&progress.phase);
+            eprintln!("{}", progress.message);
+        }
+    }
+
+    fn message(&self, text: &str) {
+        eprintln!("{}", text);
+    }
+}
+
+/// Точка входа `papayu`: аргументы без имени программы, возвращает код выхода.
+pub fn run(args: impl IntoIterator<Item = String>) -> i32 {
+    let args = match Args::parse(args) {
+        Ok(a) if a.flag("help") || a.command == "help" => {
+            println!("{}", USAGE);
+            return EXIT_OK;
+        }
+        Ok(a) => a,
+        Err(e) => return usage_error(&e),
+    };
+    let sink = Arc::new(Terminal {
+        phase: Mutex::new(String::new()),
+    });
+    let dirs = AppDirs::platform();
+    let result = match args.command.as_str() {
+        "analyze" => analyze(&args, sink, &dirs),
+        "report" => report(&args, sink, &dirs),
+        "preview" => preview(&args, sink.as_ref(), &dirs),
+        "apply" => apply(&args, sink.as_ref(), &dirs),
+        "undo" => undo(&args, sink.as_ref(), &dirs),
+        other => return usage_error(&format!("неизвестная команда: {}", other)),
+    };
+    result.unwrap_or_else(|e| {
+        eprintln!("papayu: {}", e);
+        EXIT_ERROR
+    })
+}
+
+fn usage_error(error: &str) -> i32 {
+    eprintln!("papayu: {}\n\n{}", error, USAGE);
+    EXIT_ERROR
+}
+
+/// Ранг уровня находки: `info` < `warn` < `high`.
+fn severity_rank(severity: &str) -> u8 {
+    match severity {
+        "high" => 3,
+        "warn" => 2,
+        _ => 1,
+    }
+}
+
+/// Порог `--fail-on`; `never` — код 1 не возвращается никогда.
+fn fail_threshold(args: &Args) -> Result<Option<u8>, String> {
+    match args.value("fail-on").unwrap_or("high") {
+        "never" => Ok(None),
+        s @ ("high" | "warn" | "info") => Ok(Some(severity_rank(s))),
+        other => Err(format!("--fail-on: неизвестный уровень {}", other)),
+    }
+}
+
+fn findings_exit(findings: &[Finding], threshold: Option<u8>) -> i32 {
+    match threshold {
+        Some(t) if findings.iter().any(|f| severity_rank(&f.severity) >= t) => EXIT_FAILED,
+        _ => EXIT_OK,
+    }
+}
+
+fn run_analysis(args: &Args, sink: Arc<Terminal>, dirs: &AppDirs) -> Result<AnalyzeReport, String> {
+    let profile = match args.value("profile") {
+        None => None,
+        Some("quick") => Some(AnalysisProfile::Quick),
+        Some("standard") => Some(AnalysisProfile::Standard),
+        Some("exhaustive") => Some(AnalysisProfile::Exhaustive),
+        Some(other) => return Err(format!("--profile: неизвестный профиль {}", other)),
+    };
+    let options = AnalyzeOptions {
+        git_history: args.flag("git-history"),
+        profile,
+    };
+    let report = crate::analyze::run_analysis(sink, dirs, args.path()?, &options, None)?;
+    for warning in &report.config.warnings {
+        eprintln!(".papayu.toml: {}", warning);
+    }
+    if let Some(stop) = report.stopped {
+        eprintln!("Анализ остановлен раньше времени ({:?}): отчёт неполный.", stop);
+    }
+    Ok(report)
+}
+
+/// Текст в `--out` или в stdout.
+fn output(args: &Args, text: &str) -> Result<(), String> {
+    match args.value("out") {
+        Some(out) if Path::new(out).is_dir() => Err(format!("--out: {} — папка", out)),
+        Some(out) => crate::actions::write_atomic(Path::new(out), text.as_bytes()),
+        None => writeln!(io::stdout(), "{}", text.trim_end()).map_err(|e| e.to_string()),
+    }
+}
+
+fn to_json(value: &impl Serialize) -> Result<String, String> {
+    serde_json::to_string_pretty(value).map_err(|e| e.to_string())
+}
+
+fn analyze(args: &Args, sink: Arc<Terminal>, dirs: &AppDirs) -> Result<i32, String> {
+    let threshold = fail_threshold(args)?;
+    let report = run_analysis(args, sink, dirs)?;
+    if args.value("out").is_some() {
+        output(args, &to_json(&report)?)?;
+    }
+    let count = |s: &str| report.findings.iter().filter(|f| f.severity == s).count();
+    println!(
+        "{}: файлов {}, находок {} (high {}, warn {}, info {}), риск {}",
+        report.path,
+        report.stats.file_count,
+        report.findings.len(),
+        count("high"),
+        count("warn"),
+        count("info"),
+        report.project_context.risk_level
+    );
+    for f in report.findings.iter().filter(|f| f.severity != "info") {
+        let place = match (&f.path, f.start_line) {
+            (Some(p), Some(l)) => format!(" {}:{}", p, l),
+            (Some(p), None) => format!(" {}", p),
+            _ => String::new(),
+        };
+        println!("  {:<4} {}{} — {}", f.severity, f.rule_id.as_deref().unwrap_or("-"), place, f.title);
+    }
+    Ok(findings_exit(&report.findings, threshold))
+}
+
+fn report(args: &Args, sink: Arc<Terminal>, dirs: &AppDirs) -> Result<i32, String> {
+    let threshold = fail_threshold(args)?;
+    let format = args.value("format").ok_or("не указан --format (md|json|sarif|html)")?;
+    if !matches!(format, "md" | "json" | "sarif" | "html") {
+        return Err(format!("--format: неизвестный формат {}", format));
+    }
+    let report = match args.value("from") {
+        Some(from) => {
+            let text = fs::read_to_string(from).map_err(|e| format!("{}: {}", from, e))?;
+            serde_json::from_str::<AnalyzeReport>(&text).map_err(|e| format!("{}: {}", from, e))?
+        }
+        None => run_analysis(args, sink, dirs)?,
+    };
+    let text = match format {
+        "md" => report.report_md.clone(),
+        "json" => to_json(&report)?,
+        "sarif" => to_json(&sarif::to_sarif(&report))?,
+        _ => report_html::to_html(&report),
+    };
+    output(args, &text)?;
+    Ok(findings_exit(&report.findings, threshold))
+}
+
+fn read_actions(args: &Args) -> Result<Vec<Action>, String> {
+    let file = args.value("actions").ok_or("не указан --actions <actions.json>")?;
+    let text = fs::read_to_string(file).map_err(|e| format!("{}: {}", file, e))?;
+    serde_json::from_str(&text).map_err(|e| format!("{}: {}", file, e))
+}
+
+fn preview(args: &Args, sink: &dyn ProgressSink, dirs: &AppDirs) -> Result<i32, String> {
+    let payload = PreviewPayload {
+        path: args.path()?.to_string(),
+        actions: read_actions(args)?,
+    };
+    let result = crate::preview::preview(sink, dirs, payload);
+    output(args, &to_json(&result)?)?;
+    Ok(if result.ok { EXIT_OK } else { EXIT_FAILED })
+}
+
+fn apply(args: &Args, sink: &dyn ProgressSink, dirs: &AppDirs) -> Result<i32, String> {
+    let git = (args.flag("git") || args.flag("git-branch")).then(|| GitApply {
+        branch: args.flag("git-branch"),
+        allow_dirty: args.flag("allow-dirty"),
+    });
+    let payload = ApplyPayload {
+        path: args.path()?.to_string(),
+        actions: read_actions(args)?,
+        mode: if args.flag("best-effort") {
+            ApplyMode::BestEffort
+        } else {
+            ApplyMode::AllOrNothing
+        },
+        dry_run: args.flag("dry-run"),
+        analyze: args.flag("analyze"),
+        git,
+    };
+    let result = crate::apply::apply(sink, dirs, payload);
+    output(args, &to_json(&result)?)?;
+    Ok(if result.ok { EXIT_OK } else { EXIT_FAILED })
+}
+
+fn undo(args: &Args, sink: &dyn ProgressSink, dirs: &AppDirs) -> Result<i32, String> {
+    let path = args.path()?.to_string();
+    let mode = match args.value("mode") {
+        None => None,
+        Some("abort") => Some(UndoMode::Abort),
+        Some("force") => Some(UndoMode::Force),
+        Some("merge") => Some(UndoMode::Merge),
+        Some(other) => return Err(format!("--mode: неизвестный режим {}", other)),
+    };
+    let result = match args.value("session") {
+        Some(id) => crate::undo::undo(
+            sink,
+            dirs,
+            SessionPayload {
+                path,
+                session_id: id.to_string(),
+                mode,
+            },
+        ),
+        None => crate::undo::undo_latest(sink, dirs, &path, mode),
+    };
+    output(args, &to_json(&result)?)?;
+    Ok(if result.ok { EXIT_OK } else { EXIT_FAILED })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(list: &[&str]) -> Result<Args, String> {
+        Args::parse(list.iter().map(|s| s.to_string()))
+    }
+
+    #[test]
+    fn parses_values_flags_and_path() {
+        let a = args(&["report", "--format=sarif", "proj", "--fail-on", "warn", "--git-history"]).unwrap();
+        assert_eq!(a.command, "report");
+        assert_eq!(a.path().unwrap(), "proj");
+        assert_eq!(a.value("format"), Some("sarif"));
+        assert_eq!(a.value("fail-on"), Some("warn"));
+        assert!(a.flag("git-history"));
+        assert!(!a.flag("dry-run"));
+
+        assert!(args(&["analyze", "a", "b"]).is_err());
+        assert!(args(&["analyze", "--out"]).is_err());
+        assert!(args(&["analyze", "--unknown"]).is_err());
+        assert!(args(&["apply", "--dry-run=yes"]).is_err());
+    }
+
+    #[test]
+    fn exit_code_follows_severity_threshold() {
+        let finding = |severity: &str| Finding {
+            severity: severity.to_string(),
+            ..Default::default()
+        };
+        let findings = vec![finding("info"), finding("warn")];
+
+        let threshold = |level: &str| fail_threshold(&args(&["analyze", "--fail-on", level]).unwrap()).unwrap();
+        assert_eq!(findings_exit(&findings, threshold("high")), EXIT_OK);
+        assert_eq!(findings_exit(&findings, threshold("warn")), EXIT_FAILED);
+        assert_eq!(findings_exit(&findings, threshold("info")), EXIT_FAILED);
+        assert_eq!(findings_exit(&findings, threshold("never")), EXIT_OK);
+        assert_eq!(findings_exit(&findings, fail_threshold(&args(&["analyze"]).unwrap()).unwrap()), EXIT_OK);
+        assert!(fail_threshold(&args(&["analyze", "--fail-on", "low"]).unwrap()).is_err());
+    }
+}
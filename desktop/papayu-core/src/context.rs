@@ -0,0 +1,101 @@
+//! Контекст проекта для LLM: приоритетные файлы и код в пределах лимитов `context_*`.
+
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::analysis_job::Job;
+use crate::file_index::{self, FileIndex, IndexedFile};
+use crate::project_config;
+use crate::project_fs::DiskFs;
+use crate::types::AnalysisProfile;
+
+// Лимиты контекста — из профиля и `.papayu.toml` (`context_*`).
+
+const CODE_EXTENSIONS: &[&str] = &[
+    "js","jsx","ts","tsx","mjs","cjs","py","rs","go","rb","php","java","kt",
+    "sh","bash","yml","yaml","toml","json","md","txt","sql","graphql",
+    "css","scss","html","vue","svelte",
+];
+
+const PRIORITY_FILES: &[&str] = &[
+    "package.json","Cargo.toml","pyproject.toml","requirements.txt",
+    "README.md","readme.md","tsconfig.json",
+    "next.config.js","next.config.ts","vite.config.ts","vite.config.js",
+    "Dockerfile","docker-compose.yml",".env.example",".gitignore",
+];
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ProjectContextRequest {
+    pub path: String,
+    /// Профиль вместо указанного в `.papayu.toml`.
+    #[serde(default)]
+    pub profile: Option<AnalysisProfile>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct FileContext { pub path: String, pub content: String, pub lines: u32 }
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ProjectContextResponse {
+    pub ok: bool, pub files: Vec<FileContext>,
+    pub total_files: u32, pub total_bytes: u32,
+    pub truncated: bool, pub error: Option<String>,
+}
+
+pub async fn collect(request: ProjectContextRequest) -> Result<ProjectContextResponse, String> {
+    let root = Path::new(&request.path);
+    if !root.exists() || !root.is_dir() {
+        return Ok(ProjectContextResponse { ok: false, files: vec![], total_files: 0, total_bytes: 0, truncated: false, error: Some(format!("Путь не существует: {}", request.path)) });
+    }
+    let mut files: Vec<FileContext> = Vec::new();
+    let mut total_bytes: usize = 0;
+    let mut truncated = false;
+    let config = project_config::load(&DiskFs, root, request.profile);
+    let limits = &config.limits;
+
+    // После analyze_project индекс уже есть: дерево не обходится второй раз.
+    let index = file_index::remembered(root).unwrap_or_else(|| {
+        let index = FileIndex::build(&DiskFs, root, &Job::detached(limits.max_files, Duration::from_secs(limits.max_duration_secs)), &config.exclude);
+        file_index::remember(&index);
+        Arc::new(index)
+    });
+
+    for pf in PRIORITY_FILES {
+        if let Some(fc) = index.get(pf).and_then(|f| read_file_ctx(f, limits.context_max_file_bytes)) { total_bytes += fc.content.len(); files.push(fc); }
+    }
+
+    let mut all: Vec<&IndexedFile> = index.files.iter()
+        .filter(|f| f.depth <= limits.context_max_depth && CODE_EXTENSIONS.contains(&f.ext.as_str()))
+        .take(limits.context_max_files)
+        .collect();
+    all.sort_by(|a, b| {
+        let a_src = a.rel.contains("src/");
+        let b_src = b.rel.contains("src/");
+        match (a_src, b_src) {
+            (true, false) => std::cmp::Ordering::Less,
+            (false, true) => std::cmp::Ordering::Greater,
+            _ => a.size.cmp(&b.size),
+        }
+    });
+
+    for f in all {
+        if total_bytes >= limits.context_max_bytes { truncated = true; break; }
+        if files.iter().any(|fc| fc.path == f.rel) { continue; }
+        if let Some(fc) = read_file_ctx(f, limits.context_max_file_bytes) {
+            if total_bytes + fc.content.len() > limits.context_max_bytes { truncated = true; break; }
+            total_bytes += fc.content.len();
+            files.push(fc);
+        }
+    }
+
+    Ok(ProjectContextResponse { ok: true, total_files: files.len() as u32, total_bytes: total_bytes as u32, truncated, files, error: None })
+}
+
+fn read_file_ctx(f: &IndexedFile, max_bytes: u64) -> Option<FileContext> {
+    if f.size > max_bytes { return None; }
+    // Читается с диска, а не через `content()`: текст не должен оседать в запомненном индексе.
+    let content = std::fs::read_to_string(&f.path).ok()?;
+    Some(FileContext { path: f.rel.clone(), lines: content.lines().count() as u32, content })
+}
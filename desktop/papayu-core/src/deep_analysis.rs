@@ -0,0 +1,326 @@
+use crate::file_index::{FileIndex, IndexedFile};
+use crate::project_fs::ProjectFs;
+use crate::types::{AnalysisConfig, AnalysisLimits, Finding, ProjectSignal};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::sync::OnceLock;
+
+pub const CODE_EXTENSIONS: &[&str] = &[
+    "js", "jsx", "ts", "tsx", "mjs", "cjs",
+    "py", "rs", "go", "rb", "php", "java", "kt",
+    "sh", "bash", "zsh",
+    "yml", "yaml", "toml", "json",
+    "sql", "env", "cfg", "ini", "conf",
+];
+
+/// (id правила, шаблон, описание).
+pub const SECRET_PATTERNS: &[(&str, &str, &str)] = &[
+    ("secrets/hardcoded-password", r"(?i)(password|passwd|pwd)\s*[:=]\s*['\x22][^'\x22]{4,}['\x22]", "Захардкоженный пароль"),
+    ("secrets/api-key", r"(?i)(api[_-]?key|apikey)\s*[:=]\s*['\x22][^'\x22]{8,}['\x22]", "Захардкоженный API-ключ"),
+    ("secrets/secret-token", r"(?i)(secret|token)\s*[:=]\s*['\x22][^'\x22]{8,}['\x22]", "Захардкоженный секрет/токен"),
+    ("secrets/aws-access-key", r"AKIA[0-9A-Z]{16}", "AWS Access Key ID"),
+    ("secrets/private-key", r"-----BEGIN (RSA |EC |DSA )?PRIVATE KEY-----", "PEM приватный ключ"),
+    ("secrets/github-token", r"ghp_[0-9a-zA-Z]{36}", "GitHub Personal Access Token"),
+    ("secrets/sk-key", r"sk-[a-zA-Z0-9]{20,}", "Возможный API-ключ (sk-...)"),
+];
+
+/// (id правила, шаблон, описание, расширения).
+pub const VULN_PATTERNS: &[(&str, &str, &str, &str)] = &[
+    ("security/eval", r"eval\s*\(", "Использование eval() — риск code injection", "js,jsx,ts,tsx,py"),
+    ("security/inner-html", r"innerHTML\s*=", "Прямая запись innerHTML — риск XSS", "js,jsx,ts,tsx"),
+    ("security/document-write", r"document\.write\s*\(", "document.write() — устаревший метод", "js,jsx,ts,tsx"),
+    ("security/dangerously-set-inner-html", r"(?i)dangerouslySetInnerHTML", "dangerouslySetInnerHTML — риск XSS", "jsx,tsx"),
+    ("security/subprocess-shell", r"subprocess\.call\s*\(.*shell\s*=\s*True", "subprocess с shell=True", "py"),
+    ("security/os-system", r"os\.system\s*\(", "os.system() — лучше subprocess", "py"),
+    ("security/cors-wildcard", r"(?i)cors.*origin.*\*", "CORS с wildcard origin", "js,ts,py,rb"),
+    ("security/chmod-777", r"(?i)chmod\s+777", "chmod 777 — слишком широкие права", "sh,bash,zsh,yml,yaml"),
+];
+
+pub const QUALITY_PATTERNS: &[(&str, &str, &str, &str)] = &[
+    ("quality/todo", r"TODO|FIXME|HACK|XXX", "TODO/FIXME комментарии", "js,jsx,ts,tsx,py,rs,go,rb"),
+    ("quality/console-log", r"console\.(log|debug|info)\s*\(", "console.log в коде", "js,jsx,ts,tsx"),
+    ("quality/dbg", r"dbg!\s*\(", "dbg!() макрос (отладочный)", "rs"),
+    ("quality/unwrap", r"\.unwrap\(\)", "Небезопасный .unwrap()", "rs"),
+];
+
+/// Проверки файла без шаблона: (id правила, описание).
+pub const FILE_RULES: &[(&str, &str)] = &[
+    ("quality/large-file", "Большой файл — больше 500 строк"),
+    ("quality/npm-no-test", "В package.json нет скрипта test"),
+    ("quality/unpinned-requirements", "Версии в requirements.txt не зафиксированы через =="),
+];
+
+/// Находок одного правила в одном файле не больше — остальные только в счётчиках.
+pub const MAX_OCCURRENCES: usize = 20;
+/// Длина `Finding::snippet` в символах.
+const MAX_SNIPPET_CHARS: usize = 120;
+
+/// Итог по проекту или по одному файлу (так он хранится в индексе, `analysis_store.rs`).
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct DeepAnalysisResult {
+    pub findings: Vec<Finding>,
+    pub signals: Vec<ProjectSignal>,
+    pub todo_count: u32,
+    pub security_issues: u32,
+    pub quality_issues: u32,
+    pub files_scanned: u32,
+}
+
+/// Скомпилированный шаблон правила.
+pub struct Pattern {
+    pub id: &'static str,
+    pub re: Regex,
+    pub title: &'static str,
+    exts: Vec<&'static str>,
+}
+
+/// Шаблоны компилируются один раз на процесс.
+struct Patterns {
+    secrets: Vec<Pattern>,
+    vulns: Vec<Pattern>,
+    quality: Vec<Pattern>,
+}
+
+fn compile(rules: impl Iterator<Item = (&'static str, &'static str, &'static str, &'static str)>) -> Vec<Pattern> {
+    rules
+        .filter_map(|(id, p, t, e)| Regex::new(p).ok().map(|re| Pattern { id, re, title: t, exts: e.split(',').collect() }))
+        .collect()
+}
+
+fn patterns() -> &'static Patterns {
+    static PATTERNS: OnceLock<Patterns> = OnceLock::new();
+    PATTERNS.get_or_init(|| Patterns {
+        secrets: compile(SECRET_PATTERNS.iter().map(|&(id, p, d)| (id, p, d, ""))),
+        vulns: compile(VULN_PATTERNS.iter().copied()),
+        quality: compile(QUALITY_PATTERNS.iter().copied()),
+    })
+}
+
+/// Скомпилированные `SECRET_PATTERNS`.
+pub fn secret_patterns() -> &'static [Pattern] {
+    &patterns().secrets
+}
+
+/// Строки текста: перевод смещения в строку и колонку.
+struct Lines<'a> {
+    text: &'a str,
+    starts: Vec<usize>,
+}
+
+impl<'a> Lines<'a> {
+    fn new(text: &'a str) -> Lines<'a> {
+        let starts = std::iter::once(0).chain(text.match_indices('\n').map(|(i, _)| i + 1)).collect();
+        Lines { text, starts }
+    }
+
+    /// (строка, колонка) с 1; колонка — в символах.
+    fn position(&self, offset: usize) -> (u32, u32) {
+        let line = self.starts.partition_point(|&s| s <= offset) - 1;
+        let column = self.text[self.starts[line]..offset].chars().count() + 1;
+        (line as u32 + 1, column as u32)
+    }
+
+    /// Текст строки `line` (с 1) без перевода строки.
+    fn line(&self, line: u32) -> &'a str {
+        let start = self.starts[line as usize - 1];
+        let end = self.starts.get(line as usize).map_or(self.text.len(), |e| e - 1);
+        self.text[start..end].trim_end_matches('\r')
+    }
+
+    /// `template` на месте совпадения `start..end`; пустые `details` — `путь:строка:колонка`.
+    fn at(&self, template: &Finding, rel: &str, start: usize, end: usize) -> Finding {
+        let (start_line, start_column) = self.position(start);
+        let (end_line, end_column) = self.position(end);
+        let details = match template.details.is_empty() {
+            true => format!("{}:{}:{}", rel, start_line, start_column),
+            false => template.details.clone(),
+        };
+        Finding {
+            details,
+            path: Some(rel.to_string()),
+            start_line: Some(start_line),
+            start_column: Some(start_column),
+            end_line: Some(end_line),
+            end_column: Some(end_column),
+            snippet: Some(snippet(self.line(start_line))),
+            ..template.clone()
+        }
+    }
+}
+
+/// Файл глубокого анализа для правил пользователя (`rules.rs`).
+pub struct SourceFile<'a> {
+    /// Путь от корня через `/`.
+    pub rel: &'a str,
+    pub ext: &'a str,
+    pub content: &'a str,
+    lines: &'a Lines<'a>,
+}
+
+impl SourceFile<'_> {
+    /// `template` на месте каждого совпадения, как у встроенных правил.
+    pub fn occurrences(&self, template: &Finding, matches: &[regex::Match]) -> Vec<Finding> {
+        let mut findings = vec![];
+        occurrences(&mut findings, self.lines, self.rel, matches, template.clone());
+        findings
+    }
+}
+
+/// Строка для `Finding::snippet`: без отступа, с замаскированными секретами, обрезанная.
+pub fn snippet(line: &str) -> String {
+    let mut line = line.trim().to_string();
+    for p in secret_patterns() {
+        line = p.re.replace_all(&line, |c: &regex::Captures| mask(&c[0])).into_owned();
+    }
+    match line.char_indices().nth(MAX_SNIPPET_CHARS) {
+        Some((i, _)) => format!("{}…", &line[..i]),
+        None => line,
+    }
+}
+
+/// Значение в кавычках заменяется на `****`, остальное — после первых четырёх символов.
+fn mask(secret: &str) -> String {
+    match secret.find(['\'', '"']) {
+        Some(q) => format!("{}****{}", &secret[..=q], &secret[q..=q]),
+        None => format!("{}****", secret.chars().take(4).collect::<String>()),
+    }
+}
+
+/// Файлы, которые разбирает глубокий анализ, в порядке индекса.
+pub fn candidates<'a>(index: &'a FileIndex, limits: &AnalysisLimits) -> Vec<&'a IndexedFile> {
+    index.files.iter()
+        .filter(|f| f.depth <= limits.deep_max_depth && f.size <= limits.deep_max_file_bytes && CODE_EXTENSIONS.contains(&f.ext.as_str()))
+        .take(limits.deep_max_files)
+        .collect()
+}
+
+/// Сливает результаты по файлам и добавляет сигналы по проекту в целом.
+pub fn merge(per_file: impl IntoIterator<Item = DeepAnalysisResult>) -> DeepAnalysisResult {
+    let mut result = DeepAnalysisResult::default();
+    for r in per_file {
+        result.findings.extend(r.findings);
+        result.signals.extend(r.signals);
+        result.todo_count += r.todo_count;
+        result.security_issues += r.security_issues;
+        result.quality_issues += r.quality_issues;
+        result.files_scanned += r.files_scanned;
+    }
+
+    if result.security_issues > 0 {
+        result.signals.push(ProjectSignal { category: "security".into(), level: "high".into(), message: format!("Deep analysis: {} проблем безопасности", result.security_issues) });
+    }
+    if result.todo_count > 5 {
+        result.signals.push(ProjectSignal { category: "quality".into(), level: "warn".into(), message: format!("{} TODO/FIXME комментариев", result.todo_count) });
+    }
+    result
+}
+
+/// Проверки включённых наборов правил (`config.rules`) и правил пользователя
+/// (`config.rule_set`); `None` — файл не читается или не UTF-8.
+/// По одной находке на совпадение, не больше `MAX_OCCURRENCES` на правило в файле.
+pub fn analyze_file(fs: &dyn ProjectFs, file: &IndexedFile, config: &AnalysisConfig) -> Option<DeepAnalysisResult> {
+    let mut result = DeepAnalysisResult::default();
+    let ext = file.ext.as_str();
+    let content = file.content(fs)?;
+    result.files_scanned += 1;
+    let rel = &file.rel;
+    let patterns = patterns();
+    let lines = Lines::new(content);
+
+    if config.rule("secrets") && !rel.contains(".example") && !rel.contains(".sample") {
+        for p in &patterns.secrets {
+            let matches: Vec<_> = p.re.find_iter(content).collect();
+            if !matches.is_empty() {
+                result.security_issues += 1;
+                occurrences(&mut result.findings, &lines, rel, &matches, rule_finding(p, "high", "security", format!("🔐 {}", p.title)));
+                result.signals.push(ProjectSignal { category: "security".into(), level: "high".into(), message: format!("{} в {}", p.title, rel) });
+            }
+        }
+    }
+
+    for p in &patterns.vulns {
+        if !config.rule("security") || !p.exts.contains(&ext) { continue; }
+        let matches: Vec<_> = p.re.find_iter(content).collect();
+        if !matches.is_empty() {
+            result.security_issues += 1;
+            occurrences(&mut result.findings, &lines, rel, &matches, rule_finding(p, "high", "security", format!("⚠️ {}", p.title)));
+        }
+    }
+
+    for p in &patterns.quality {
+        if !config.rule("quality") || !p.exts.contains(&ext) { continue; }
+        let matches: Vec<_> = p.re.find_iter(content).collect();
+        let count = matches.len();
+        if count > 0 {
+            if p.id == "quality/todo" { result.todo_count += count as u32; }
+            result.quality_issues += count as u32;
+            if count >= 3 || p.id == "quality/unwrap" {
+                occurrences(&mut result.findings, &lines, rel, &matches, rule_finding(p, "warn", "quality", format!("📝 {}", p.title)));
+            }
+        }
+    }
+
+    let source = SourceFile { rel, ext, content, lines: &lines };
+    for rule in config.rule_set.rules() {
+        let found = rule.check_file(&source);
+        if found.is_empty() { continue; }
+        match rule.meta().category.as_str() {
+            "security" => result.security_issues += 1,
+            _ => result.quality_issues += found.len() as u32,
+        }
+        result.findings.extend(found);
+    }
+
+    if !config.rule("quality") { return Some(result); }
+    let line_count = content.lines().count();
+    if line_count > 500 {
+        result.findings.push(Finding { severity: "warn".into(), title: "📏 Большой файл".into(), details: format!("{}: {} строк", rel, line_count), rule_id: Some("quality/large-file".into()), category: Some("quality".into()), path: Some(rel.clone()), ..Default::default() });
+    }
+
+    if rel == "package.json" { check_package_json(content, &lines, &mut result); }
+    if rel == "requirements.txt" { check_requirements_txt(content, &lines, &mut result); }
+    Some(result)
+}
+
+/// Находки по совпадениям правила: `template` на месте каждого совпадения.
+fn occurrences(findings: &mut Vec<Finding>, lines: &Lines, rel: &str, matches: &[regex::Match], template: Finding) {
+    for m in matches.iter().take(MAX_OCCURRENCES) {
+        findings.push(lines.at(&template, rel, m.start(), m.end()));
+    }
+    if matches.len() > MAX_OCCURRENCES {
+        if let Some(last) = findings.last_mut() {
+            last.details = format!("{} (и ещё {} в файле)", last.details, matches.len() - MAX_OCCURRENCES);
+        }
+    }
+}
+
+/// Шаблон находки правила `p`; место добавляет `occurrences`.
+fn rule_finding(p: &Pattern, severity: &str, category: &str, title: String) -> Finding {
+    Finding { severity: severity.into(), title, rule_id: Some(p.id.into()), category: Some(category.into()), ..Default::default() }
+}
+
+fn check_package_json(content: &str, lines: &Lines, result: &mut DeepAnalysisResult) {
+    if let Ok(json) = serde_json::from_str::<serde_json::Value>(content) {
+        if let Some(scripts) = json.get("scripts").and_then(|s| s.as_object()) {
+            if !scripts.contains_key("test") || scripts.get("test").and_then(|t| t.as_str()).unwrap_or("").contains("no test specified") {
+                // Место — ключ "scripts" (или "test", если он есть).
+                let at = content.find("\"test\"").or_else(|| content.find("\"scripts\"")).unwrap_or(0);
+                let key_end = content[at..].find(':').map_or(at, |i| at + i);
+                let template = Finding { severity: "warn".into(), title: "🧪 Нет скрипта test".into(), details: "npm test не настроен".into(), rule_id: Some("quality/npm-no-test".into()), category: Some("quality".into()), ..Default::default() };
+                result.findings.push(lines.at(&template, "package.json", at, key_end));
+            }
+        }
+    }
+}
+
+fn check_requirements_txt(content: &str, lines: &Lines, result: &mut DeepAnalysisResult) {
+    let unpinned: Vec<u32> = content.lines().enumerate().filter(|(_, l)| { let l = l.trim(); !l.is_empty() && !l.starts_with('#') && !l.contains("==") }).map(|(i, _)| i as u32 + 1).collect();
+    if unpinned.len() > 3 {
+        let template = Finding { severity: "warn".into(), title: "📦 Незафиксированные версии".into(), rule_id: Some("quality/unpinned-requirements".into()), category: Some("quality".into()), ..Default::default() };
+        for line in unpinned.iter().take(MAX_OCCURRENCES) {
+            let start = lines.starts[*line as usize - 1];
+            result.findings.push(lines.at(&template, "requirements.txt", start, start + lines.line(*line).len()));
+        }
+        if let Some(last) = result.findings.last_mut() { last.details = format!("{} (всего {} пакетов без ==)", last.details, unpinned.len()); }
+    }
+}
@@ -0,0 +1,214 @@
+//! Построчный diff для предпросмотра: ханки с номерами строк, счётчики
+//! добавленных/удалённых строк, большие и бинарные файлы без содержимого.
+
+use std::fs;
+use std::path::Path;
+use std::time::Duration;
+
+use similar::{Algorithm, ChangeTag, TextDiff};
+
+use crate::types::{DiffHunk, DiffItem, DiffLine, PreviewSummary};
+
+/// До какого размера `before`/`after` отдаются в UI целиком.
+pub const MAX_INLINE_BYTES: usize = 200_000;
+/// Файлы больше этого не читаются и не сравниваются построчно.
+pub const MAX_DIFF_BYTES: u64 = 5 * 1024 * 1024;
+/// Сколько строк ханков отдаётся на один файл.
+const MAX_HUNK_LINES: usize = 5_000;
+const CONTEXT_LINES: usize = 3;
+const DIFF_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Содержимое одной стороны diff'а.
+#[derive(Debug, Clone)]
+pub enum Content {
+    Missing,
+    Text(String),
+    Binary(u64),
+    TooLarge(u64),
+}
+
+impl Content {
+    pub fn text(&self) -> Option<&str> {
+        match self {
+            Content::Text(t) => Some(t),
+            _ => None,
+        }
+    }
+
+    fn size(&self) -> u64 {
+        match self {
+            Content::Missing => 0,
+            Content::Text(t) => t.len() as u64,
+            Content::Binary(n) | Content::TooLarge(n) => *n,
+        }
+    }
+
+    fn inline(&self) -> Option<String> {
+        self.text().filter(|t| t.len() <= MAX_INLINE_BYTES).map(str::to_string)
+    }
+}
+
+fn looks_binary(bytes: &[u8]) -> bool {
+    bytes.iter().take(8192).any(|b| *b == 0)
+}
+
+pub fn load(path: &Path) -> Content {
+    let meta = match fs::metadata(path) {
+        Ok(m) if m.is_file() => m,
+        _ => return Content::Missing,
+    };
+    if meta.len() > MAX_DIFF_BYTES {
+        return Content::TooLarge(meta.len());
+    }
+    match fs::read(path) {
+        Ok(bytes) if looks_binary(&bytes) => Content::Binary(meta.len()),
+        Ok(bytes) => match String::from_utf8(bytes) {
+            Ok(t) => Content::Text(t),
+            Err(_) => Content::Binary(meta.len()),
+        },
+        Err(_) => Content::Missing,
+    }
+}
+
+/// Ханки unified-diff'а (3 строки контекста) и число добавленных/удалённых строк.
+/// Последний элемент — ханки обрезаны по `MAX_HUNK_LINES`.
+pub fn line_diff(before: &str, after: &str) -> (Vec<DiffHunk>, usize, usize, bool) {
+    let diff = TextDiff::configure()
+        .algorithm(Algorithm::Myers)
+        .timeout(DIFF_TIMEOUT)
+        .diff_lines(before, after);
+
+    let mut hunks = vec![];
+    let (mut added, mut removed) = (0, 0);
+    let mut emitted = 0;
+    let mut truncated = false;
+
+    for group in diff.grouped_ops(CONTEXT_LINES) {
+        let (first, last) = match (group.first(), group.last()) {
+            (Some(f), Some(l)) => (f, l),
+            _ => continue,
+        };
+        let old = first.old_range().start..last.old_range().end;
+        let new = first.new_range().start..last.new_range().end;
+        let mut lines = vec![];
+        for op in &group {
+            for change in diff.iter_changes(op) {
+                let kind = match change.tag() {
+                    ChangeTag::Equal => "context",
+                    ChangeTag::Insert => {
+                        added += 1;
+                        "add"
+                    }
+                    ChangeTag::Delete => {
+                        removed += 1;
+                        "remove"
+                    }
+                };
+                if emitted >= MAX_HUNK_LINES {
+                    truncated = true;
+                    continue;
+                }
+                emitted += 1;
+                lines.push(DiffLine {
+                    kind: kind.into(),
+                    old_line: change.old_index().map(|i| i + 1),
+                    new_line: change.new_index().map(|i| i + 1),
+                    text: change.value().trim_end_matches('\n').trim_end_matches('\r').to_string(),
+                });
+            }
+        }
+        if !lines.is_empty() {
+            hunks.push(DiffHunk {
+                old_start: old.start + 1,
+                old_lines: old.len(),
+                new_start: new.start + 1,
+                new_lines: new.len(),
+                lines,
+            });
+        }
+    }
+    (hunks, added, removed, truncated)
+}
+
+/// Элемент предпросмотра для файла: ханки, счётчики и, если файл невелик, тексты целиком.
+pub fn file_item(path: &str, kind: &str, before: &Content, after: &Content, summary: String) -> DiffItem {
+    let binary = matches!(before, Content::Binary(_)) || matches!(after, Content::Binary(_));
+    let too_large = matches!(before, Content::TooLarge(_)) || matches!(after, Content::TooLarge(_));
+
+    let (hunks, added, removed, cut) = match (before, after) {
+        (Content::Text(_) | Content::Missing, Content::Text(_) | Content::Missing) => {
+            line_diff(before.text().unwrap_or(""), after.text().unwrap_or(""))
+        }
+        _ => (vec![], 0, 0, false),
+    };
+
+    let summary = if binary {
+        format!("{} (бинарный файл, {} → {} байт)", summary, before.size(), after.size())
+    } else if too_large {
+        format!("{} (файл больше {} МБ — построчный diff не строится)", summary, MAX_DIFF_BYTES / (1024 * 1024))
+    } else {
+        format!("{} (+{} −{})", summary, added, removed)
+    };
+    let inline_cut = before.text().is_some_and(|t| t.len() > MAX_INLINE_BYTES)
+        || after.text().is_some_and(|t| t.len() > MAX_INLINE_BYTES);
+
+    DiffItem {
+        path: path.to_string(),
+        kind: kind.to_string(),
+        before: before.inline(),
+        after: after.inline(),
+        summary,
+        to: None,
+        hunks,
+        added,
+        removed,
+        binary,
+        truncated: cut || too_large || inline_cut,
+    }
+}
+
+/// Элемент предпросмотра без содержимого (папки).
+pub fn dir_item(path: &str, kind: &str, summary: String) -> DiffItem {
+    DiffItem {
+        path: path.to_string(),
+        kind: kind.to_string(),
+        before: None,
+        after: None,
+        summary,
+        to: None,
+        hunks: vec![],
+        added: 0,
+        removed: 0,
+        binary: false,
+        truncated: false,
+    }
+}
+
+pub fn summarize(diffs: &[DiffItem]) -> PreviewSummary {
+    let count = |kinds: &[&str]| diffs.iter().filter(|d| kinds.contains(&d.kind.as_str())).count();
+    let mut s = PreviewSummary {
+        files_created: count(&["create"]),
+        files_updated: count(&["update", "patch", "replace", "insert"]),
+        moved: count(&["move", "movedir"]),
+        copied: count(&["copy"]),
+        files_deleted: count(&["delete"]),
+        dirs_created: count(&["mkdir"]),
+        dirs_deleted: count(&["rmdir"]),
+        added: diffs.iter().map(|d| d.added).sum(),
+        removed: diffs.iter().map(|d| d.removed).sum(),
+        text: String::new(),
+    };
+    s.text = format!(
+        "Файлы: создать {}, изменить {}, удалить {}; перенести {}, скопировать {}; папки: создать {}, удалить {}; строк +{} −{}",
+        s.files_created,
+        s.files_updated,
+        s.files_deleted,
+        s.moved,
+        s.copied,
+        s.dirs_created,
+        s.dirs_deleted,
+        s.added,
+        s.removed
+    );
+    s
+}
@@ -0,0 +1,150 @@
+//! Индекс файлов проекта: один параллельный обход (`walker.rs`) на весь анализ —
+//! структура, глубокий анализ и контекст LLM берут файлы и их содержимое отсюда.
+//! Содержимое читается при первом обращении и дальше берётся из индекса.
+//! Последний индекс проекта на диске запоминается без содержимого файлов, чтобы
+//! `collect_project_context` после анализа не обходил дерево заново; apply, undo и
+//! восстановление его сбрасывают.
+
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::SystemTime;
+
+use crate::analysis_job::Job;
+use crate::project_fs::ProjectFs;
+use crate::types::AnalysisStop;
+use crate::walker;
+
+pub struct IndexedDir {
+    pub path: PathBuf,
+    pub depth: u32,
+}
+
+pub struct IndexedFile {
+    pub path: PathBuf,
+    /// Путь от корня проекта через `/`.
+    pub rel: String,
+    /// Глубина папки, в которой лежит файл (корень — 0).
+    pub depth: u32,
+    pub size: u64,
+    pub modified: Option<SystemTime>,
+    /// Расширение в нижнем регистре, без точки.
+    pub ext: String,
+    content: OnceLock<Option<String>>,
+}
+
+impl IndexedFile {
+    /// Текст файла (`None` — не читается или не UTF-8); читается один раз.
+    pub fn content(&self, fs: &dyn ProjectFs) -> Option<&str> {
+        self.content.get_or_init(|| fs.read_to_string(&self.path)).as_deref()
+    }
+
+    /// Копия без прочитанного содержимого.
+    fn metadata_only(&self) -> IndexedFile {
+        IndexedFile {
+            path: self.path.clone(),
+            rel: self.rel.clone(),
+            depth: self.depth,
+            size: self.size,
+            modified: self.modified,
+            ext: self.ext.clone(),
+            content: OnceLock::new(),
+        }
+    }
+}
+
+pub struct FileIndex {
+    pub root: PathBuf,
+    /// Папки и файлы отсортированы по пути.
+    pub dirs: Vec<IndexedDir>,
+    pub files: Vec<IndexedFile>,
+    /// Обход остановлен по лимиту файлов, времени или отмене — индекс неполный.
+    pub stopped: Option<AnalysisStop>,
+}
+
+impl FileIndex {
+    pub fn build(fs: &dyn ProjectFs, root: &Path, job: &Job, exclude: &[String]) -> FileIndex {
+        let walk = walker::walk(fs, root, job, exclude);
+        let mut dirs = vec![];
+        let mut files = vec![];
+        for w in walk.entries {
+            let e = w.entry;
+            if e.is_dir {
+                dirs.push(IndexedDir {
+                    path: e.path,
+                    depth: w.depth,
+                });
+                continue;
+            }
+            let rel = e
+                .path
+                .strip_prefix(root)
+                .unwrap_or(&e.path)
+                .to_string_lossy()
+                .replace('\\', "/");
+            let ext = e
+                .path
+                .extension()
+                .map(|x| x.to_string_lossy().to_lowercase())
+                .unwrap_or_default();
+            files.push(IndexedFile {
+                path: e.path,
+                rel,
+                depth: w.depth,
+                size: e.len,
+                modified: e.modified,
+                ext,
+                content: OnceLock::new(),
+            });
+        }
+        dirs.sort_by(|a, b| a.path.cmp(&b.path));
+        files.sort_by(|a, b| a.rel.cmp(&b.rel));
+        FileIndex {
+            root: root.to_path_buf(),
+            dirs,
+            files,
+            stopped: walk.stopped,
+        }
+    }
+
+    pub fn get(&self, rel: &str) -> Option<&IndexedFile> {
+        self.files
+            .binary_search_by(|f| f.rel.as_str().cmp(rel))
+            .ok()
+            .map(|i| &self.files[i])
+    }
+}
+
+static LAST: Mutex<Option<Arc<FileIndex>>> = Mutex::new(None);
+
+/// Запоминает индекс проекта на диске (хранится один, последний). Запоминается
+/// только список файлов: текст, прочитанный глубоким анализом, между командами
+/// не держится — на большом проекте это весь его исходный код.
+pub fn remember(index: &FileIndex) {
+    let copy = FileIndex {
+        root: index.root.clone(),
+        dirs: index
+            .dirs
+            .iter()
+            .map(|d| IndexedDir {
+                path: d.path.clone(),
+                depth: d.depth,
+            })
+            .collect(),
+        files: index.files.iter().map(IndexedFile::metadata_only).collect(),
+        stopped: index.stopped,
+    };
+    *LAST.lock().unwrap_or_else(|e| e.into_inner()) = Some(Arc::new(copy));
+}
+
+pub fn remembered(root: &Path) -> Option<Arc<FileIndex>> {
+    LAST.lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .as_ref()
+        .filter(|i| i.root == root)
+        .cloned()
+}
+
+/// Файлы проекта меняются (apply, undo, восстановление) — запомненный индекс больше не годится.
+pub fn forget() {
+    *LAST.lock().unwrap_or_else(|e| e.into_inner()) = None;
+}
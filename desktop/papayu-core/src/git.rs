@@ -0,0 +1,267 @@
+//! Git-интеграция apply (по выбору пользователя): проверка репозитория в корне
+//! проекта и рабочего дерева, ветка `papayu/<session_id>`, коммит применённых
+//! действий, откат и повтор коммита при undo/redo. Только локальный репозиторий
+//! через `git` CLI — с remote ничего не делается.
+
+use std::collections::HashSet;
+use std::fs;
+use std::io::Write;
+use std::path::Path;
+use std::process::{Command, Output, Stdio};
+
+use crate::types::{Action, GitSession};
+
+pub const BRANCH_PREFIX: &str = "papayu/";
+
+#[derive(Debug, Clone)]
+pub struct GitError {
+    pub error: String,
+    pub code: &'static str,
+}
+
+impl GitError {
+    fn new(error: impl Into<String>, code: &'static str) -> Self {
+        GitError {
+            error: error.into(),
+            code,
+        }
+    }
+}
+
+/// Состояние репозитория перед apply.
+#[derive(Debug, Clone)]
+pub struct Repo {
+    pub head: String,
+    /// Текущая ветка; `None` — detached HEAD.
+    pub branch: Option<String>,
+    /// Пути с незакоммиченными изменениями (включая неотслеживаемые).
+    pub dirty: Vec<String>,
+}
+
+pub fn command(root: &Path, args: &[&str]) -> Command {
+    let mut c = Command::new("git");
+    c.arg("-C").arg(root).args(args).env("GIT_TERMINAL_PROMPT", "0");
+    c
+}
+
+pub fn run(root: &Path, args: &[&str]) -> Result<Output, String> {
+    command(root, args)
+        .output()
+        .map_err(|e| format!("git_unavailable: {}", e))
+}
+
+/// Как `run`, но `input` подаётся на stdin (для `--stdin -z`: пути без экранирования).
+pub fn run_with_input(root: &Path, args: &[&str], input: &[u8]) -> Result<Output, String> {
+    let mut child = command(root, args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| format!("git_unavailable: {}", e))?;
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin.write_all(input).map_err(|e| e.to_string())?;
+    }
+    child.wait_with_output().map_err(|e| e.to_string())
+}
+
+pub fn git(root: &Path, args: &[&str]) -> Result<String, String> {
+    let out = run(root, args)?;
+    if out.status.success() {
+        return Ok(String::from_utf8_lossy(&out.stdout).trim_end().to_string());
+    }
+    Err(format!(
+        "git {}: {}",
+        args.iter().find(|a| !a.starts_with('-') && !a.contains('=')).unwrap_or(&""),
+        String::from_utf8_lossy(&out.stderr).trim()
+    ))
+}
+
+/// Если автор в git не настроен, коммиты подписываются от имени приложения.
+fn identity(root: &Path) -> Vec<&'static str> {
+    match git(root, &["config", "user.email"]) {
+        Ok(email) if !email.is_empty() => vec![],
+        _ => vec!["-c", "user.name=PAPA YU", "-c", "user.email=papayu@localhost"],
+    }
+}
+
+fn with_identity<'a>(root: &Path, args: &[&'a str]) -> Vec<&'a str> {
+    let mut all = identity(root);
+    all.extend_from_slice(args);
+    all
+}
+
+/// Репозиторий должен начинаться ровно в корне проекта и иметь хотя бы один коммит.
+pub fn inspect(project_root: &Path) -> Result<Repo, GitError> {
+    let top = git(project_root, &["rev-parse", "--show-toplevel"])
+        .map_err(|e| GitError::new(format!("git_not_a_repo: {}", e), "GIT_NOT_A_REPO"))?;
+    let same_root = match (fs::canonicalize(&top), fs::canonicalize(project_root)) {
+        (Ok(a), Ok(b)) => a == b,
+        _ => false,
+    };
+    if !same_root {
+        return Err(GitError::new(format!("git_repo_root_mismatch: {}", top), "GIT_NOT_REPO_ROOT"));
+    }
+    let head = git(project_root, &["rev-parse", "--verify", "-q", "HEAD"])
+        .map_err(|_| GitError::new("git_no_commits", "GIT_NO_COMMITS"))?;
+    let branch = git(project_root, &["symbolic-ref", "-q", "--short", "HEAD"]).ok();
+    let status = git(project_root, &["status", "--porcelain=v1", "-z", "--untracked-files=all"])
+        .map_err(|e| GitError::new(e, "GIT_FAILED"))?;
+
+    // `XY path\0`, у переименований следом идёт старый путь.
+    let mut dirty = vec![];
+    let mut entries = status.split('\0').filter(|e| !e.is_empty());
+    while let Some(e) = entries.next() {
+        if e.len() > 3 {
+            dirty.push(e[3..].to_string());
+        }
+        if e.starts_with('R') || e.starts_with('C') {
+            entries.next();
+        }
+    }
+    Ok(Repo { head, branch, dirty })
+}
+
+fn commit_message(session_id: &str, actions: &[Action]) -> String {
+    let mut msg = format!("papayu: применено изменений — {}\n\n", actions.len());
+    for a in actions {
+        let target = match &a.to {
+            Some(to) => format!("{} → {}", a.path, to),
+            None => a.path.clone(),
+        };
+        msg.push_str(&format!("- {} ({})\n", a.title, target));
+    }
+    msg.push_str(&format!("\nPAPA YU session: {}\n", session_id));
+    msg
+}
+
+/// Пути, которые можно передать в `git add`/`git commit`: существуют на диске или
+/// известны git'у, и не игнорируются.
+fn committable(root: &Path, paths: &[String]) -> Result<Vec<String>, String> {
+    let mut args = vec!["ls-files", "-z", "--"];
+    args.extend(paths.iter().map(String::as_str));
+    let tracked = git(root, &args)?;
+    let tracked: Vec<&str> = tracked.split('\0').filter(|t| !t.is_empty()).collect();
+    let known = |p: &str| {
+        root.join(p).symlink_metadata().is_ok()
+            || tracked.iter().any(|t| *t == p || t.starts_with(&format!("{}/", p)))
+    };
+
+    let input: Vec<u8> = paths.iter().flat_map(|p| p.bytes().chain([0])).collect();
+    // Код 1 — ничего не игнорируется.
+    let out = run_with_input(root, &["check-ignore", "--stdin", "-z"], &input)?;
+    let ignored: HashSet<String> = String::from_utf8_lossy(&out.stdout)
+        .split('\0')
+        .filter(|s| !s.is_empty())
+        .map(str::to_string)
+        .collect();
+
+    Ok(paths
+        .iter()
+        .filter(|p| known(p) && !ignored.contains(p.as_str()))
+        .cloned()
+        .collect())
+}
+
+/// Коммитит изменения путей сессии — и только их: чужие незакоммиченные правки
+/// в коммит не попадают. С `branch` коммит делается в новой ветке `papayu/<session_id>`.
+pub fn commit_session(
+    project_root: &Path,
+    repo: &Repo,
+    session_id: &str,
+    paths: &[String],
+    actions: &[Action],
+    branch: bool,
+) -> Result<GitSession, GitError> {
+    let paths = committable(project_root, paths).map_err(|e| GitError::new(e, "GIT_FAILED"))?;
+    if paths.is_empty() {
+        return Err(GitError::new("git_nothing_to_commit", "GIT_NOTHING_TO_COMMIT"));
+    }
+
+    let branch_name = format!("{}{}", BRANCH_PREFIX, session_id);
+    if branch {
+        git(project_root, &["checkout", "-q", "-b", &branch_name])
+            .map_err(|e| GitError::new(e, "GIT_BRANCH_FAILED"))?;
+    }
+
+    let msg = commit_message(session_id, actions);
+    let mut add = vec!["add", "-A", "--"];
+    add.extend(paths.iter().map(String::as_str));
+    let mut commit = vec!["commit", "-q", "-m", &msg, "--only", "--"];
+    commit.extend(paths.iter().map(String::as_str));
+
+    let committed = git(project_root, &add)
+        .and_then(|_| git(project_root, &with_identity(project_root, &commit)))
+        .and_then(|_| git(project_root, &["rev-parse", "HEAD"]));
+    match committed {
+        Ok(commit) => Ok(GitSession {
+            commit,
+            base: repo.head.clone(),
+            branch: branch.then_some(branch_name),
+            previous_branch: repo.branch.clone(),
+        }),
+        Err(e) => {
+            if branch {
+                leave_branch(project_root, &branch_name, repo.branch.as_deref(), &repo.head);
+            }
+            Err(GitError::new(e, "GIT_COMMIT_FAILED"))
+        }
+    }
+}
+
+/// Возвращается на исходную ветку (на тот же коммит — рабочее дерево не меняется) и удаляет ветку сессии.
+fn leave_branch(project_root: &Path, branch: &str, previous: Option<&str>, base: &str) {
+    let back = match previous {
+        Some(b) => git(project_root, &["checkout", "-q", b]),
+        None => git(project_root, &["checkout", "-q", "--detach", base]),
+    };
+    if back.is_ok() {
+        let _ = git(project_root, &["branch", "-q", "-D", branch]);
+    }
+}
+
+/// Откат закоммиченной сессии. Если её коммит — HEAD, он снимается через
+/// `reset --keep` (незакоммиченные правки в других файлах сохраняются), иначе
+/// поверх создаётся `git revert`. Возвращает способ: `reset` | `revert`.
+pub fn undo(project_root: &Path, g: &GitSession) -> Result<&'static str, GitError> {
+    let head = git(project_root, &["rev-parse", "HEAD"]).map_err(|e| GitError::new(e, "GIT_FAILED"))?;
+    if head == g.commit {
+        git(project_root, &["reset", "-q", "--keep", &g.base]).map_err(|e| GitError::new(e, "GIT_UNDO_FAILED"))?;
+        let current = git(project_root, &["symbolic-ref", "-q", "--short", "HEAD"]).ok();
+        if let (Some(b), Some(cur)) = (&g.branch, &current) {
+            if b == cur {
+                leave_branch(project_root, b, g.previous_branch.as_deref(), &g.base);
+            }
+        }
+        return Ok("reset");
+    }
+
+    if git(project_root, &["merge-base", "--is-ancestor", &g.commit, "HEAD"]).is_err() {
+        return Err(GitError::new(
+            format!("git_commit_not_in_head: {}", g.commit),
+            "GIT_COMMIT_NOT_IN_HEAD",
+        ));
+    }
+    let revert = with_identity(project_root, &["revert", "--no-edit", &g.commit]);
+    if let Err(e) = git(project_root, &revert) {
+        let _ = git(project_root, &["revert", "--abort"]);
+        return Err(GitError::new(e, "GIT_REVERT_CONFLICT"));
+    }
+    Ok("revert")
+}
+
+/// Повтор откатанной сессии: её коммит заново применяется на текущий HEAD.
+pub fn redo(project_root: &Path, g: &GitSession) -> Result<GitSession, GitError> {
+    let head = git(project_root, &["rev-parse", "HEAD"]).map_err(|e| GitError::new(e, "GIT_FAILED"))?;
+    let pick = with_identity(project_root, &["cherry-pick", "--keep-redundant-commits", &g.commit]);
+    if let Err(e) = git(project_root, &pick) {
+        let _ = git(project_root, &["cherry-pick", "--abort"]);
+        return Err(GitError::new(e, "GIT_REDO_FAILED"));
+    }
+    let commit = git(project_root, &["rev-parse", "HEAD"]).map_err(|e| GitError::new(e, "GIT_FAILED"))?;
+    Ok(GitSession {
+        commit,
+        base: head,
+        branch: None,
+        previous_branch: None,
+    })
+}
@@ -0,0 +1,365 @@
+//! Проход по истории git для `analyze_project` (по запросу): часто меняемые файлы,
+//! модули одного автора, давно не менявшийся код, крупные blob'ы и секреты в прошлых
+//! коммитах. Читается только локальный репозиторий через `git` CLI; проект в подпапке
+//! репозитория анализируется по своей части истории.
+
+use std::collections::{HashMap, HashSet};
+use std::io::{BufRead, BufReader};
+use std::path::Path;
+use std::process::Stdio;
+
+use crate::analysis_job::Job;
+use crate::deep_analysis::{self, CODE_EXTENSIONS};
+use crate::git;
+use crate::types::ProjectSignal;
+
+const MAX_COMMITS: &str = "5000";
+const MAX_PATCH_COMMITS: &str = "1000";
+const DAY_SECS: i64 = 86_400;
+const HOT_WINDOW_DAYS: i64 = 90;
+const HOT_MIN_CHANGES: u32 = 10;
+const BUS_MIN_FILES: usize = 5;
+const STALE_DAYS: i64 = 365;
+const LARGE_BLOB_BYTES: u64 = 5 * 1024 * 1024;
+/// Сколько сигналов одного вида выводится поимённо; остальные — одной строкой.
+const MAX_LISTED: usize = 5;
+/// Длинные добавленные строки (минифицированный код, base64) на секреты не проверяются.
+const MAX_LINE_LEN: usize = 4096;
+
+#[derive(Default)]
+struct FileHistory {
+    recent: u32,
+    authors: HashSet<String>,
+    last: i64,
+}
+
+struct Log {
+    files: HashMap<String, FileHistory>,
+    /// email → имя, для сообщений.
+    authors: HashMap<String, String>,
+    head_time: i64,
+}
+
+fn signal(level: &str, category: &str, message: String) -> ProjectSignal {
+    ProjectSignal {
+        category: category.into(),
+        level: level.into(),
+        message,
+    }
+}
+
+/// Между проходами проверяется задание: после остановки остальные проходы пропускаются.
+pub fn run_history_analysis(root: &Path, job: &Job) -> Vec<ProjectSignal> {
+    let prefix = match git::git(root, &["rev-parse", "--show-prefix"]) {
+        Ok(p) => p,
+        Err(_) => return vec![],
+    };
+    if git::git(root, &["rev-parse", "--verify", "-q", "HEAD"]).is_err() {
+        return vec![];
+    }
+
+    let mut signals = vec![];
+    job.message("История git: частота изменений и авторы…");
+    match read_log(root) {
+        Ok(log) => {
+            hot_files(&log, &mut signals);
+            bus_factor(&log, &mut signals);
+            stale_files(&log, &mut signals);
+        }
+        Err(e) => log::warn!("git log: {}", e),
+    }
+
+    if job.should_stop() {
+        return signals;
+    }
+    job.message("История git: крупные файлы…");
+    if let Err(e) = large_blobs(root, &prefix, &mut signals) {
+        log::warn!("git rev-list: {}", e);
+    }
+
+    if job.should_stop() {
+        return signals;
+    }
+    job.message("История git: секреты в прошлых коммитах…");
+    if let Err(e) = past_secrets(root, &mut signals) {
+        log::warn!("git log -p: {}", e);
+    }
+    signals
+}
+
+/// Изменения по файлам, которые есть в проекте сейчас (переименования не отслеживаются).
+fn read_log(root: &Path) -> Result<Log, String> {
+    let out = git::git(
+        root,
+        &[
+            "-c", "core.quotePath=false", "log", "--no-merges", "-n", MAX_COMMITS, "--relative",
+            "--format=%x01%aE%x09%aN%x09%ct", "--name-only", "--", ".",
+        ],
+    )?;
+
+    let mut commits = vec![];
+    let mut authors = HashMap::new();
+    for chunk in out.split('\u{1}').skip(1) {
+        let mut lines = chunk.lines();
+        let mut header = lines.next().unwrap_or("").splitn(3, '\t');
+        let email = header.next().unwrap_or("").to_lowercase();
+        let name = header.next().unwrap_or("").to_string();
+        let time: i64 = header.next().and_then(|t| t.trim().parse().ok()).unwrap_or(0);
+        authors.entry(email.clone()).or_insert(name);
+        let files: Vec<&str> = lines.filter(|l| !l.is_empty()).collect();
+        commits.push((email, time, files));
+    }
+
+    let head_time = commits.iter().map(|c| c.1).max().unwrap_or(0);
+    let mut files: HashMap<String, FileHistory> = HashMap::new();
+    for (email, time, paths) in &commits {
+        for p in paths {
+            if !root.join(p).is_file() {
+                continue;
+            }
+            let h = files.entry(p.to_string()).or_default();
+            if *time >= head_time - HOT_WINDOW_DAYS * DAY_SECS {
+                h.recent += 1;
+            }
+            h.authors.insert(email.clone());
+            h.last = h.last.max(*time);
+        }
+    }
+    Ok(Log {
+        files,
+        authors,
+        head_time,
+    })
+}
+
+/// Окно «недавних» изменений отсчитывается от последнего коммита, а не от текущей даты.
+fn hot_files(log: &Log, signals: &mut Vec<ProjectSignal>) {
+    let mut hot: Vec<(&String, u32)> = log
+        .files
+        .iter()
+        .filter(|(_, h)| h.recent >= HOT_MIN_CHANGES)
+        .map(|(p, h)| (p, h.recent))
+        .collect();
+    hot.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(b.0)));
+    for (p, n) in hot.into_iter().take(MAX_LISTED) {
+        signals.push(signal(
+            "warn",
+            "history",
+            format!("Часто меняется {}: {} изменений за {} дней.", p, n, HOT_WINDOW_DAYS),
+        ));
+    }
+}
+
+/// Модуль — папка верхнего уровня; в проекте одного автора сигнал не нужен.
+fn bus_factor(log: &Log, signals: &mut Vec<ProjectSignal>) {
+    let all: HashSet<&String> = log.files.values().flat_map(|h| &h.authors).collect();
+    if all.len() < 2 {
+        return;
+    }
+    let mut modules: HashMap<&str, (usize, HashSet<&String>)> = HashMap::new();
+    for (p, h) in &log.files {
+        if let Some((module, _)) = p.split_once('/') {
+            let m = modules.entry(module).or_default();
+            m.0 += 1;
+            m.1.extend(&h.authors);
+        }
+    }
+    let mut single: Vec<(&str, &String)> = modules
+        .iter()
+        .filter(|(_, (files, authors))| *files >= BUS_MIN_FILES && authors.len() == 1)
+        .filter_map(|(m, (_, authors))| authors.iter().next().map(|a| (*m, *a)))
+        .collect();
+    single.sort();
+    for (module, email) in single.into_iter().take(MAX_LISTED) {
+        let name = log.authors.get(email).filter(|n| !n.is_empty()).unwrap_or(email);
+        signals.push(signal(
+            "warn",
+            "history",
+            format!("Модуль {}/ меняет только {} — bus factor 1.", module, name),
+        ));
+    }
+}
+
+fn stale_files(log: &Log, signals: &mut Vec<ProjectSignal>) {
+    let mut stale: Vec<(&String, i64)> = log
+        .files
+        .iter()
+        .filter(|(p, h)| {
+            let ext = Path::new(p).extension().and_then(|e| e.to_str()).unwrap_or("");
+            CODE_EXTENSIONS.contains(&ext) && h.last < log.head_time - STALE_DAYS * DAY_SECS
+        })
+        .map(|(p, h)| (p, h.last))
+        .collect();
+    if stale.is_empty() {
+        return;
+    }
+    stale.sort_by(|a, b| a.1.cmp(&b.1).then(a.0.cmp(b.0)));
+    let examples: Vec<&str> = stale.iter().take(3).map(|(p, _)| p.as_str()).collect();
+    signals.push(signal(
+        "info",
+        "history",
+        format!(
+            "{} файлов кода не менялись больше года (например: {}).",
+            stale.len(),
+            examples.join(", ")
+        ),
+    ));
+}
+
+/// Крупные blob'ы во всех локальных ветках и тегах, включая удалённые из проекта файлы:
+/// они остаются в истории и раздувают репозиторий.
+fn large_blobs(root: &Path, prefix: &str, signals: &mut Vec<ProjectSignal>) -> Result<(), String> {
+    let objects = git::git(root, &["rev-list", "--objects", "--all"])?;
+    let mut paths: HashMap<&str, &str> = HashMap::new();
+    for line in objects.lines() {
+        if let Some((sha, path)) = line.split_once(' ') {
+            if let Some(rel) = path.strip_prefix(prefix) {
+                paths.entry(sha).or_insert(rel);
+            }
+        }
+    }
+    if paths.is_empty() {
+        return Ok(());
+    }
+
+    let input: String = paths.keys().map(|s| format!("{}\n", s)).collect();
+    let out = git::run_with_input(
+        root,
+        &["cat-file", "--batch-check=%(objecttype) %(objectname) %(objectsize)"],
+        input.as_bytes(),
+    )?;
+    let mut largest: HashMap<&str, u64> = HashMap::new();
+    for line in String::from_utf8_lossy(&out.stdout).lines() {
+        let mut parts = line.split(' ');
+        let (Some("blob"), Some(sha), Some(size)) = (parts.next(), parts.next(), parts.next()) else {
+            continue;
+        };
+        let size: u64 = size.parse().unwrap_or(0);
+        if size < LARGE_BLOB_BYTES {
+            continue;
+        }
+        if let Some(path) = paths.get(sha) {
+            let max = largest.entry(path).or_insert(0);
+            *max = (*max).max(size);
+        }
+    }
+
+    let mut largest: Vec<(&str, u64)> = largest.into_iter().collect();
+    largest.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(b.0)));
+    for (path, size) in largest.iter().take(MAX_LISTED) {
+        let gone = if root.join(path).exists() { "" } else { ", удалён из проекта, но остался в истории" };
+        signals.push(signal(
+            "warn",
+            "history",
+            format!(
+                "Крупный файл в истории git: {} ({:.1} МБ{}).",
+                path,
+                *size as f64 / (1024.0 * 1024.0),
+                gone
+            ),
+        ));
+    }
+    if largest.len() > MAX_LISTED {
+        signals.push(signal(
+            "info",
+            "history",
+            format!("И ещё {} крупных файлов в истории git.", largest.len() - MAX_LISTED),
+        ));
+    }
+    Ok(())
+}
+
+/// Секреты в строках, добавленных прошлыми коммитами. Находки, которые есть и в текущей
+/// версии файла, пропускаются — их уже показывает глубокий анализ.
+fn past_secrets(root: &Path, signals: &mut Vec<ProjectSignal>) -> Result<(), String> {
+    let patterns = deep_analysis::secret_patterns();
+
+    let mut child = git::command(
+        root,
+        &[
+            "-c", "core.quotePath=false", "log", "--no-merges", "-n", MAX_PATCH_COMMITS, "--relative", "-p", "-U0",
+            "--no-color", "--no-ext-diff", "--format=%x01%H", "--", ".",
+        ],
+    )
+    .stdout(Stdio::piped())
+    .stderr(Stdio::null())
+    .spawn()
+    .map_err(|e| format!("git_unavailable: {}", e))?;
+    let stdout = child.stdout.take().ok_or("git: no stdout")?;
+
+    // (описание, файл, коммит): по одной находке на пару, из самого свежего коммита.
+    let mut hits: Vec<(&str, String, String)> = vec![];
+    let mut seen: HashSet<(&str, String)> = HashSet::new();
+    let mut commit = String::new();
+    let mut file: Option<String> = None;
+    let mut in_header = false;
+
+    let mut reader = BufReader::new(stdout);
+    let mut buf = vec![];
+    loop {
+        buf.clear();
+        match reader.read_until(b'\n', &mut buf) {
+            Ok(0) | Err(_) => break,
+            Ok(_) => {}
+        }
+        let line = String::from_utf8_lossy(&buf);
+        let line = line.trim_end_matches(['\n', '\r']);
+        if let Some(sha) = line.strip_prefix('\u{1}') {
+            commit = sha.to_string();
+        } else if line.starts_with("diff --git ") {
+            in_header = true;
+            file = None;
+        } else if in_header {
+            if let Some(p) = line.strip_prefix("+++ ") {
+                file = p.strip_prefix("b/").map(str::to_string);
+            } else if line.starts_with("@@") {
+                in_header = false;
+            }
+        } else if let (Some(added), Some(f)) = (line.strip_prefix('+'), &file) {
+            if added.len() > MAX_LINE_LEN || f.contains(".example") || f.contains(".sample") {
+                continue;
+            }
+            for p in patterns {
+                if p.re.is_match(added) && seen.insert((p.title, f.clone())) {
+                    hits.push((p.title, f.clone(), commit.clone()));
+                }
+            }
+        }
+    }
+    let _ = child.wait();
+
+    let current = |f: &str, re: &regex::Regex| {
+        std::fs::read_to_string(root.join(f))
+            .map(|c| re.is_match(&c))
+            .unwrap_or(false)
+    };
+    let hits: Vec<_> = hits
+        .into_iter()
+        .filter(|(desc, f, _)| {
+            !patterns
+                .iter()
+                .any(|p| p.title == *desc && current(f, &p.re))
+        })
+        .collect();
+
+    for (desc, f, sha) in hits.iter().take(MAX_LISTED * 2) {
+        signals.push(signal(
+            "high",
+            "security",
+            format!(
+                "{} в истории git: {} (коммит {}) — секрет нужно отозвать, удаление файла его не скрывает.",
+                desc,
+                f,
+                &sha[..sha.len().min(10)]
+            ),
+        ));
+    }
+    if hits.len() > MAX_LISTED * 2 {
+        signals.push(signal(
+            "high",
+            "security",
+            format!("И ещё {} секретов в истории git.", hits.len() - MAX_LISTED * 2),
+        ));
+    }
+    Ok(())
+}
@@ -0,0 +1,347 @@
+//! Журнал apply (write-ahead): `journal.jsonl` в папке сессии. Каждый шаг
+//! дописывается и сбрасывается на диск до перехода к следующему, поэтому после
+//! сбоя видно, на чём остановился apply, и сессию можно довести или откатить.
+
+use std::collections::HashSet;
+use std::fs::{self, File, OpenOptions};
+use std::io::Write;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use crate::actions;
+use crate::path_policy::PathPolicy;
+use crate::session;
+use crate::types::{ApplyPayload, FileDigest, RecoveryInfo, RecoveryResult, RecoveryStrategy};
+
+const JOURNAL_FILE: &str = "journal.jsonl";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum JournalEntry {
+    Begin {
+        project_root: String,
+        started_at: String,
+        payload: ApplyPayload,
+    },
+    Snapshot {
+        before: Vec<Option<FileDigest>>,
+    },
+    Applied {
+        action_id: String,
+    },
+    /// Действие не применено (best effort) — при восстановлении не повторяется.
+    Skipped {
+        action_id: String,
+    },
+    Commit,
+    RolledBack,
+}
+
+pub struct Journal {
+    file: File,
+}
+
+impl Journal {
+    pub fn create(session_dir: &Path) -> Result<Self, String> {
+        let file = OpenOptions::new()
+            .create_new(true)
+            .append(true)
+            .open(session_dir.join(JOURNAL_FILE))
+            .map_err(|e| e.to_string())?;
+        Ok(Journal { file })
+    }
+
+    fn open(session_dir: &Path) -> Result<Self, String> {
+        let path = session_dir.join(JOURNAL_FILE);
+        let torn = fs::read(&path)
+            .map(|b| b.last().is_some_and(|c| *c != b'\n'))
+            .unwrap_or(false);
+        let mut file = OpenOptions::new()
+            .append(true)
+            .open(&path)
+            .map_err(|e| e.to_string())?;
+        // Запись, оборванную сбоем, закрываем переводом строки, чтобы не склеить со следующей.
+        if torn {
+            file.write_all(b"\n").map_err(|e| e.to_string())?;
+        }
+        Ok(Journal { file })
+    }
+
+    pub fn append(&mut self, entry: &JournalEntry) -> Result<(), String> {
+        let mut line = serde_json::to_string(entry).map_err(|e| e.to_string())?;
+        line.push('\n');
+        self.file
+            .write_all(line.as_bytes())
+            .and_then(|_| self.file.sync_data())
+            .map_err(|e| format!("journal_write_failed: {}", e))
+    }
+}
+
+/// Записи журнала; недописанная последняя строка (сбой во время записи) пропускается.
+pub fn read(session_dir: &Path) -> Vec<JournalEntry> {
+    fs::read_to_string(session_dir.join(JOURNAL_FILE))
+        .unwrap_or_default()
+        .lines()
+        .filter_map(|l| serde_json::from_str(l).ok())
+        .collect()
+}
+
+fn is_finished(entries: &[JournalEntry]) -> bool {
+    entries
+        .iter()
+        .any(|e| matches!(e, JournalEntry::Commit | JournalEntry::RolledBack))
+}
+
+fn recovery_info(session_id: &str, entries: &[JournalEntry]) -> Option<RecoveryInfo> {
+    if is_finished(entries) {
+        return None;
+    }
+    let (project_root, started_at, payload) = entries.iter().find_map(|e| match e {
+        JournalEntry::Begin {
+            project_root,
+            started_at,
+            payload,
+        } => Some((project_root, started_at, payload)),
+        _ => None,
+    })?;
+    let done: HashSet<&str> = applied_ids(entries);
+    let skipped = skipped_ids(entries);
+
+    Some(RecoveryInfo {
+        session_id: session_id.to_string(),
+        project_root: project_root.clone(),
+        started_at: started_at.clone(),
+        snapshot_complete: snapshot_before(entries).is_some(),
+        applied: payload
+            .actions
+            .iter()
+            .filter(|a| done.contains(a.id.as_str()))
+            .map(|a| a.id.clone())
+            .collect(),
+        pending: payload
+            .actions
+            .iter()
+            .filter(|a| !done.contains(a.id.as_str()) && !skipped.contains(&a.id))
+            .map(|a| a.id.clone())
+            .collect(),
+    })
+}
+
+fn applied_ids(entries: &[JournalEntry]) -> HashSet<&str> {
+    entries
+        .iter()
+        .filter_map(|e| match e {
+            JournalEntry::Applied { action_id } => Some(action_id.as_str()),
+            _ => None,
+        })
+        .collect()
+}
+
+fn skipped_ids(entries: &[JournalEntry]) -> Vec<String> {
+    entries
+        .iter()
+        .filter_map(|e| match e {
+            JournalEntry::Skipped { action_id } => Some(action_id.clone()),
+            _ => None,
+        })
+        .collect()
+}
+
+fn snapshot_before(entries: &[JournalEntry]) -> Option<&Vec<Option<FileDigest>>> {
+    entries.iter().find_map(|e| match e {
+        JournalEntry::Snapshot { before } => Some(before),
+        _ => None,
+    })
+}
+
+/// Сессии, apply которых прервался (нет ни `commit`, ни `rolled_back`).
+pub fn incomplete_sessions(history: &Path) -> Vec<RecoveryInfo> {
+    let entries = match fs::read_dir(history) {
+        Ok(e) => e,
+        Err(_) => return vec![],
+    };
+    let mut out: Vec<RecoveryInfo> = entries
+        .flatten()
+        .filter(|e| e.path().is_dir())
+        .filter_map(|e| {
+            let id = e.file_name().to_string_lossy().to_string();
+            recovery_info(&id, &read(&e.path()))
+        })
+        .collect();
+    out.sort_by(|a, b| a.started_at.cmp(&b.started_at));
+    out
+}
+
+/// Проверка при запуске: сессии, прерванные до завершения snapshot'а, проект
+/// ещё не меняли — их журнал просто закрывается. Остальные возвращаются,
+/// чтобы пользователь выбрал, довести их или откатить.
+pub fn startup_check(history: &Path) -> Vec<RecoveryInfo> {
+    let (untouched, pending): (Vec<RecoveryInfo>, Vec<RecoveryInfo>) = incomplete_sessions(history)
+        .into_iter()
+        .partition(|r| !r.snapshot_complete);
+    for r in untouched {
+        let dir = session::session_dir(history, &r.session_id);
+        if let Ok(mut j) = Journal::open(&dir) {
+            let _ = j.append(&JournalEntry::RolledBack);
+        }
+    }
+    pending
+}
+
+fn failed(session_id: &str, error: String, code: &str) -> RecoveryResult {
+    RecoveryResult {
+        ok: false,
+        session_id: session_id.to_string(),
+        applied: vec![],
+        restored: vec![],
+        error: Some(error),
+        error_code: Some(code.into()),
+    }
+}
+
+/// Откат по snapshot. `unsafe_forward` — причина, по которой нельзя было довести:
+/// откат при этом успешен, но отчёт говорит, что стратегия другая.
+fn roll_back(
+    dir: &Path,
+    project_root: &Path,
+    info: &RecoveryInfo,
+    journal: &mut Journal,
+    unsafe_forward: Option<String>,
+) -> RecoveryResult {
+    let restored = if info.snapshot_complete {
+        match session::revert_snapshot(dir, project_root) {
+            Ok(r) => r,
+            Err(e) => return failed(&info.session_id, e, "RECOVERY_FAILED"),
+        }
+    } else {
+        vec![]
+    };
+    if let Err(e) = journal.append(&JournalEntry::RolledBack) {
+        return failed(&info.session_id, e, "JOURNAL_WRITE_FAILED");
+    }
+    let error_code = unsafe_forward.as_ref().map(|_| "ROLL_FORWARD_UNSAFE_ROLLED_BACK".to_string());
+    RecoveryResult {
+        ok: true,
+        session_id: info.session_id.clone(),
+        applied: vec![],
+        restored,
+        error: unsafe_forward,
+        error_code,
+    }
+}
+
+/// Довести можно, только если пути оставшихся действий такие же, как в snapshot'е.
+/// Путь, который уже меняло применённое действие, сверить не с чем — его тоже нельзя.
+fn roll_forward_blocker(
+    project_root: &Path,
+    payload: &ApplyPayload,
+    info: &RecoveryInfo,
+    before: &[Option<FileDigest>],
+) -> Option<String> {
+    let targets = session::action_paths(&payload.actions);
+    let done: Vec<&str> = payload
+        .actions
+        .iter()
+        .filter(|a| info.applied.contains(&a.id))
+        .flat_map(actions::touched_paths)
+        .collect();
+    for a in payload.actions.iter().filter(|a| info.pending.contains(&a.id)) {
+        for p in actions::touched_paths(a) {
+            if done.contains(&p) {
+                return Some(format!("roll_forward_unsafe: {} уже изменён применённым действием", p));
+            }
+            let expected = targets
+                .iter()
+                .position(|t| t == Path::new(p))
+                .and_then(|i| before.get(i).cloned())
+                .flatten();
+            if session::file_digest(&project_root.join(p)) != expected {
+                return Some(format!("roll_forward_unsafe: {} изменился после snapshot", p));
+            }
+        }
+    }
+    None
+}
+
+pub fn recover(
+    history: &Path,
+    session_id: &str,
+    strategy: RecoveryStrategy,
+    policy: &PathPolicy,
+    app_version: &str,
+) -> RecoveryResult {
+    if !session::is_valid_session_id(session_id) {
+        return failed(session_id, "session_id_invalid".into(), "SESSION_NOT_FOUND");
+    }
+    let dir = session::session_dir(history, session_id);
+    let entries = read(&dir);
+    let info = match recovery_info(session_id, &entries) {
+        Some(i) => i,
+        None => return failed(session_id, "nothing_to_recover".into(), "RECOVERY_NOT_NEEDED"),
+    };
+    let mut journal = match Journal::open(&dir) {
+        Ok(j) => j,
+        Err(e) => return failed(session_id, e, "JOURNAL_WRITE_FAILED"),
+    };
+    let project_root = Path::new(&info.project_root);
+
+    match strategy {
+        RecoveryStrategy::RollBack => roll_back(&dir, project_root, &info, &mut journal, None),
+        RecoveryStrategy::RollForward => {
+            let before = match snapshot_before(&entries) {
+                Some(b) => b.clone(),
+                None => {
+                    return failed(session_id, "snapshot_incomplete".into(), "RECOVERY_SNAPSHOT_INCOMPLETE")
+                }
+            };
+            let payload = entries
+                .iter()
+                .find_map(|e| match e {
+                    JournalEntry::Begin { payload, .. } => Some(payload.clone()),
+                    _ => None,
+                })
+                .unwrap_or_else(|| ApplyPayload {
+                    path: info.project_root.clone(),
+                    ..Default::default()
+                });
+
+            // Прерванное действие или правка после сбоя: повтор дал бы двойное изменение.
+            if let Some(reason) = roll_forward_blocker(project_root, &payload, &info, &before) {
+                log::warn!("session {}: {}, откатываю", session_id, reason);
+                return roll_back(&dir, project_root, &info, &mut journal, Some(reason));
+            }
+
+            let mut applied = info.applied.clone();
+            for a in payload.actions.iter().filter(|a| info.pending.contains(&a.id)) {
+                let step = actions::execute(project_root, policy, a).and_then(|_| {
+                    journal.append(&JournalEntry::Applied {
+                        action_id: a.id.clone(),
+                    })
+                });
+                if let Err(e) = step {
+                    let _ = session::revert_snapshot(&dir, project_root);
+                    let _ = journal.append(&JournalEntry::RolledBack);
+                    return failed(session_id, e, "RECOVERY_FAILED_ROLLED_BACK");
+                }
+                applied.push(a.id.clone());
+            }
+
+            // В журнале упавшие и не начатые действия не различаются: snapshot остаётся для всех.
+            let skipped = skipped_ids(&entries);
+            let finished = session::finalize_session(&dir, project_root, session_id, app_version, &payload, before, &skipped, &[])
+                .and_then(|_| journal.append(&JournalEntry::Commit));
+            if let Err(e) = finished {
+                return failed(session_id, e, "SESSION_META_WRITE_FAILED");
+            }
+            RecoveryResult {
+                ok: true,
+                session_id: session_id.to_string(),
+                applied,
+                restored: vec![],
+                error: None,
+                error_code: None,
+            }
+        }
+    }
+}
@@ -0,0 +1,37 @@
+//! Ядро PAPA YU без Tauri: анализ проектов и отчёты, движок действий (предпросмотр,
+//! apply, откат, восстановление), клиент LLM. Ход работы уходит в `ProgressSink`;
+//! приложение отправляет его в окно, CLI `papayu` — в терминал.
+
+pub mod actions;
+pub mod ai_actions;
+pub mod analysis_job;
+pub mod analysis_store;
+pub mod analyze;
+pub mod app_dirs;
+pub mod apply;
+pub mod cli;
+pub mod context;
+pub mod deep_analysis;
+pub mod diff;
+pub mod file_index;
+pub mod git;
+pub mod git_history;
+pub mod journal;
+pub mod llm;
+pub mod merge;
+pub mod overlay;
+pub mod patch;
+pub mod path_policy;
+pub mod preview;
+pub mod progress;
+pub mod project_config;
+pub mod project_fs;
+pub mod report_html;
+pub mod rules;
+pub mod sarif;
+pub mod session;
+pub mod types;
+pub mod undo;
+pub mod validate;
+pub mod verify;
+pub mod walker;
@@ -0,0 +1,142 @@
+//! Построчное трёхстороннее слияние (diff3) для undo в режиме merge.
+
+use std::collections::HashMap;
+
+use similar::{capture_diff_slices, Algorithm, DiffOp};
+
+pub struct MergeOutcome {
+    pub text: String,
+    pub conflicts: usize,
+}
+
+/// Соответствие строк base → строки other для совпадающих участков.
+fn matches(base: &[&str], other: &[&str]) -> HashMap<usize, usize> {
+    let mut map = HashMap::new();
+    for op in capture_diff_slices(Algorithm::Myers, base, other) {
+        if let DiffOp::Equal {
+            old_index,
+            new_index,
+            len,
+        } = op
+        {
+            for k in 0..len {
+                map.insert(old_index + k, new_index + k);
+            }
+        }
+    }
+    map
+}
+
+/// Сливает изменения `ours` и `theirs` относительно общего предка `base`.
+/// Непримиримые участки оборачиваются в маркеры конфликта в стиле git.
+pub fn merge3(base: &str, ours: &str, theirs: &str, ours_label: &str, theirs_label: &str) -> MergeOutcome {
+    let b: Vec<&str> = base.split_inclusive('\n').collect();
+    let o: Vec<&str> = ours.split_inclusive('\n').collect();
+    let t: Vec<&str> = theirs.split_inclusive('\n').collect();
+    let mo = matches(&b, &o);
+    let mt = matches(&b, &t);
+
+    let mut out = String::new();
+    let mut conflicts = 0;
+    let (mut ib, mut io, mut it) = (0, 0, 0);
+
+    loop {
+        // Ближайшая строка base, совпадающая в обеих версиях после текущих позиций.
+        let sync = (ib..b.len()).find(|i| {
+            matches!((mo.get(i), mt.get(i)), (Some(&x), Some(&y)) if x >= io && y >= it)
+        });
+
+        if let Some(i) = sync {
+            if i == ib && mo[&i] == io && mt[&i] == it {
+                out.push_str(b[ib]);
+                ib += 1;
+                io += 1;
+                it += 1;
+                continue;
+            }
+        }
+
+        let (eb, eo, et) = match sync {
+            Some(i) => (i, mo[&i], mt[&i]),
+            None => (b.len(), o.len(), t.len()),
+        };
+        let (cb, co, ct) = (&b[ib..eb], &o[io..eo], &t[it..et]);
+
+        if co == cb || co == ct {
+            ct.iter().for_each(|l| out.push_str(l));
+        } else if ct == cb {
+            co.iter().for_each(|l| out.push_str(l));
+        } else {
+            conflicts += 1;
+            push_conflict(&mut out, co, ct, ours_label, theirs_label);
+        }
+
+        if sync.is_none() {
+            break;
+        }
+        ib = eb;
+        io = eo;
+        it = et;
+    }
+
+    MergeOutcome { text: out, conflicts }
+}
+
+fn push_conflict(out: &mut String, ours: &[&str], theirs: &[&str], ours_label: &str, theirs_label: &str) {
+    let push_lines = |out: &mut String, lines: &[&str]| {
+        for l in lines {
+            out.push_str(l);
+            if !l.ends_with('\n') {
+                out.push('\n');
+            }
+        }
+    };
+    out.push_str(&format!("<<<<<<< {}\n", ours_label));
+    push_lines(out, ours);
+    out.push_str("=======\n");
+    push_lines(out, theirs);
+    out.push_str(&format!(">>>>>>> {}\n", theirs_label));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn merge(base: &str, ours: &str, theirs: &str) -> MergeOutcome {
+        merge3(base, ours, theirs, "ours", "theirs")
+    }
+
+    #[test]
+    fn non_overlapping_changes_merge_cleanly() {
+        let m = merge("a\nb\nc\nd\n", "a\nB\nc\nd\n", "a\nb\nc\nD\n");
+        assert_eq!(m.conflicts, 0);
+        assert_eq!(m.text, "a\nB\nc\nD\n");
+
+        let m = merge("a\nb\nc\n", "a\nb\nc\n", "a\nc\n");
+        assert_eq!(m.conflicts, 0);
+        assert_eq!(m.text, "a\nc\n");
+    }
+
+    #[test]
+    fn overlapping_changes_conflict() {
+        let m = merge("a\nb\nc\n", "a\nours\nc\n", "a\ntheirs\nc\n");
+        assert_eq!(m.conflicts, 1);
+        assert_eq!(m.text, "a\n<<<<<<< ours\nours\n=======\ntheirs\n>>>>>>> theirs\nc\n");
+
+        // Одинаковая правка с двух сторон — не конфликт.
+        let m = merge("a\nb\nc\n", "a\nB\nc\n", "a\nB\nc\n");
+        assert_eq!((m.conflicts, m.text.as_str()), (0, "a\nB\nc\n"));
+    }
+
+    #[test]
+    fn insert_at_eof() {
+        let m = merge("a\nb\n", "a\nb\nc\n", "A\nb\n");
+        assert_eq!(m.conflicts, 0);
+        assert_eq!(m.text, "A\nb\nc\n");
+
+        // Обе стороны дописали разное в конец; строка без перевода строки в маркерах его получает.
+        let m = merge("a\n", "a\nours", "a\ntheirs\n");
+        assert_eq!(m.conflicts, 1);
+        assert_eq!(m.text, "a\n<<<<<<< ours\nours\n=======\ntheirs\n>>>>>>> theirs\n");
+    }
+}
@@ -0,0 +1,340 @@
+//! Пробный apply: действия выполняются над виртуальным деревом проекта в памяти
+//! поверх диска. Проверяется то, на чём споткнулся бы настоящий apply, — права
+//! на запись, файл на месте папки, место на томе проекта; сам проект не меняется.
+//! Итоговое дерево читается через `ProjectFs`, так что по нему можно прогнать анализ.
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::actions;
+use crate::path_policy::PathPolicy;
+use crate::project_fs::{DiskFs, Entry, ProjectFs};
+use crate::types::{Action, ActionKind};
+
+#[derive(Debug, Clone)]
+enum Node {
+    /// Папка, созданная в overlay: содержимое с диска под ней не видно.
+    Dir,
+    File(Vec<u8>),
+    /// Файл с содержимым файла на диске (после переноса или копии).
+    Disk(PathBuf),
+    Removed,
+}
+
+/// Где искать путь: в overlay, на диске или нигде (под удалённым или новым путём).
+enum Found<'a> {
+    Node(&'a Node),
+    Disk,
+    Hidden,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Kind {
+    File(u64),
+    Dir,
+}
+
+pub struct Overlay {
+    root: PathBuf,
+    nodes: HashMap<PathBuf, Node>,
+    /// Сколько байт ещё можно записать на том проекта (`None` — узнать не удалось).
+    free: Option<u64>,
+}
+
+#[cfg(unix)]
+fn dir_writable(dir: &Path) -> bool {
+    use std::os::unix::ffi::OsStrExt;
+    match std::ffi::CString::new(dir.as_os_str().as_bytes()) {
+        // SAFETY: строка с завершающим нулём живёт до конца вызова.
+        Ok(c) => unsafe { libc::access(c.as_ptr(), libc::W_OK | libc::X_OK) == 0 },
+        Err(_) => false,
+    }
+}
+
+#[cfg(not(unix))]
+fn dir_writable(dir: &Path) -> bool {
+    fs::metadata(dir).map(|m| !m.permissions().readonly()).unwrap_or(false)
+}
+
+fn disk_kind(p: &Path) -> Option<Kind> {
+    match fs::metadata(p) {
+        Ok(m) if m.is_dir() => Some(Kind::Dir),
+        Ok(m) => Some(Kind::File(m.len())),
+        // Висячий symlink: путь занят, но это не папка.
+        Err(_) => p.symlink_metadata().ok().map(|_| Kind::File(0)),
+    }
+}
+
+impl Overlay {
+    pub fn new(project_root: &Path) -> Self {
+        Overlay {
+            root: project_root.to_path_buf(),
+            nodes: HashMap::new(),
+            free: fs4::available_space(project_root).ok(),
+        }
+    }
+
+    fn rel(&self, p: &Path) -> String {
+        p.strip_prefix(&self.root).unwrap_or(p).display().to_string()
+    }
+
+    fn lookup(&self, p: &Path) -> Found<'_> {
+        if let Some(n) = self.nodes.get(p) {
+            return Found::Node(n);
+        }
+        for anc in p.ancestors().skip(1) {
+            if self.nodes.contains_key(anc) {
+                return Found::Hidden;
+            }
+        }
+        Found::Disk
+    }
+
+    fn kind(&self, p: &Path) -> Option<Kind> {
+        match self.lookup(p) {
+            Found::Node(Node::Dir) => Some(Kind::Dir),
+            Found::Node(Node::File(b)) => Some(Kind::File(b.len() as u64)),
+            Found::Node(Node::Disk(src)) => Some(Kind::File(fs::metadata(src).map(|m| m.len()).unwrap_or(0))),
+            Found::Node(Node::Removed) | Found::Hidden => None,
+            Found::Disk => disk_kind(p),
+        }
+    }
+
+    /// Содержимое файла в его текущем виде — для переноса и копии внутри overlay.
+    fn file_node(&self, p: &Path) -> Node {
+        match self.lookup(p) {
+            Found::Node(Node::File(b)) => Node::File(b.clone()),
+            Found::Node(Node::Disk(src)) => Node::Disk(src.clone()),
+            _ => Node::Disk(p.to_path_buf()),
+        }
+    }
+
+    /// Созданные в overlay папки доступны на запись; права остальных берутся с диска.
+    fn check_writable(&self, dir: &Path) -> Result<(), String> {
+        if matches!(self.lookup(dir), Found::Disk) && !dir_writable(dir) {
+            return Err(format!("permission_denied: {}", self.rel(dir)));
+        }
+        Ok(())
+    }
+
+    fn reserve(&mut self, bytes: u64) -> Result<(), String> {
+        if let Some(free) = self.free {
+            if bytes > free {
+                return Err(format!("insufficient_disk_space: нужно {} байт, свободно {}", bytes, free));
+            }
+            self.free = Some(free - bytes);
+        }
+        Ok(())
+    }
+
+    fn release(&mut self, bytes: u64) {
+        if let Some(free) = self.free.as_mut() {
+            *free = free.saturating_add(bytes);
+        }
+    }
+
+    /// Как `fs::create_dir_all`: недостающие папки создаются в ближайшей существующей.
+    fn create_dir_all(&mut self, dir: &Path) -> Result<(), String> {
+        let mut missing = vec![];
+        let mut cur = dir;
+        loop {
+            match self.kind(cur) {
+                Some(Kind::Dir) => break,
+                Some(Kind::File(_)) => return Err(format!("parent_not_directory: {}", self.rel(cur))),
+                None => missing.push(cur.to_path_buf()),
+            }
+            match cur.parent() {
+                Some(p) => cur = p,
+                None => break,
+            }
+        }
+        if !missing.is_empty() {
+            self.check_writable(cur)?;
+        }
+        for p in missing {
+            self.nodes.insert(p, Node::Dir);
+        }
+        Ok(())
+    }
+
+    fn write_file(&mut self, p: &Path, bytes: Vec<u8>) -> Result<(), String> {
+        let parent = p.parent().unwrap_or(&self.root).to_path_buf();
+        self.create_dir_all(&parent)?;
+        let old = match self.kind(p) {
+            Some(Kind::Dir) => return Err(format!("is_a_directory: {}", self.rel(p))),
+            Some(Kind::File(n)) => n,
+            None => 0,
+        };
+        self.check_writable(&parent)?;
+        // Новое содержимое пишется во временный файл рядом, старое освобождается после rename.
+        self.reserve(bytes.len() as u64)?;
+        self.release(old);
+        self.nodes.insert(p.to_path_buf(), Node::File(bytes));
+        Ok(())
+    }
+
+    /// Папка с диска, внутри которой что-то нельзя удалить (для `delete_dir`).
+    fn first_locked_subdir(&self, dir: &Path) -> Option<PathBuf> {
+        if !matches!(self.lookup(dir), Found::Disk) {
+            return None;
+        }
+        if !dir_writable(dir) && !DiskFs.read_dir(dir).is_empty() {
+            return Some(dir.to_path_buf());
+        }
+        DiskFs
+            .read_dir(dir)
+            .into_iter()
+            .filter(|e| e.is_dir && !e.is_symlink)
+            .find_map(|e| self.first_locked_subdir(&e.path))
+    }
+
+    /// Переносит содержимое папки `src` в `dst` поштучно, чтобы оно было видно по новому пути.
+    fn copy_tree(&mut self, src: &Path, dst: &Path) {
+        self.nodes.insert(dst.to_path_buf(), Node::Dir);
+        for e in self.read_dir(src) {
+            let name = match e.path.file_name() {
+                Some(n) => n.to_os_string(),
+                None => continue,
+            };
+            if e.is_dir && !e.is_symlink {
+                self.copy_tree(&e.path, &dst.join(name));
+            } else {
+                let node = self.file_node(&e.path);
+                self.nodes.insert(dst.join(name), node);
+            }
+        }
+    }
+
+    /// Проверенный путь `to` — те же проверки, что у `actions::destination`.
+    fn destination(&mut self, policy: &PathPolicy, a: &Action, source: &Path) -> Result<PathBuf, String> {
+        let to = a.to.as_deref().ok_or_else(|| "destination_missing".to_string())?;
+        let dst = policy.resolve(&self.root, to).map_err(|e| e.error)?;
+        if self.kind(&dst).is_some() {
+            return Err(format!("destination_exists: {}", to));
+        }
+        if dst.starts_with(source) {
+            return Err(format!("destination_inside_source: {}", to));
+        }
+        if let Some(parent) = dst.parent() {
+            self.create_dir_all(parent)?;
+            self.check_writable(parent)?;
+        }
+        Ok(dst)
+    }
+
+    /// То же, что `actions::execute`, но над overlay. Ошибки — в тех же строках,
+    /// плюс `permission_denied`, `parent_not_directory`, `is_a_directory`,
+    /// `not_a_directory`, `insufficient_disk_space`.
+    pub fn execute(&mut self, policy: &PathPolicy, a: &Action) -> Result<(), String> {
+        let abs = policy.resolve(&self.root, &a.path).map_err(|e| e.error)?;
+        let parent = abs.parent().unwrap_or(&self.root).to_path_buf();
+
+        match a.kind {
+            ActionKind::CreateDir => {
+                if let Some(Kind::File(_)) = self.kind(&abs) {
+                    return Err(format!("not_a_directory: {}", a.path));
+                }
+                self.create_dir_all(&abs)?;
+            }
+            ActionKind::DeleteDir => match self.kind(&abs) {
+                None => {}
+                Some(Kind::File(_)) => return Err(format!("not_a_directory: {}", a.path)),
+                Some(Kind::Dir) => {
+                    self.check_writable(&parent)?;
+                    if let Some(locked) = self.first_locked_subdir(&abs) {
+                        return Err(format!("permission_denied: {}", self.rel(&locked)));
+                    }
+                    self.nodes.insert(abs, Node::Removed);
+                }
+            },
+            ActionKind::CreateFile | ActionKind::UpdateFile => {
+                let content = a.content.as_ref().ok_or_else(|| "content_missing".to_string())?;
+                self.write_file(&abs, content.as_bytes().to_vec())?;
+            }
+            ActionKind::DeleteFile => match self.kind(&abs) {
+                None => {}
+                Some(Kind::Dir) => return Err(format!("is_a_directory: {}", a.path)),
+                Some(Kind::File(n)) => {
+                    self.check_writable(&parent)?;
+                    self.release(n);
+                    self.nodes.insert(abs, Node::Removed);
+                }
+            },
+            ActionKind::ApplyPatch | ActionKind::SearchReplace | ActionKind::InsertAfter => {
+                let current = self
+                    .read_to_string(&abs)
+                    .ok_or_else(|| format!("patch_target_unreadable: {}", a.path))?;
+                let patched = actions::patched_content(&current, a)?;
+                self.write_file(&abs, patched.into_bytes())?;
+            }
+            ActionKind::MoveFile | ActionKind::CopyFile => {
+                let len = match self.kind(&abs) {
+                    Some(Kind::File(n)) => n,
+                    _ => return Err(format!("source_not_found: {}", a.path)),
+                };
+                let dst = self.destination(policy, a, &abs)?;
+                let node = self.file_node(&abs);
+                if matches!(a.kind, ActionKind::MoveFile) {
+                    self.check_writable(&parent)?;
+                    self.nodes.insert(abs, Node::Removed);
+                } else {
+                    self.reserve(len)?;
+                }
+                self.nodes.insert(dst, node);
+            }
+            ActionKind::MoveDir => {
+                if self.kind(&abs) != Some(Kind::Dir) {
+                    return Err(format!("source_not_found: {}", a.path));
+                }
+                let dst = self.destination(policy, a, &abs)?;
+                self.check_writable(&parent)?;
+                self.copy_tree(&abs, &dst);
+                self.nodes.insert(abs, Node::Removed);
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl ProjectFs for Overlay {
+    fn read_dir(&self, dir: &Path) -> Vec<Entry> {
+        let mut out: Vec<Entry> = vec![];
+        if matches!(self.lookup(dir), Found::Disk) {
+            out.extend(
+                DiskFs
+                    .read_dir(dir)
+                    .into_iter()
+                    .filter(|e| matches!(self.lookup(&e.path), Found::Disk)),
+            );
+        }
+        for p in self.nodes.keys() {
+            if p.parent() != Some(dir) {
+                continue;
+            }
+            let (is_dir, len) = match self.kind(p) {
+                Some(Kind::Dir) => (true, 0),
+                Some(Kind::File(len)) => (false, len),
+                None => continue,
+            };
+            out.push(Entry {
+                path: p.clone(),
+                is_symlink: false,
+                is_dir,
+                len,
+                modified: None,
+            });
+        }
+        out
+    }
+
+    fn read(&self, path: &Path) -> Option<Vec<u8>> {
+        match self.lookup(path) {
+            Found::Node(Node::File(b)) => Some(b.clone()),
+            Found::Node(Node::Disk(src)) => fs::read(src).ok(),
+            Found::Node(_) | Found::Hidden => None,
+            Found::Disk => fs::read(path).ok(),
+        }
+    }
+}
@@ -0,0 +1,404 @@
+//! Точечные правки файлов: unified diff, замена блока и вставка после якоря.
+//! Контекст ищется с допуском — смещение строк, отличия в пробелах, до двух
+//! несовпавших строк контекста по краям ханка (как fuzz у GNU patch).
+
+/// Сколько строк контекста с каждого края ханка можно не найти в файле.
+const MAX_FUZZ: usize = 2;
+
+#[derive(Debug, Clone, PartialEq)]
+enum HunkLine {
+    Context(String),
+    Remove(String),
+    Add(String),
+}
+
+#[derive(Debug, Clone)]
+struct Hunk {
+    header: String,
+    old_start: usize,
+    /// Строк старой версии в ханке; 0 — чистая вставка после строки `old_start`.
+    old_count: usize,
+    lines: Vec<HunkLine>,
+    /// `\ No newline at end of file` после последней строки новой версии.
+    no_newline_at_end: bool,
+}
+
+/// Текст, разбитый на строки без переводов строк, с запомненным стилем концов строк.
+struct Lines {
+    lines: Vec<String>,
+    eol: &'static str,
+    trailing_newline: bool,
+}
+
+impl Lines {
+    fn parse(text: &str) -> Self {
+        let eol = if text.contains("\r\n") { "\r\n" } else { "\n" };
+        let trailing_newline = text.is_empty() || text.ends_with('\n');
+        let lines = text
+            .split_inclusive('\n')
+            .map(|l| l.trim_end_matches('\n').trim_end_matches('\r').to_string())
+            .collect();
+        Lines {
+            lines,
+            eol,
+            trailing_newline,
+        }
+    }
+
+    fn join(&self) -> String {
+        let mut out = self.lines.join(self.eol);
+        if self.trailing_newline && !self.lines.is_empty() {
+            out.push_str(self.eol);
+        }
+        out
+    }
+}
+
+fn split_block(block: &str) -> Vec<String> {
+    Lines::parse(block).lines
+}
+
+/// `start[,count]`; без count — одна строка.
+fn parse_range(s: &str) -> Option<(usize, usize)> {
+    let mut parts = s.split(',');
+    let start = parts.next()?.parse().ok()?;
+    let count = match parts.next() {
+        Some(c) => c.parse().ok()?,
+        None => 1,
+    };
+    Some((start, count))
+}
+
+fn parse_header(line: &str) -> Option<(usize, usize)> {
+    let rest = line.strip_prefix("@@ -")?;
+    let old = rest.split_whitespace().next()?;
+    parse_range(old)
+}
+
+fn parse_unified(diff: &str) -> Result<Vec<Hunk>, String> {
+    let mut hunks: Vec<Hunk> = vec![];
+    for line in diff.trim_end().lines() {
+        let line = line.trim_end_matches('\r');
+        if line.starts_with("@@") {
+            let (old_start, old_count) =
+                parse_header(line).ok_or_else(|| format!("patch_failed: bad hunk header '{}'", line))?;
+            hunks.push(Hunk {
+                header: line.to_string(),
+                old_start,
+                old_count,
+                lines: vec![],
+                no_newline_at_end: false,
+            });
+            continue;
+        }
+        let hunk = match hunks.last_mut() {
+            Some(h) => h,
+            // Заголовки `diff --git`, `index`, `---`, `+++` до первого ханка.
+            None => continue,
+        };
+        if line.starts_with("--- ") || line.starts_with("+++ ") || line.starts_with("diff ") {
+            continue;
+        }
+        match line.chars().next() {
+            Some('+') => hunk.lines.push(HunkLine::Add(line[1..].to_string())),
+            Some('-') => hunk.lines.push(HunkLine::Remove(line[1..].to_string())),
+            Some(' ') => hunk.lines.push(HunkLine::Context(line[1..].to_string())),
+            Some('\\') => {
+                if matches!(hunk.lines.last(), Some(HunkLine::Add(_)) | Some(HunkLine::Context(_))) {
+                    hunk.no_newline_at_end = true;
+                }
+            }
+            // Пустая строка контекста, у которой редактор съел пробел.
+            None => hunk.lines.push(HunkLine::Context(String::new())),
+            Some(_) => return Err(format!("patch_failed: unexpected line in hunk {}: '{}'", hunk.header, line)),
+        }
+    }
+    if hunks.is_empty() {
+        return Err("patch_failed: no hunks in diff".into());
+    }
+    Ok(hunks)
+}
+
+/// Уровни сравнения строк: точно, без хвостовых пробелов, без пробелов по краям.
+fn line_eq(a: &str, b: &str, level: u8) -> bool {
+    match level {
+        0 => a == b,
+        1 => a.trim_end() == b.trim_end(),
+        _ => a.trim() == b.trim(),
+    }
+}
+
+fn block_at(lines: &[String], pos: usize, block: &[&str], level: u8) -> bool {
+    pos + block.len() <= lines.len() && block.iter().enumerate().all(|(k, b)| line_eq(&lines[pos + k], b, level))
+}
+
+/// Ближайшее к `expected` вхождение блока, не раньше `from`.
+fn find_near(lines: &[String], block: &[&str], expected: usize, from: usize, level: u8) -> Option<usize> {
+    if block.len() > lines.len() {
+        return None;
+    }
+    let last = lines.len() - block.len();
+    let expected = expected.clamp(from, last.max(from));
+    for d in 0..=last.max(expected) {
+        let fwd = expected + d;
+        if fwd <= last && block_at(lines, fwd, block, level) {
+            return Some(fwd);
+        }
+        if d > 0 && d <= expected && expected - d >= from && block_at(lines, expected - d, block, level) {
+            return Some(expected - d);
+        }
+    }
+    None
+}
+
+/// Все вхождения блока строк; используется там, где место не задано номером строки.
+fn find_all(lines: &[String], block: &[&str], level: u8) -> Vec<usize> {
+    if block.is_empty() || block.len() > lines.len() {
+        return vec![];
+    }
+    (0..=lines.len() - block.len())
+        .filter(|&p| block_at(lines, p, block, level))
+        .collect()
+}
+
+/// Единственное вхождение блока: сначала точное сравнение, затем с допуском по пробелам.
+fn find_unique(lines: &[String], block: &[&str], what: &str) -> Result<usize, String> {
+    for level in 0..=2 {
+        match find_all(lines, block, level).as_slice() {
+            [] => continue,
+            [p] => return Ok(*p),
+            many => return Err(format!("patch_failed: {}_ambiguous ({} matches)", what, many.len())),
+        }
+    }
+    Err(format!("patch_failed: {}_not_found", what))
+}
+
+/// Ханк, найденный в файле: где и сколько строк заменить, на что, и сколько
+/// строк контекста в начале было отброшено.
+struct Placement {
+    pos: usize,
+    old_len: usize,
+    new: Vec<String>,
+    lead: usize,
+}
+
+/// Применяет ханк, отбрасывая до `fuzz` строк контекста с каждого края.
+fn try_hunk(lines: &[String], hunk: &Hunk, fuzz: usize, expected: usize, from: usize) -> Option<Placement> {
+    let lead = hunk
+        .lines
+        .iter()
+        .take_while(|l| matches!(l, HunkLine::Context(_)))
+        .count()
+        .min(fuzz);
+    let trail = hunk
+        .lines
+        .iter()
+        .rev()
+        .take_while(|l| matches!(l, HunkLine::Context(_)))
+        .count()
+        .min(fuzz);
+    if lead + trail >= hunk.lines.len() && fuzz > 0 {
+        return None;
+    }
+    let body = &hunk.lines[lead..hunk.lines.len() - trail];
+    let old: Vec<&str> = body
+        .iter()
+        .filter_map(|l| match l {
+            HunkLine::Context(s) | HunkLine::Remove(s) => Some(s.as_str()),
+            HunkLine::Add(_) => None,
+        })
+        .collect();
+    let expected = expected + lead;
+
+    let pos = if old.is_empty() {
+        expected.clamp(from, lines.len())
+    } else {
+        (0..=2).find_map(|level| find_near(lines, &old, expected, from, level))?
+    };
+
+    // Строки контекста берутся из файла: при совпадении с допуском его пробелы сохраняются.
+    let mut new = vec![];
+    let mut k = pos;
+    for l in body {
+        match l {
+            HunkLine::Context(_) => {
+                new.push(lines[k].clone());
+                k += 1;
+            }
+            HunkLine::Remove(_) => k += 1,
+            HunkLine::Add(s) => new.push(s.clone()),
+        }
+    }
+    Some(Placement {
+        pos,
+        old_len: old.len(),
+        new,
+        lead,
+    })
+}
+
+/// Применяет unified diff. Все ханки проверяются; если хоть один не лёг,
+/// возвращается ошибка с перечнем несовпавших ханков, и файл не меняется.
+pub fn apply_unified(original: &str, diff: &str) -> Result<String, String> {
+    let hunks = parse_unified(diff)?;
+    let mut text = Lines::parse(original);
+    let mut failed: Vec<String> = vec![];
+    let mut delta: isize = 0;
+    let mut from = 0;
+
+    for (i, hunk) in hunks.iter().enumerate() {
+        // При пустом старом диапазоне `-N,0` вставка идёт после строки N, а не перед ней.
+        let nominal = if hunk.old_count == 0 {
+            hunk.old_start
+        } else {
+            hunk.old_start.saturating_sub(1)
+        };
+        let expected = (nominal as isize + delta).max(0) as usize;
+        let applied = (0..=MAX_FUZZ).find_map(|fuzz| try_hunk(&text.lines, hunk, fuzz, expected, from));
+        match applied {
+            Some(p) => {
+                let new_len = p.new.len();
+                text.lines.splice(p.pos..p.pos + p.old_len, p.new);
+                delta += new_len as isize - p.old_len as isize + p.pos as isize - p.lead as isize - expected as isize;
+                from = p.pos + new_len;
+                if hunk.no_newline_at_end && from == text.lines.len() {
+                    text.trailing_newline = false;
+                }
+            }
+            None => failed.push(format!("hunk {}/{} ({}): context_not_found", i + 1, hunks.len(), hunk.header)),
+        }
+    }
+
+    if !failed.is_empty() {
+        return Err(format!("patch_failed: {}", failed.join("; ")));
+    }
+    Ok(text.join())
+}
+
+/// Заменяет единственное вхождение `search` на `replace`. Если точного вхождения
+/// нет, блок ищется построчно без учёта пробелов по краям строк.
+pub fn search_replace(original: &str, search: &str, replace: &str) -> Result<String, String> {
+    if search.is_empty() {
+        return Err("patch_failed: search_block_empty".into());
+    }
+    match original.matches(search).count() {
+        1 => return Ok(original.replacen(search, replace, 1)),
+        0 => {}
+        n => return Err(format!("patch_failed: search_block_ambiguous ({} matches)", n)),
+    }
+    let mut text = Lines::parse(original);
+    let block = split_block(search);
+    let block: Vec<&str> = block.iter().map(String::as_str).collect();
+    let pos = find_unique(&text.lines, &block, "search_block")?;
+    text.lines.splice(pos..pos + block.len(), split_block(replace));
+    Ok(text.join())
+}
+
+/// Вставляет `content` отдельными строками сразу после строки, которой заканчивается `anchor`.
+pub fn insert_after(original: &str, anchor: &str, content: &str) -> Result<String, String> {
+    if anchor.trim().is_empty() {
+        return Err("patch_failed: anchor_empty".into());
+    }
+    let mut text = Lines::parse(original);
+    let anchor_lines = split_block(anchor);
+    let anchor_lines: Vec<&str> = anchor_lines.iter().map(String::as_str).collect();
+
+    let end = match original.matches(anchor).count() {
+        1 => {
+            let at = original.find(anchor).unwrap_or(0) + anchor.len();
+            // Якорь, оканчивающийся переводом строки, относится к предыдущей строке.
+            let at = if anchor.ends_with('\n') { at - 1 } else { at };
+            original[..at].matches('\n').count() + 1
+        }
+        0 => find_unique(&text.lines, &anchor_lines, "anchor")? + anchor_lines.len(),
+        n => return Err(format!("patch_failed: anchor_ambiguous ({} matches)", n)),
+    };
+    // Перевод строки в конце файла остаётся как был: вставка после последней
+    // строки файла без `\n` даёт файл без `\n`.
+    let end = end.min(text.lines.len());
+    text.lines.splice(end..end, split_block(content));
+    Ok(text.join())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FILE: &str = "one\ntwo\nthree\nfour\nfive\nsix\nseven\n";
+
+    #[test]
+    fn unified_hunk_applies_at_offset() {
+        let text = format!("zero\nzero\n{}", FILE);
+        let diff = "@@ -3,3 +3,3 @@\n three\n-four\n+FOUR\n five\n";
+        assert_eq!(
+            apply_unified(&text, diff).unwrap(),
+            "zero\nzero\none\ntwo\nthree\nFOUR\nfive\nsix\nseven\n"
+        );
+        // Второй ханк ищется с учётом сдвига от первого.
+        let diff = "@@ -1,2 +1,3 @@\n one\n+one-and-half\n two\n@@ -6,2 +7,2 @@\n six\n-seven\n+SEVEN\n";
+        assert_eq!(
+            apply_unified(FILE, diff).unwrap(),
+            "one\none-and-half\ntwo\nthree\nfour\nfive\nsix\nSEVEN\n"
+        );
+    }
+
+    #[test]
+    fn unified_zero_context_insertion() {
+        let text = "one\ntwo\nthree\nfour\nfive\n";
+        assert_eq!(apply_unified(text, "@@ -3,0 +4 @@\n+X\n").unwrap(), "one\ntwo\nthree\nX\nfour\nfive\n");
+        assert_eq!(apply_unified(text, "@@ -0,0 +1 @@\n+X\n").unwrap(), "X\none\ntwo\nthree\nfour\nfive\n");
+        assert_eq!(apply_unified(text, "@@ -5,0 +6,2 @@\n+X\n+Y\n").unwrap(), "one\ntwo\nthree\nfour\nfive\nX\nY\n");
+    }
+
+    #[test]
+    fn unified_context_fuzz() {
+        // Fuzz 1: крайние строки контекста не совпали.
+        let diff = "@@ -2,5 +2,5 @@\n TWO?\n three\n-four\n+FOUR\n five\n SIX?\n";
+        assert_eq!(apply_unified(FILE, diff).unwrap(), FILE.replace("four", "FOUR"));
+        // Fuzz 2: по две строки с каждого края.
+        let diff = "@@ -1,7 +1,7 @@\n ONE?\n TWO?\n three\n-four\n+FOUR\n five\n SIX?\n SEVEN?\n";
+        assert_eq!(apply_unified(FILE, diff).unwrap(), FILE.replace("four", "FOUR"));
+        // Три несовпавшие строки — уже не тот контекст.
+        let diff = "@@ -1,7 +1,7 @@\n ONE?\n TWO?\n THREE?\n-four\n+FOUR\n five\n";
+        let err = apply_unified(FILE, diff).unwrap_err();
+        assert!(err.contains("hunk 1/1") && err.contains("context_not_found"), "{}", err);
+    }
+
+    #[test]
+    fn unified_tolerates_whitespace_and_keeps_file_spacing() {
+        let text = "fn main() {\n    let x = 1;  \n    println!(\"{}\", x);\n}\n";
+        let diff = "@@ -1,4 +1,4 @@\n fn main() {\n let x = 1;\n-println!(\"{}\", x);\n+    dbg!(x);\n }\n";
+        assert_eq!(
+            apply_unified(text, diff).unwrap(),
+            "fn main() {\n    let x = 1;  \n    dbg!(x);\n}\n"
+        );
+    }
+
+    #[test]
+    fn unified_keeps_crlf_and_missing_eof_newline() {
+        let diff = "@@ -1,2 +1,2 @@\n a\n-b\n+B\n";
+        assert_eq!(apply_unified("a\r\nb\r\n", diff).unwrap(), "a\r\nB\r\n");
+        let diff = "@@ -1,2 +1,2 @@\n a\n-b\n+B\n\\ No newline at end of file\n";
+        assert_eq!(apply_unified("a\nb\n", diff).unwrap(), "a\nB");
+    }
+
+    #[test]
+    fn search_replace_rejects_ambiguous_block() {
+        let err = search_replace("x = 1;\nx = 1;\n", "x = 1;", "x = 2;").unwrap_err();
+        assert_eq!(err, "patch_failed: search_block_ambiguous (2 matches)");
+        // Неоднозначность и в построчном поиске без учёта пробелов.
+        let err = search_replace("  x = 1;\n\tx = 1;\n", "x = 1; \n", "x = 2;").unwrap_err();
+        assert_eq!(err, "patch_failed: search_block_ambiguous (2 matches)");
+        assert_eq!(search_replace("  a\n  b\n", "a\nb", "c").unwrap(), "c\n");
+        assert!(search_replace("a\n", "z", "c").unwrap_err().contains("search_block_not_found"));
+    }
+
+    #[test]
+    fn insert_after_anchor_at_eof_keeps_newline_state() {
+        assert_eq!(insert_after("a\nb", "b", "c").unwrap(), "a\nb\nc");
+        assert_eq!(insert_after("a\nb\n", "b", "c").unwrap(), "a\nb\nc\n");
+        assert_eq!(insert_after("a\nb\n", "b\n", "c\nd").unwrap(), "a\nb\nc\nd\n");
+        assert_eq!(insert_after("a\nb\nc\n", "a", "x").unwrap(), "a\nx\nb\nc\n");
+        assert!(insert_after("b\nb\n", "b", "x").unwrap_err().contains("anchor_ambiguous"));
+    }
+}
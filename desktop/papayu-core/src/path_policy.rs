@@ -0,0 +1,237 @@
+//! Политика путей для действий над проектом: нормализация компонентов,
+//! разрешение symlink'ов и проверка, что цель остаётся внутри канонического
+//! корня проекта, плюс список защищённых путей (`.git/`, `.ssh/`, lock-файлы).
+//! Список можно переопределить в `app_config_dir/path_policy.json`.
+
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::app_dirs::AppDirs;
+
+const POLICY_FILE: &str = "path_policy.json";
+
+/// Защищённые пути по умолчанию. `name/` — папка с таким именем на любом уровне,
+/// `a/b` — путь от корня проекта, `*.ext` — окончание имени файла, иначе — имя файла.
+const DEFAULT_DENY: &[&str] = &[
+    ".git/",
+    ".hg/",
+    ".svn/",
+    ".ssh/",
+    ".gnupg/",
+    "Cargo.lock",
+    "package-lock.json",
+    "yarn.lock",
+    "pnpm-lock.yaml",
+    "bun.lockb",
+    "poetry.lock",
+    "Pipfile.lock",
+    "composer.lock",
+    "Gemfile.lock",
+    "go.sum",
+];
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PathPolicy {
+    #[serde(default = "default_deny")]
+    pub deny: Vec<String>,
+}
+
+impl Default for PathPolicy {
+    fn default() -> Self {
+        PathPolicy { deny: default_deny() }
+    }
+}
+
+fn default_deny() -> Vec<String> {
+    DEFAULT_DENY.iter().map(|s| s.to_string()).collect()
+}
+
+#[derive(Debug, Clone)]
+pub struct PathError {
+    pub error: String,
+    pub code: &'static str,
+}
+
+impl PathError {
+    fn new(error: impl Into<String>, code: &'static str) -> Self {
+        PathError {
+            error: error.into(),
+            code,
+        }
+    }
+}
+
+/// Политика из `app_config_dir/path_policy.json`; если файла нет или он не читается — по умолчанию.
+pub fn load(dirs: &AppDirs) -> PathPolicy {
+    let path = match dirs.config_dir() {
+        Ok(d) => d.join(POLICY_FILE),
+        Err(_) => return PathPolicy::default(),
+    };
+    match fs::read_to_string(&path) {
+        Ok(s) => serde_json::from_str(&s).unwrap_or_else(|e| {
+            log::warn!("{}: {}, использую политику по умолчанию", path.display(), e);
+            PathPolicy::default()
+        }),
+        Err(_) => PathPolicy::default(),
+    }
+}
+
+/// Убирает `.` и сворачивает `..`; выход выше корня — ошибка.
+fn normalize(rel: &str) -> Result<PathBuf, PathError> {
+    let mut out = PathBuf::new();
+    for c in Path::new(rel).components() {
+        match c {
+            Component::Normal(part) => out.push(part),
+            Component::CurDir => {}
+            Component::ParentDir => {
+                if !out.pop() {
+                    return Err(PathError::new("path_traversal_denied", "PATH_TRAVERSAL"));
+                }
+            }
+            Component::RootDir | Component::Prefix(_) => {
+                return Err(PathError::new("absolute_path_denied", "PATH_ABSOLUTE"));
+            }
+        }
+    }
+    if out.as_os_str().is_empty() {
+        return Err(PathError::new("path_is_project_root", "PATH_EMPTY"));
+    }
+    Ok(out)
+}
+
+/// Сравнение без учёта регистра: на macOS и Windows `.GIT/config` — тот же `.git/config`.
+fn matches_rule(rel: &Path, rule: &str) -> bool {
+    let parts: Vec<String> = rel
+        .components()
+        .map(|c| c.as_os_str().to_string_lossy().to_lowercase())
+        .collect();
+    let rule = rule.to_lowercase();
+    let under = |prefix: &str| {
+        let prefix: Vec<&str> = prefix.split('/').filter(|p| !p.is_empty()).collect();
+        parts.len() >= prefix.len() && parts.iter().zip(&prefix).all(|(a, b)| a == b)
+    };
+    if let Some(dir) = rule.strip_suffix('/') {
+        if dir.contains('/') {
+            return under(dir);
+        }
+        return parts.iter().any(|p| p == dir);
+    }
+    if rule.contains('/') {
+        return under(&rule);
+    }
+    let name = parts.last().map(String::as_str).unwrap_or("");
+    match rule.strip_prefix('*') {
+        Some(suffix) => name.ends_with(suffix),
+        None => name == rule,
+    }
+}
+
+impl PathPolicy {
+    fn check_deny(&self, rel: &Path) -> Result<(), PathError> {
+        match self.deny.iter().find(|r| matches_rule(rel, r)) {
+            Some(rule) => Err(PathError::new(
+                format!("protected_path_denied: {} ({})", rel.display(), rule),
+                "PATH_PROTECTED",
+            )),
+            None => Ok(()),
+        }
+    }
+
+    /// Проверяет относительный путь действия и возвращает абсолютный путь внутри проекта.
+    /// Существующая часть пути разрешается через `canonicalize`, поэтому symlink,
+    /// ведущий за пределы проекта (или в защищённую папку), отклоняется.
+    pub fn resolve(&self, project_root: &Path, rel: &str) -> Result<PathBuf, PathError> {
+        if rel.trim().is_empty() {
+            return Err(PathError::new("path_is_project_root", "PATH_EMPTY"));
+        }
+        let norm = normalize(rel)?;
+        self.check_deny(&norm)?;
+
+        let canon_root = fs::canonicalize(project_root)
+            .map_err(|e| PathError::new(format!("project_root_unavailable: {}", e), "PATH_INVALID"))?;
+
+        // Ближайший существующий предок (включая висячие symlink'и) — его и разрешаем.
+        let mut existing = norm.clone();
+        let mut rest: Vec<std::ffi::OsString> = vec![];
+        while !existing.as_os_str().is_empty() && fs::symlink_metadata(canon_root.join(&existing)).is_err() {
+            if let Some(name) = existing.file_name() {
+                rest.push(name.to_os_string());
+            }
+            existing.pop();
+        }
+
+        let mut real = if existing.as_os_str().is_empty() {
+            canon_root.clone()
+        } else {
+            fs::canonicalize(canon_root.join(&existing))
+                .map_err(|_| PathError::new(format!("symlink_unresolved: {}", existing.display()), "PATH_OUTSIDE_ROOT"))?
+        };
+        for part in rest.iter().rev() {
+            real.push(part);
+        }
+
+        let inside = real
+            .strip_prefix(&canon_root)
+            .map_err(|_| PathError::new(format!("path_outside_project: {}", norm.display()), "PATH_OUTSIDE_ROOT"))?;
+        if inside.as_os_str().is_empty() {
+            return Err(PathError::new("path_is_project_root", "PATH_EMPTY"));
+        }
+        self.check_deny(inside)?;
+
+        Ok(project_root.join(norm))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn code(root: &Path, rel: &str) -> &'static str {
+        PathPolicy::default().resolve(root, rel).unwrap_err().code
+    }
+
+    #[test]
+    fn rejects_parent_escape_and_absolute_paths() {
+        let root = tempfile::tempdir().unwrap();
+        assert_eq!(code(root.path(), "../outside.txt"), "PATH_TRAVERSAL");
+        assert_eq!(code(root.path(), "src/../../outside.txt"), "PATH_TRAVERSAL");
+        assert_eq!(code(root.path(), "/etc/passwd"), "PATH_ABSOLUTE");
+        assert_eq!(code(root.path(), "src/.."), "PATH_EMPTY");
+        let ok = PathPolicy::default().resolve(root.path(), "src/../README.md").unwrap();
+        assert_eq!(ok, root.path().join("README.md"));
+    }
+
+    #[test]
+    fn deny_list_ignores_case() {
+        let root = tempfile::tempdir().unwrap();
+        for rel in [".git/config", ".GIT/config", "sub/.Git/HEAD", ".SSH/id_rsa", "cargo.LOCK", "web/Yarn.lock"] {
+            assert_eq!(code(root.path(), rel), "PATH_PROTECTED", "{}", rel);
+        }
+        let policy = PathPolicy {
+            deny: vec![".env".into(), "*.PEM".into(), "config/Secrets/".into()],
+        };
+        for rel in [".Env", "certs/key.pem", "CONFIG/secrets/db.toml"] {
+            assert!(policy.resolve(root.path(), rel).is_err(), "{}", rel);
+        }
+        assert!(policy.resolve(root.path(), "config/secrets.toml").is_ok());
+        assert!(policy.resolve(root.path(), "src/env.rs").is_ok());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn rejects_symlink_escape() {
+        let root = tempfile::tempdir().unwrap();
+        let outside = tempfile::tempdir().unwrap();
+        std::os::unix::fs::symlink(outside.path(), root.path().join("link")).unwrap();
+        std::os::unix::fs::symlink(outside.path().join("missing"), root.path().join("dangling")).unwrap();
+        std::fs::create_dir(root.path().join(".git")).unwrap();
+        std::os::unix::fs::symlink(root.path().join(".git"), root.path().join("meta")).unwrap();
+
+        assert_eq!(code(root.path(), "link/file.txt"), "PATH_OUTSIDE_ROOT");
+        assert_eq!(code(root.path(), "link/new/dir/file.txt"), "PATH_OUTSIDE_ROOT");
+        assert_eq!(code(root.path(), "dangling"), "PATH_OUTSIDE_ROOT");
+        assert_eq!(code(root.path(), "meta/config"), "PATH_PROTECTED");
+    }
+}
@@ -0,0 +1,220 @@
+//! Предпросмотр действий: diff по файлам без изменения проекта.
+
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+use serde::{Deserialize, Serialize};
+
+use crate::actions;
+use crate::app_dirs::AppDirs;
+use crate::diff::{self, Content};
+use crate::path_policy;
+use crate::progress::ProgressSink;
+use crate::session;
+use crate::types::{Action, ActionKind, DiffItem, PreviewResult};
+use crate::validate;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PreviewPayload {
+    pub path: String,
+    pub actions: Vec<Action>,
+}
+
+fn summarize(kind: &str, path: &str) -> String {
+    match kind {
+        "create" => format!("Создать файл {}", path),
+        "update" => format!("Обновить файл {}", path),
+        "delete" => format!("Удалить файл {}", path),
+        "mkdir" => format!("Создать папку {}", path),
+        "rmdir" => format!("Удалить папку {}", path),
+        "patch" => format!("Применить патч к {}", path),
+        "replace" => format!("Заменить блок в {}", path),
+        "insert" => format!("Вставить строки в {}", path),
+        "move" => format!("Переместить файл {}", path),
+        "movedir" => format!("Переместить папку {}", path),
+        "copy" => format!("Скопировать файл {}", path),
+        _ => format!("Изменение {}", path),
+    }
+}
+
+/// Удаляемые и переносимые папки целиком копируются в snapshot; как и `apply::dry_run`,
+/// предупреждает, если их суммарный размер превысит лимит.
+fn add_snapshot_bytes(total: &mut u64, bytes: u64, path: &str, warnings: &mut Vec<String>) {
+    *total += bytes;
+    if *total > session::MAX_SNAPSHOT_DIR_BYTES {
+        warnings.push(format!(
+            "Папка {}: удаляемые и переносимые папки не помещаются в snapshot ({} МБ, лимит {} МБ) — применение будет отклонено",
+            path,
+            *total / (1024 * 1024),
+            session::MAX_SNAPSHOT_DIR_BYTES / (1024 * 1024)
+        ));
+    }
+}
+
+/// Diff действий без изменения проекта.
+pub fn preview(sink: &dyn ProgressSink, dirs: &AppDirs, payload: PreviewPayload) -> PreviewResult {
+    let project_root = PathBuf::from(&payload.path);
+    if !project_root.exists() || !project_root.is_dir() {
+        return PreviewResult {
+            ok: false,
+            diffs: vec![],
+            summary: Default::default(),
+            warnings: vec![],
+            issues: vec![],
+            error: Some("path_invalid".into()),
+            error_code: Some("PATH_INVALID".into()),
+        };
+    }
+
+    sink.message("Готовлю предпросмотр изменений…");
+
+    let policy = path_policy::load(dirs);
+    let ordered = match validate::validate(&project_root, &policy, &payload.actions) {
+        Ok(a) => a,
+        Err(issues) => {
+            return PreviewResult {
+                ok: false,
+                diffs: vec![],
+                summary: Default::default(),
+                warnings: vec![],
+                issues,
+                error: Some("validation_failed".into()),
+                error_code: Some("VALIDATION_FAILED".into()),
+            };
+        }
+    };
+
+    let mut diffs: Vec<DiffItem> = vec![];
+    let mut warnings: Vec<String> = vec![];
+    let mut snapshot_bytes: u64 = 0;
+    // Содержимое файлов после предыдущих действий: правки одного файла идут цепочкой.
+    let mut pending: HashMap<PathBuf, Content> = HashMap::new();
+
+    for a in ordered {
+        let abs = match policy.resolve(&project_root, &a.path) {
+            Ok(p) => p,
+            Err(e) => {
+                return PreviewResult {
+                    ok: false,
+                    diffs: vec![],
+                    summary: Default::default(),
+                    warnings: vec![],
+                    issues: vec![],
+                    error: Some(e.error),
+                    error_code: Some(e.code.into()),
+                };
+            }
+        };
+        let dest = match a.to.as_deref().map(|to| policy.resolve(&project_root, to)).transpose() {
+            Ok(d) => d,
+            Err(e) => {
+                return PreviewResult {
+                    ok: false,
+                    diffs: vec![],
+                    summary: Default::default(),
+                    warnings: vec![],
+                    issues: vec![],
+                    error: Some(e.error),
+                    error_code: Some(e.code.into()),
+                };
+            }
+        };
+        let current = pending.get(&abs).cloned().unwrap_or_else(|| diff::load(&abs));
+        let new_content = || a.content.clone().map(Content::Text).unwrap_or(Content::Missing);
+
+        match a.kind {
+            ActionKind::CreateDir => {
+                diffs.push(diff::dir_item(&a.path, "mkdir", summarize("mkdir", &a.path)));
+            }
+            ActionKind::DeleteDir => {
+                let (files, bytes) = session::dir_size(&abs);
+                add_snapshot_bytes(&mut snapshot_bytes, bytes, &a.path, &mut warnings);
+                diffs.push(diff::dir_item(
+                    &a.path,
+                    "rmdir",
+                    format!("{} ({} файлов, {} КБ)", summarize("rmdir", &a.path), files, bytes / 1024),
+                ));
+            }
+            ActionKind::CreateFile => {
+                let after = new_content();
+                diffs.push(diff::file_item(&a.path, "create", &Content::Missing, &after, summarize("create", &a.path)));
+                pending.insert(abs, after);
+            }
+            ActionKind::UpdateFile => {
+                let after = new_content();
+                diffs.push(diff::file_item(&a.path, "update", &current, &after, summarize("update", &a.path)));
+                pending.insert(abs, after);
+            }
+            ActionKind::DeleteFile => {
+                diffs.push(diff::file_item(&a.path, "delete", &current, &Content::Missing, summarize("delete", &a.path)));
+                pending.insert(abs, Content::Missing);
+            }
+            ActionKind::MoveFile | ActionKind::CopyFile | ActionKind::MoveDir => {
+                let kind = match a.kind {
+                    ActionKind::MoveFile => "move",
+                    ActionKind::CopyFile => "copy",
+                    _ => "movedir",
+                };
+                let to = a.to.clone().unwrap_or_default();
+                let summary = format!("{} → {}", summarize(kind, &a.path), to);
+                let mut item = if matches!(a.kind, ActionKind::MoveDir) {
+                    let (files, bytes) = session::dir_size(&abs);
+                    add_snapshot_bytes(&mut snapshot_bytes, bytes, &a.path, &mut warnings);
+                    diff::dir_item(&a.path, kind, format!("{} ({} файлов, {} КБ)", summary, files, bytes / 1024))
+                } else {
+                    // Содержимое не меняется — ханков нет, сравниваем с самим собой.
+                    diff::file_item(&a.path, kind, &current, &current, summary)
+                };
+                item.to = a.to.clone();
+                diffs.push(item);
+                if let Some(dest) = dest {
+                    if !matches!(a.kind, ActionKind::MoveDir) {
+                        pending.insert(dest, current.clone());
+                    }
+                }
+                if !matches!(a.kind, ActionKind::CopyFile) {
+                    pending.insert(abs, Content::Missing);
+                }
+            }
+            ActionKind::ApplyPatch | ActionKind::SearchReplace | ActionKind::InsertAfter => {
+                let kind = match a.kind {
+                    ActionKind::ApplyPatch => "patch",
+                    ActionKind::SearchReplace => "replace",
+                    _ => "insert",
+                };
+                let result = match &current {
+                    Content::Text(c) => actions::patched_content(c, &a),
+                    Content::Missing => Err("patch_target_missing".into()),
+                    Content::Binary(_) => Err("patch_target_binary".into()),
+                    Content::TooLarge(_) => Err("patch_target_too_large_for_preview".into()),
+                };
+                match result {
+                    Ok(text) => {
+                        let after = Content::Text(text);
+                        diffs.push(diff::file_item(&a.path, kind, &current, &after, summarize(kind, &a.path)));
+                        pending.insert(abs, after);
+                    }
+                    Err(e) => {
+                        warnings.push(format!("{}: правка не применится — {}", a.path, e));
+                        let mut item = diff::file_item(&a.path, kind, &current, &current, summarize(kind, &a.path));
+                        item.summary = format!("{} — не применится", summarize(kind, &a.path));
+                        diffs.push(item);
+                    }
+                }
+            }
+        }
+    }
+
+    let summary = diff::summarize(&diffs);
+    sink.message(&summary.text);
+
+    PreviewResult {
+        ok: true,
+        diffs,
+        summary,
+        warnings,
+        issues: vec![],
+        error: None,
+        error_code: None,
+    }
+}
@@ -0,0 +1,18 @@
+//! Куда уходит ход работы: в окно приложения событиями или, в CLI, в терминал.
+
+use crate::types::AnalyzeProgress;
+
+pub trait ProgressSink: Send + Sync {
+    /// Ход анализа (`analysis_progress`).
+    fn analysis(&self, progress: &AnalyzeProgress);
+    /// Этап apply, предпросмотра или отката (`analyze_progress`).
+    fn message(&self, text: &str);
+}
+
+/// Ход работы никуда не отправляется: пробный apply, контекст LLM.
+pub struct NoProgress;
+
+impl ProgressSink for NoProgress {
+    fn analysis(&self, _: &AnalyzeProgress) {}
+    fn message(&self, _: &str) {}
+}
@@ -0,0 +1,158 @@
+//! Настройки анализа проекта: профиль (`quick` | `standard` | `exhaustive`) задаёт
+//! лимиты, наборы правил и историю git, `.papayu.toml` в корне проекта может их
+//! переопределить и добавить исключения. Файл читается через `ProjectFs`, поэтому
+//! при пробном apply действуют настройки из дерева после действий.
+
+use std::path::Path;
+
+use serde::Deserialize;
+
+use crate::project_fs::ProjectFs;
+use crate::rules::RuleSet;
+use crate::types::{AnalysisConfig, AnalysisLimits, AnalysisProfile};
+
+pub const CONFIG_FILE: &str = ".papayu.toml";
+
+/// Наборы правил глубокого анализа.
+pub const RULE_SETS: &[&str] = &["secrets", "security", "quality"];
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct ConfigFile {
+    profile: Option<AnalysisProfile>,
+    rules: Option<Vec<String>>,
+    exclude: Vec<String>,
+    git_history: Option<bool>,
+    limits: LimitsFile,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct LimitsFile {
+    max_files: Option<u64>,
+    max_duration_secs: Option<u64>,
+    deep_max_files: Option<usize>,
+    deep_max_file_bytes: Option<u64>,
+    deep_max_depth: Option<u32>,
+    context_max_bytes: Option<usize>,
+    context_max_file_bytes: Option<u64>,
+    context_max_files: Option<usize>,
+    context_max_depth: Option<u32>,
+}
+
+fn profile_limits(profile: AnalysisProfile) -> AnalysisLimits {
+    match profile {
+        AnalysisProfile::Quick => AnalysisLimits {
+            max_files: 10_000,
+            max_duration_secs: 15,
+            deep_max_files: 100,
+            deep_max_file_bytes: 128 * 1024,
+            deep_max_depth: 6,
+            context_max_bytes: 50_000,
+            context_max_file_bytes: 20_000,
+            context_max_files: 100,
+            context_max_depth: 6,
+        },
+        AnalysisProfile::Standard => AnalysisLimits {
+            max_files: 50_000,
+            max_duration_secs: 60,
+            deep_max_files: 500,
+            deep_max_file_bytes: 512 * 1024,
+            deep_max_depth: 10,
+            context_max_bytes: 100_000,
+            context_max_file_bytes: 30_000,
+            context_max_files: 300,
+            context_max_depth: 8,
+        },
+        AnalysisProfile::Exhaustive => AnalysisLimits {
+            max_files: 500_000,
+            max_duration_secs: 600,
+            deep_max_files: 20_000,
+            deep_max_file_bytes: 2 * 1024 * 1024,
+            deep_max_depth: 64,
+            context_max_bytes: 400_000,
+            context_max_file_bytes: 100_000,
+            context_max_files: 2_000,
+            context_max_depth: 32,
+        },
+    }
+}
+
+fn profile_rules(profile: AnalysisProfile) -> Vec<String> {
+    let sets: &[&str] = match profile {
+        AnalysisProfile::Quick => &["secrets", "security"],
+        AnalysisProfile::Standard | AnalysisProfile::Exhaustive => RULE_SETS,
+    };
+    sets.iter().map(|s| s.to_string()).collect()
+}
+
+/// Настройки проекта: `profile` (из `AnalyzeOptions`) сильнее профиля из файла.
+/// Ошибки в файле не прерывают анализ — они попадают в `warnings`.
+pub fn load(fs: &dyn ProjectFs, root: &Path, profile: Option<AnalysisProfile>) -> AnalysisConfig {
+    let path = root.join(CONFIG_FILE);
+    let mut warnings = vec![];
+    let text = fs.read_to_string(&path);
+    let file = match text.as_deref().map(toml::from_str::<ConfigFile>) {
+        Some(Ok(f)) => f,
+        Some(Err(e)) => {
+            log::warn!("{}: {}", path.display(), e);
+            warnings.push(format!("{}: {}", CONFIG_FILE, e.message()));
+            ConfigFile::default()
+        }
+        None => ConfigFile::default(),
+    };
+
+    let profile = profile.or(file.profile).unwrap_or_default();
+    let mut limits = profile_limits(profile);
+    let l = &file.limits;
+    positive("max_files", l.max_files, &mut limits.max_files, &mut warnings);
+    positive("max_duration_secs", l.max_duration_secs, &mut limits.max_duration_secs, &mut warnings);
+    positive("deep_max_files", l.deep_max_files, &mut limits.deep_max_files, &mut warnings);
+    positive("deep_max_file_bytes", l.deep_max_file_bytes, &mut limits.deep_max_file_bytes, &mut warnings);
+    positive("context_max_bytes", l.context_max_bytes, &mut limits.context_max_bytes, &mut warnings);
+    positive("context_max_file_bytes", l.context_max_file_bytes, &mut limits.context_max_file_bytes, &mut warnings);
+    positive("context_max_files", l.context_max_files, &mut limits.context_max_files, &mut warnings);
+    // Глубина 0 допустима: только файлы в корне.
+    limits.deep_max_depth = l.deep_max_depth.unwrap_or(limits.deep_max_depth);
+    limits.context_max_depth = l.context_max_depth.unwrap_or(limits.context_max_depth);
+
+    let rules = match file.rules {
+        Some(rules) => {
+            for r in rules.iter().filter(|r| !RULE_SETS.contains(&r.as_str())) {
+                warnings.push(format!("{}: неизвестный набор правил `{}`", CONFIG_FILE, r));
+            }
+            rules.into_iter().filter(|r| RULE_SETS.contains(&r.as_str())).collect()
+        }
+        None => profile_rules(profile),
+    };
+
+    AnalysisConfig {
+        profile,
+        source: text.is_some().then(|| path.to_string_lossy().to_string()),
+        limits,
+        rules,
+        exclude: file.exclude,
+        git_history: file.git_history.unwrap_or(profile == AnalysisProfile::Exhaustive),
+        warnings,
+        // Правила пользователя загружает `analyze::load_config`.
+        custom_rules: vec![],
+        rule_warnings: vec![],
+        rule_set: RuleSet::default(),
+    }
+}
+
+/// Лимит из файла, если он больше нуля.
+fn positive<T: Copy + Default + PartialEq>(name: &str, value: Option<T>, target: &mut T, warnings: &mut Vec<String>) {
+    match value {
+        Some(v) if v == T::default() => warnings.push(format!("{}: limits.{} = 0 не применён", CONFIG_FILE, name)),
+        Some(v) => *target = v,
+        None => {}
+    }
+}
+
+impl AnalysisConfig {
+    /// Набор правил включён.
+    pub fn rule(&self, set: &str) -> bool {
+        self.rules.iter().any(|r| r == set)
+    }
+}
@@ -0,0 +1,58 @@
+//! Чтение дерева проекта для анализа: с диска или из виртуального дерева
+//! пробного apply (`overlay.rs`).
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+/// Запись каталога. `is_dir` и `len` — уже с переходом по symlink'у.
+pub struct Entry {
+    pub path: PathBuf,
+    pub is_symlink: bool,
+    pub is_dir: bool,
+    pub len: u64,
+    /// Время изменения; `None` — неизвестно (например, файл только в памяти).
+    pub modified: Option<SystemTime>,
+}
+
+/// `Sync` — дерево обходится параллельно.
+pub trait ProjectFs: Sync {
+    /// Содержимое папки; нечитаемая или отсутствующая папка — пустой список.
+    fn read_dir(&self, dir: &Path) -> Vec<Entry>;
+    fn read(&self, path: &Path) -> Option<Vec<u8>>;
+
+    fn read_to_string(&self, path: &Path) -> Option<String> {
+        self.read(path).and_then(|b| String::from_utf8(b).ok())
+    }
+}
+
+/// Проект как он есть на диске.
+pub struct DiskFs;
+
+impl ProjectFs for DiskFs {
+    fn read_dir(&self, dir: &Path) -> Vec<Entry> {
+        let entries = match fs::read_dir(dir) {
+            Ok(e) => e,
+            Err(_) => return vec![],
+        };
+        entries
+            .flatten()
+            .filter_map(|e| {
+                let is_symlink = e.file_type().ok()?.is_symlink();
+                let path = e.path();
+                let meta = fs::metadata(&path).ok();
+                Some(Entry {
+                    path,
+                    is_symlink,
+                    is_dir: meta.as_ref().is_some_and(|m| m.is_dir()),
+                    len: meta.as_ref().map_or(0, |m| m.len()),
+                    modified: meta.and_then(|m| m.modified().ok()),
+                })
+            })
+            .collect()
+    }
+
+    fn read(&self, path: &Path) -> Option<Vec<u8>> {
+        fs::read(path).ok()
+    }
+}
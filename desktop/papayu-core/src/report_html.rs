@@ -0,0 +1,127 @@
+//! Отчёт `AnalyzeReport` одной HTML-страницей без внешних стилей и скриптов —
+//! для артефактов CI, которые открывают прямо в браузере.
+
+use std::fmt::Write;
+
+use crate::types::{AnalyzeReport, Finding};
+
+const STYLE: &str = "body{font:14px/1.5 system-ui,sans-serif;margin:2em auto;max-width:72em;padding:0 1em;color:#222}\
+table{border-collapse:collapse;width:100%}th,td{border-bottom:1px solid #ddd;padding:.3em .5em;text-align:left;vertical-align:top}\
+code{font-size:90%;background:#f4f4f4;padding:0 .2em}.high{color:#b00020}.warn{color:#a15c00}.info{color:#555}";
+
+fn escape(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\'' => out.push_str("&#39;"),
+            _ => out.push(c),
+        }
+    }
+    out
+}
+
+/// `src/a.js:3:5`; у находок по проекту в целом места нет.
+fn place(f: &Finding) -> String {
+    let Some(path) = &f.path else {
+        return String::new();
+    };
+    match (f.start_line, f.start_column) {
+        (Some(l), Some(c)) => format!("{}:{}:{}", path, l, c),
+        (Some(l), None) => format!("{}:{}", path, l),
+        _ => path.clone(),
+    }
+}
+
+fn severity_class(severity: &str) -> &'static str {
+    match severity {
+        "high" => "high",
+        "warn" => "warn",
+        _ => "info",
+    }
+}
+
+pub fn to_html(report: &AnalyzeReport) -> String {
+    let mut h = String::new();
+    let title = format!("PAPA YU — {}", report.path);
+    let _ = write!(
+        h,
+        "<!DOCTYPE html>\n<html lang=\"ru\"><head><meta charset=\"utf-8\"><title>{}</title><style>{}</style></head><body>\n",
+        escape(&title),
+        STYLE
+    );
+    let _ = writeln!(h, "<h1>{}</h1>", escape(&title));
+    let _ = writeln!(h, "<p>{}</p>", escape(&report.narrative));
+
+    let ctx = &report.project_context;
+    let stats = &report.stats;
+    let _ = writeln!(
+        h,
+        "<p>Стек: {} · Зрелость: {} · Риск: {} · Файлов: {} · Папок: {}</p>",
+        escape(&ctx.stack.join(", ")),
+        escape(&ctx.maturity),
+        escape(&ctx.risk_level),
+        stats.file_count,
+        stats.dir_count
+    );
+    if let Some(stop) = report.stopped {
+        let _ = writeln!(h, "<p class=\"warn\">Анализ остановлен раньше времени ({:?}): отчёт неполный.</p>", stop);
+    }
+
+    if !report.signals.is_empty() {
+        h.push_str("<h2>Сигналы</h2>\n<ul>\n");
+        for s in &report.signals {
+            let _ = writeln!(
+                h,
+                "<li class=\"{}\">[{}] {}</li>",
+                severity_class(&s.level),
+                escape(&s.category),
+                escape(&s.message)
+            );
+        }
+        h.push_str("</ul>\n");
+    }
+
+    let _ = writeln!(h, "<h2>Находки ({})</h2>", report.findings.len());
+    if !report.findings.is_empty() {
+        h.push_str("<table>\n<tr><th>Уровень</th><th>Правило</th><th>Находка</th><th>Место</th></tr>\n");
+        for f in &report.findings {
+            let snippet = f
+                .snippet
+                .as_deref()
+                .map(|s| format!("<br><code>{}</code>", escape(s)))
+                .unwrap_or_default();
+            let _ = writeln!(
+                h,
+                "<tr><td class=\"{}\">{}</td><td>{}</td><td>{}{}</td><td>{}</td></tr>",
+                severity_class(&f.severity),
+                escape(&f.severity),
+                escape(f.rule_id.as_deref().unwrap_or("")),
+                escape(&f.title),
+                snippet,
+                escape(&place(f))
+            );
+        }
+        h.push_str("</table>\n");
+    }
+
+    if !report.recommendations.is_empty() {
+        h.push_str("<h2>Рекомендации</h2>\n<ol>\n");
+        for r in &report.recommendations {
+            let _ = writeln!(
+                h,
+                "<li><b>{}</b> (приоритет {}) — {}</li>",
+                escape(&r.title),
+                escape(&r.priority),
+                escape(&r.details)
+            );
+        }
+        h.push_str("</ol>\n");
+    }
+
+    h.push_str("</body></html>\n");
+    h
+}
@@ -0,0 +1,673 @@
+//! Правила пользователя: наборы в `app_config_dir/rules/` и `.papayu/rules/` проекта —
+//! файлы `*.toml`, `*.yaml` или `*.yml` со списком `rule`. Правило проекта заменяет
+//! правило из настроек приложения с тем же id. Шаблоны и расширения разбираются один
+//! раз при загрузке; ошибочное правило пропускается, ошибка попадает в
+//! `AnalysisConfig::rule_warnings`.
+//!
+//! Виды правил (`kind`):
+//! - `regex` — `pattern` в файлах глубокого анализа, находка на каждое совпадение;
+//! - `file` — файлы или папки `paths` (синтаксис gitignore): нет ни одного
+//!   (`when = "missing"`, по умолчанию) или есть (`when = "present"`);
+//! - `manifest` — ключ `key` (через точку) в JSON-, TOML- или YAML-файле `file`:
+//!   `when = "missing" | "present"`, `equals = <значение>` или `matches = "<шаблон>"`.
+//!
+//! `fix` — шаблон `Action`, `{path}` в нём — файл находки; для каждого файла с
+//! находками правила в `report.actions` попадает своё действие.
+
+use std::collections::HashSet;
+use std::fmt;
+use std::path::Path;
+use std::sync::Arc;
+
+use ignore::gitignore::{Gitignore, GitignoreBuilder};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+
+use crate::analyze::STRUCTURE_RULES;
+use crate::app_dirs::AppDirs;
+use crate::deep_analysis::{self, SourceFile, CODE_EXTENSIONS, FILE_RULES, QUALITY_PATTERNS, SECRET_PATTERNS, VULN_PATTERNS};
+use crate::file_index::FileIndex;
+use crate::project_fs::{DiskFs, ProjectFs};
+use crate::types::{Action, ActionKind, Finding};
+
+/// Папка наборов в `app_config_dir`.
+pub const RULES_DIR: &str = "rules";
+/// Папка наборов проекта, от корня.
+pub const PROJECT_RULES_DIR: &str = ".papayu/rules";
+const PACK_EXTENSIONS: &[&str] = &["toml", "yaml", "yml"];
+const SEVERITIES: &[&str] = &["info", "warn", "high"];
+const CATEGORIES: &[&str] = &["security", "quality", "structure"];
+
+/// Языки в `languages` → расширения; остальное считается расширением.
+const LANGUAGES: &[(&str, &[&str])] = &[
+    ("javascript", &["js", "jsx", "mjs", "cjs"]),
+    ("typescript", &["ts", "tsx"]),
+    ("python", &["py"]),
+    ("rust", &["rs"]),
+    ("go", &["go"]),
+    ("ruby", &["rb"]),
+    ("php", &["php"]),
+    ("java", &["java"]),
+    ("kotlin", &["kt"]),
+    ("shell", &["sh", "bash", "zsh"]),
+    ("yaml", &["yml", "yaml"]),
+];
+
+/// Правило анализа. `check_file` вызывается для файлов глубокого анализа (находки
+/// хранятся в индексе вместе со встроенными), `check_project` — раз на анализ.
+pub trait Rule: Send + Sync {
+    fn meta(&self) -> &RuleMeta;
+
+    fn check_file(&self, _file: &SourceFile) -> Vec<Finding> {
+        vec![]
+    }
+
+    fn check_project(&self, _fs: &dyn ProjectFs, _index: &FileIndex) -> Vec<Finding> {
+        vec![]
+    }
+}
+
+/// Описание правила; в отчёте — `AnalysisConfig::custom_rules`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RuleMeta {
+    pub id: String,
+    pub kind: String,     // regex|file|manifest
+    pub severity: String, // info|warn|high
+    pub category: String, // security|quality|structure
+    /// Расширения файлов без точки; пусто — все файлы глубокого анализа.
+    #[serde(default)]
+    pub languages: Vec<String>,
+    pub message: String,
+    #[serde(default)]
+    pub fix_hint: Option<String>,
+    #[serde(default)]
+    pub fix: Option<ActionTemplate>,
+    /// Файл набора, из которого взято правило.
+    pub source: String,
+}
+
+impl RuleMeta {
+    fn finding(&self, details: String, path: Option<String>) -> Finding {
+        Finding {
+            severity: self.severity.clone(),
+            title: self.message.clone(),
+            details,
+            rule_id: Some(self.id.clone()),
+            category: Some(self.category.clone()),
+            path,
+            fix_hint: self.fix_hint.clone(),
+            ..Default::default()
+        }
+    }
+}
+
+/// Исправление находки — `Action`, в строках которого `{path}` заменяется файлом находки.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ActionTemplate {
+    pub kind: ActionKind,
+    /// По умолчанию — `message` правила.
+    #[serde(default)]
+    pub title: Option<String>,
+    /// По умолчанию — `{path}`.
+    #[serde(default)]
+    pub path: Option<String>,
+    #[serde(default)]
+    pub content: Option<String>,
+    #[serde(default)]
+    pub search: Option<String>,
+    #[serde(default)]
+    pub anchor: Option<String>,
+    #[serde(default)]
+    pub to: Option<String>,
+}
+
+impl ActionTemplate {
+    /// `None` — шаблону нужен `{path}`, а у находки нет файла.
+    fn action(&self, meta: &RuleMeta, finding: &Finding) -> Option<Action> {
+        let fill = |s: &str| match s.contains("{path}") {
+            true => finding.path.as_deref().map(|p| s.replace("{path}", p)),
+            false => Some(s.to_string()),
+        };
+        let fill_opt = |s: &Option<String>| match s {
+            Some(s) => fill(s).map(Some),
+            None => Some(None),
+        };
+        let path = fill(self.path.as_deref().unwrap_or("{path}"))?;
+        Some(Action {
+            id: format!("{}@{}", meta.id, path),
+            title: fill(self.title.as_deref().unwrap_or(&meta.message))?,
+            description: meta.fix_hint.clone().unwrap_or_else(|| meta.message.clone()),
+            kind: self.kind.clone(),
+            path,
+            content: fill_opt(&self.content)?,
+            search: fill_opt(&self.search)?,
+            anchor: fill_opt(&self.anchor)?,
+            to: fill_opt(&self.to)?,
+        })
+    }
+}
+
+struct RegexRule {
+    meta: RuleMeta,
+    re: Regex,
+}
+
+impl Rule for RegexRule {
+    fn meta(&self) -> &RuleMeta {
+        &self.meta
+    }
+
+    fn check_file(&self, file: &SourceFile) -> Vec<Finding> {
+        // В наборах правил лежат сами шаблоны.
+        let own = file.rel.starts_with(".papayu/");
+        if own || !(self.meta.languages.is_empty() || self.meta.languages.iter().any(|l| l == file.ext)) {
+            return vec![];
+        }
+        let matches: Vec<_> = self.re.find_iter(file.content).collect();
+        file.occurrences(&self.meta.finding(String::new(), None), &matches)
+    }
+}
+
+struct FileRule {
+    meta: RuleMeta,
+    paths: Vec<String>,
+    matcher: Gitignore,
+    present: bool,
+}
+
+impl Rule for FileRule {
+    fn meta(&self) -> &RuleMeta {
+        &self.meta
+    }
+
+    fn check_project(&self, _fs: &dyn ProjectFs, index: &FileIndex) -> Vec<Finding> {
+        let dirs = index.dirs.iter().filter_map(|d| {
+            let rel = d.path.strip_prefix(&index.root).ok()?.to_string_lossy().replace('\\', "/");
+            self.matcher.matched(&d.path, true).is_ignore().then_some(rel)
+        });
+        let files = index.files.iter().filter(|f| self.matcher.matched(&f.path, false).is_ignore()).map(|f| f.rel.clone());
+        let found: Vec<String> = dirs.chain(files).collect();
+
+        match (self.present, found.is_empty()) {
+            (true, _) => found
+                .into_iter()
+                .take(deep_analysis::MAX_OCCURRENCES)
+                .map(|rel| self.meta.finding(rel.clone(), Some(rel)))
+                .collect(),
+            (false, true) => {
+                // Путь без шаблона — файл находки: его может создать `fix`.
+                let plain = match self.paths.as_slice() {
+                    [p] if !p.contains(['*', '?', '[', '!']) => Some(p.trim_matches('/').to_string()),
+                    _ => None,
+                };
+                vec![self.meta.finding(format!("Нет {}", self.paths.join(", ")), plain)]
+            }
+            (false, false) => vec![],
+        }
+    }
+}
+
+enum Check {
+    Missing,
+    Present,
+    Equals(Value),
+    Matches(Regex),
+}
+
+struct ManifestRule {
+    meta: RuleMeta,
+    file: String,
+    key: Vec<String>,
+    check: Check,
+}
+
+impl Rule for ManifestRule {
+    fn meta(&self) -> &RuleMeta {
+        &self.meta
+    }
+
+    fn check_project(&self, fs: &dyn ProjectFs, index: &FileIndex) -> Vec<Finding> {
+        let Some(text) = fs.read_to_string(&index.root.join(&self.file)) else {
+            return vec![];
+        };
+        let Some(doc) = parse_manifest(&self.file, &text) else {
+            return vec![];
+        };
+        let value = lookup(&doc, &self.key);
+        let hit = match (&self.check, value) {
+            (Check::Missing, v) => v.is_none(),
+            (Check::Present, v) => v.is_some(),
+            (Check::Equals(e), Some(v)) => v == e,
+            (Check::Matches(re), Some(v)) => re.is_match(&scalar(v)),
+            _ => false,
+        };
+        if !hit {
+            return vec![];
+        }
+
+        let key = self.key.join(".");
+        let details = match value {
+            Some(v) => format!("{}: {} = {}", self.file, key, scalar(v)),
+            None => format!("{}: нет {}", self.file, key),
+        };
+        let mut finding = self.meta.finding(details, Some(self.file.clone()));
+        // Место — ключ или ближайший к нему существующий родитель.
+        let known = (0..=self.key.len()).rev().find(|&n| lookup(&doc, &self.key[..n]).is_some()).unwrap_or(0);
+        if let Some(line) = known.checked_sub(1).and_then(|i| key_line(&text, &self.key[i])) {
+            finding.start_line = Some(line);
+            finding.snippet = text.lines().nth(line as usize - 1).map(deep_analysis::snippet);
+        }
+        vec![finding]
+    }
+}
+
+fn parse_manifest(file: &str, text: &str) -> Option<Value> {
+    let parsed = match Path::new(file).extension().and_then(|e| e.to_str()) {
+        Some("json") => serde_json::from_str(text).map_err(|e| e.to_string()),
+        Some("yaml" | "yml") => serde_norway::from_str(text).map_err(|e| e.to_string()),
+        _ => toml::from_str(text).map_err(|e| e.to_string()),
+    };
+    parsed.map_err(|e| log::warn!("{}: {}", file, e)).ok()
+}
+
+fn lookup<'a>(doc: &'a Value, key: &[String]) -> Option<&'a Value> {
+    key.iter().try_fold(doc, |v, k| match v {
+        Value::Object(m) => m.get(k),
+        Value::Array(a) => k.parse::<usize>().ok().and_then(|i| a.get(i)),
+        _ => None,
+    })
+}
+
+/// Строка без кавычек, остальное — как JSON.
+fn scalar(v: &Value) -> String {
+    match v {
+        Value::String(s) => s.clone(),
+        _ => v.to_string(),
+    }
+}
+
+/// Строка (с 1), где объявлен ключ `key`: `"key":`, `key =` или заголовок таблицы `[...key]`.
+fn key_line(text: &str, key: &str) -> Option<u32> {
+    let k = regex::escape(key);
+    let re = Regex::new(&format!(r#"(?m)(?:^|[\s{{,])["']?{k}["']?\s*[:=]|^\s*\[+(?:[^\]]*\.)?["']?{k}["']?\]"#)).ok()?;
+    let at = re.find(text)?.start();
+    Some(text[..at].matches('\n').count() as u32 + 1 + u32::from(text[at..].starts_with('\n')))
+}
+
+/// Набор правил в файле.
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RulePack {
+    #[serde(default, alias = "rules")]
+    rule: Vec<RuleDef>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RuleDef {
+    id: String,
+    kind: String,
+    severity: Option<String>,
+    category: Option<String>,
+    #[serde(default)]
+    languages: Vec<String>,
+    message: String,
+    fix_hint: Option<String>,
+    fix: Option<ActionTemplate>,
+    /// `regex`.
+    pattern: Option<String>,
+    /// `file`.
+    #[serde(default)]
+    paths: Vec<String>,
+    /// `manifest`.
+    file: Option<String>,
+    key: Option<String>,
+    equals: Option<Value>,
+    matches: Option<String>,
+    /// `file` и `manifest`.
+    when: Option<String>,
+}
+
+fn build(def: RuleDef, source: &str, root: &Path) -> Result<Arc<dyn Rule>, String> {
+    let id = def.id.trim().to_string();
+    if id.is_empty() || id.contains(char::is_whitespace) {
+        return Err(format!("неверный id `{}`", def.id));
+    }
+    let err = |e: String| format!("правило {}: {}", id, e);
+    let severity = def.severity.unwrap_or_else(|| "warn".into());
+    if !SEVERITIES.contains(&severity.as_str()) {
+        return Err(err(format!("severity `{}` — ожидается info, warn или high", severity)));
+    }
+    let category = def.category.unwrap_or_else(|| "quality".into());
+    if !CATEGORIES.contains(&category.as_str()) {
+        return Err(err(format!("category `{}` — ожидается security, quality или structure", category)));
+    }
+    let mut languages: Vec<String> = vec![];
+    for l in &def.languages {
+        let l = l.trim().trim_start_matches('.').to_lowercase();
+        match LANGUAGES.iter().find(|(name, _)| *name == l) {
+            Some((_, exts)) => languages.extend(exts.iter().map(|e| e.to_string())),
+            None if CODE_EXTENSIONS.contains(&l.as_str()) => languages.push(l),
+            None => return Err(err(format!("файлы `{}` не разбираются глубоким анализом", l))),
+        }
+    }
+    let meta = RuleMeta {
+        id: id.clone(),
+        kind: def.kind.clone(),
+        severity,
+        category,
+        languages,
+        message: def.message,
+        fix_hint: def.fix_hint,
+        fix: def.fix,
+        source: source.to_string(),
+    };
+
+    let rule: Arc<dyn Rule> = match def.kind.as_str() {
+        "regex" => {
+            let pattern = def.pattern.ok_or_else(|| err("нет pattern".into()))?;
+            let re = Regex::new(&pattern).map_err(|e| err(e.to_string()))?;
+            Arc::new(RegexRule { meta, re })
+        }
+        "file" => {
+            if def.paths.is_empty() {
+                return Err(err("нет paths".into()));
+            }
+            let present = match def.when.as_deref().unwrap_or("missing") {
+                "missing" => false,
+                "present" => true,
+                w => return Err(err(format!("when `{}` — ожидается missing или present", w))),
+            };
+            let mut builder = GitignoreBuilder::new(root);
+            for p in &def.paths {
+                builder.add_line(None, p).map_err(|e| err(e.to_string()))?;
+            }
+            let matcher = builder.build().map_err(|e| err(e.to_string()))?;
+            Arc::new(FileRule { meta, paths: def.paths, matcher, present })
+        }
+        "manifest" => {
+            let file = def.file.ok_or_else(|| err("нет file".into()))?;
+            let key = def.key.ok_or_else(|| err("нет key".into()))?;
+            let check = match (def.when.as_deref(), def.equals, def.matches) {
+                (None | Some("missing"), None, None) => Check::Missing,
+                (Some("present"), None, None) => Check::Present,
+                (None, Some(v), None) => Check::Equals(v),
+                (None, None, Some(m)) => Check::Matches(Regex::new(&m).map_err(|e| err(e.to_string()))?),
+                (Some(w), None, None) => return Err(err(format!("when `{}` — ожидается missing или present", w))),
+                _ => return Err(err("нужно одно из when, equals, matches".into())),
+            };
+            let key = key.split('.').map(str::to_string).collect();
+            Arc::new(ManifestRule { meta, file, key, check })
+        }
+        k => return Err(err(format!("неизвестный kind `{}` — ожидается regex, file или manifest", k))),
+    };
+    Ok(rule)
+}
+
+/// Правила пользователя, готовые к проверке.
+#[derive(Clone, Default)]
+pub struct RuleSet {
+    rules: Vec<Arc<dyn Rule>>,
+    /// sha256 прочитанных наборов; пусто — наборов нет.
+    fingerprint: String,
+}
+
+impl fmt::Debug for RuleSet {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.rules.iter().map(|r| &r.meta().id)).finish()
+    }
+}
+
+impl RuleSet {
+    pub fn rules(&self) -> &[Arc<dyn Rule>] {
+        &self.rules
+    }
+
+    /// С другими наборами находки из индекса прошлого анализа не годятся.
+    pub fn fingerprint(&self) -> &str {
+        &self.fingerprint
+    }
+
+    pub fn meta(&self) -> Vec<RuleMeta> {
+        self.rules.iter().map(|r| r.meta().clone()).collect()
+    }
+
+    pub fn check_project(&self, fs: &dyn ProjectFs, index: &FileIndex) -> Vec<Finding> {
+        self.rules.iter().flat_map(|r| r.check_project(fs, index)).collect()
+    }
+
+    /// Исправления находок правил с `fix`: одно на правило и файл.
+    pub fn fixes(&self, findings: &[Finding]) -> Vec<Action> {
+        let mut seen = HashSet::new();
+        let mut actions = vec![];
+        for f in findings {
+            let Some(rule) = self.rules.iter().find(|r| f.rule_id.as_deref() == Some(r.meta().id.as_str())) else {
+                continue;
+            };
+            let meta = rule.meta();
+            if let Some(action) = meta.fix.as_ref().and_then(|t| t.action(meta, f)) {
+                if seen.insert(action.id.clone()) {
+                    actions.push(action);
+                }
+            }
+        }
+        actions
+    }
+}
+
+/// Наборы правил для проекта `root` и ошибки в них. Наборы проекта читаются через `fs`,
+/// поэтому при пробном apply действуют правила из дерева после действий.
+pub fn load(fs: &dyn ProjectFs, root: &Path, dirs: &AppDirs) -> (RuleSet, Vec<String>) {
+    let mut packs: Vec<(String, String, bool)> = vec![];
+    if let Ok(dir) = dirs.config_dir() {
+        for (path, text) in read_packs(&DiskFs, &dir.join(RULES_DIR)) {
+            packs.push((path.to_string_lossy().to_string(), text, false));
+        }
+    }
+    for (path, text) in read_packs(fs, &root.join(PROJECT_RULES_DIR)) {
+        let name = path.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+        packs.push((format!("{}/{}", PROJECT_RULES_DIR, name), text, true));
+    }
+
+    let builtin: HashSet<&str> = SECRET_PATTERNS
+        .iter()
+        .map(|r| r.0)
+        .chain(VULN_PATTERNS.iter().map(|r| r.0))
+        .chain(QUALITY_PATTERNS.iter().map(|r| r.0))
+        .chain(FILE_RULES.iter().map(|r| r.0))
+        .chain(STRUCTURE_RULES.iter().map(|r| r.0))
+        .collect();
+    let mut warnings = vec![];
+    let mut hasher = Sha256::new();
+    // Правило и откуда оно: из проекта (`true`) или из настроек приложения.
+    let mut rules: Vec<(Arc<dyn Rule>, bool)> = vec![];
+    for (source, text, project) in &packs {
+        hasher.update(source.as_bytes());
+        hasher.update(text.as_bytes());
+        let pack = match parse_pack(source, text) {
+            Ok(p) => p,
+            Err(e) => {
+                log::warn!("{}: {}", source, e);
+                warnings.push(format!("{}: {}", source, e));
+                continue;
+            }
+        };
+        for def in pack.rule {
+            let rule = match build(def, source, root) {
+                Ok(r) => r,
+                Err(e) => {
+                    warnings.push(format!("{}: {}", source, e));
+                    continue;
+                }
+            };
+            let id = rule.meta().id.clone();
+            if builtin.contains(id.as_str()) {
+                warnings.push(format!("{}: правило {} совпадает со встроенным", source, id));
+                continue;
+            }
+            match rules.iter().position(|(r, _)| r.meta().id == id) {
+                // Правило проекта сильнее правила из настроек приложения.
+                Some(i) if *project && !rules[i].1 => rules[i] = (rule, true),
+                Some(_) => warnings.push(format!("{}: правило {} уже объявлено", source, id)),
+                None => rules.push((rule, *project)),
+            }
+        }
+    }
+
+    let set = RuleSet {
+        rules: rules.into_iter().map(|(r, _)| r).collect(),
+        fingerprint: match packs.is_empty() {
+            true => String::new(),
+            false => format!("{:x}", hasher.finalize()),
+        },
+    };
+    (set, warnings)
+}
+
+/// Файлы наборов в папке `dir` по имени; нечитаемые пропускаются.
+fn read_packs(fs: &dyn ProjectFs, dir: &Path) -> Vec<(std::path::PathBuf, String)> {
+    let mut paths: Vec<_> = fs
+        .read_dir(dir)
+        .into_iter()
+        .filter(|e| !e.is_dir)
+        .map(|e| e.path)
+        .filter(|p| p.extension().and_then(|e| e.to_str()).is_some_and(|e| PACK_EXTENSIONS.contains(&e)))
+        .collect();
+    paths.sort();
+    paths.into_iter().filter_map(|p| fs.read_to_string(&p).map(|t| (p, t))).collect()
+}
+
+fn parse_pack(source: &str, text: &str) -> Result<RulePack, String> {
+    match source.ends_with(".toml") {
+        true => toml::from_str(text).map_err(|e| e.message().to_string()),
+        false => serde_norway::from_str(text).map_err(|e| e.to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::analysis_job::Job;
+    use crate::progress::NoProgress;
+    use std::time::Duration;
+
+    fn pack(dir: &Path, name: &str, text: &str) {
+        let rules = dir.join(PROJECT_RULES_DIR);
+        std::fs::create_dir_all(&rules).unwrap();
+        std::fs::write(rules.join(name), text).unwrap();
+    }
+
+    fn index(root: &Path) -> FileIndex {
+        let job = Job::new(String::new(), 1_000, Duration::from_secs(10), Arc::new(NoProgress));
+        FileIndex::build(&DiskFs, root, &job, &[])
+    }
+
+    #[test]
+    fn loads_packs_and_reports_errors() {
+        let dir = tempfile::tempdir().unwrap();
+        pack(
+            dir.path(),
+            "team.toml",
+            r#"
+[[rule]]
+id = "team/no-axios"
+kind = "regex"
+pattern = "axios"
+languages = ["javascript"]
+message = "axios запрещён"
+
+[[rule]]
+id = "team/bad-severity"
+kind = "regex"
+pattern = "x"
+severity = "critical"
+message = "-"
+
+[[rule]]
+id = "security/eval"
+kind = "regex"
+pattern = "eval"
+message = "-"
+"#,
+        );
+        pack(dir.path(), "more.yaml", "rule:\n  - id: team/license\n    kind: file\n    paths: [LICENSE]\n    message: Нет лицензии\n");
+        let (set, warnings) = load(&DiskFs, dir.path(), &AppDirs::default());
+
+        let ids: Vec<_> = set.meta().into_iter().map(|m| (m.id, m.languages)).collect();
+        assert_eq!(ids[0], ("team/license".to_string(), vec![]));
+        assert_eq!(ids[1].0, "team/no-axios");
+        assert_eq!(ids[1].1, ["js", "jsx", "mjs", "cjs"]);
+        assert_eq!(warnings.len(), 2, "{:?}", warnings);
+        assert!(warnings[0].contains("critical"));
+        assert!(warnings[1].contains("встроенным"));
+        assert!(!set.fingerprint().is_empty());
+    }
+
+    #[test]
+    fn project_rules_override_app_rules() {
+        let project = tempfile::tempdir().unwrap();
+        let app = tempfile::tempdir().unwrap();
+        let text = |severity: &str| format!("[[rule]]\nid = \"team/x\"\nkind = \"regex\"\npattern = \"x\"\nseverity = \"{}\"\nmessage = \"x\"\n", severity);
+        std::fs::create_dir_all(app.path().join(RULES_DIR)).unwrap();
+        std::fs::write(app.path().join(RULES_DIR).join("a.toml"), text("info")).unwrap();
+        pack(project.path(), "a.toml", &text("high"));
+
+        let dirs = AppDirs::new(app.path().to_path_buf(), app.path().to_path_buf());
+        let (set, warnings) = load(&DiskFs, project.path(), &dirs);
+        assert!(warnings.is_empty(), "{:?}", warnings);
+        assert_eq!(set.meta().len(), 1);
+        assert_eq!(set.meta()[0].severity, "high");
+        assert_eq!(set.meta()[0].source, ".papayu/rules/a.toml");
+    }
+
+    #[test]
+    fn manifest_rules_locate_keys() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("package.json"), "{\n  \"name\": \"x\",\n  \"license\": \"GPL-3.0\"\n}\n").unwrap();
+        std::fs::write(dir.path().join("Cargo.toml"), "[package]\nname = \"x\"\n").unwrap();
+        pack(
+            dir.path(),
+            "manifests.toml",
+            r#"
+[[rule]]
+id = "team/gpl"
+kind = "manifest"
+file = "package.json"
+key = "license"
+matches = "^GPL"
+message = "GPL-лицензия"
+
+[[rule]]
+id = "team/edition"
+kind = "manifest"
+file = "Cargo.toml"
+key = "package.edition"
+message = "Не указан edition"
+fix_hint = "Добавьте edition = \"2021\""
+
+[rule.fix]
+kind = "insert_after"
+anchor = "name = \"x\"\n"
+content = "edition = \"2021\"\n"
+"#,
+        );
+        let (set, warnings) = load(&DiskFs, dir.path(), &AppDirs::default());
+        assert!(warnings.is_empty(), "{:?}", warnings);
+        let findings = set.check_project(&DiskFs, &index(dir.path()));
+
+        assert_eq!(findings.len(), 2);
+        assert_eq!(findings[0].details, "package.json: license = GPL-3.0");
+        assert_eq!(findings[0].start_line, Some(3));
+        assert_eq!(findings[1].details, "Cargo.toml: нет package.edition");
+        assert_eq!(findings[1].start_line, Some(1), "место — таблица [package]");
+        assert_eq!(findings[1].fix_hint.as_deref(), Some("Добавьте edition = \"2021\""));
+
+        let fixes = set.fixes(&findings);
+        assert_eq!(fixes.len(), 1);
+        assert_eq!(fixes[0].id, "team/edition@Cargo.toml");
+        assert_eq!(fixes[0].path, "Cargo.toml");
+    }
+}
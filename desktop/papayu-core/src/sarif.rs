@@ -0,0 +1,296 @@
+//! Экспорт `AnalyzeReport` в SARIF 2.1.0 — формат, который читают CI и инструменты ревью.
+//! Находки становятся `results` с местом в коде, сигналы — `results` без места; в
+//! `tool.driver.rules` — все правила анализа и правила пользователя, а не только сработавшие.
+
+use serde_json::{json, Value};
+use sha2::{Digest, Sha256};
+
+use crate::analyze::STRUCTURE_RULES;
+use crate::deep_analysis::{FILE_RULES, QUALITY_PATTERNS, SECRET_PATTERNS, VULN_PATTERNS};
+use crate::rules::RuleMeta;
+use crate::types::{AnalyzeReport, Finding, ProjectSignal};
+
+pub const SARIF_VERSION: &str = "2.1.0";
+pub const SARIF_SCHEMA: &str = "https://docs.oasis-open.org/sarif/sarif/v2.1.0/errata01/os/schemas/sarif-schema-2.1.0.json";
+/// База относительных путей в `artifactLocation`.
+const SRCROOT: &str = "%SRCROOT%";
+const FINGERPRINT: &str = "papayu/v1";
+
+/// Правило для `tool.driver.rules`.
+struct Rule {
+    id: String,
+    category: String,
+    description: String,
+    /// Шаблон и расширения — у правил глубокого анализа.
+    pattern: Option<&'static str>,
+    extensions: Vec<String>,
+    /// Уровень по умолчанию; у встроенных правил — по категории.
+    level: &'static str,
+    help: Option<String>,
+}
+
+fn catalog(signals: &[ProjectSignal], custom: &[RuleMeta]) -> Vec<Rule> {
+    let builtin_level = |category: &str| if category == "security" { "error" } else { "warning" };
+    let pattern = |category: &str, (id, p, d, e): (&str, &'static str, &str, &'static str)| Rule {
+        id: id.to_string(),
+        category: category.to_string(),
+        description: d.to_string(),
+        pattern: Some(p),
+        extensions: e.split(',').filter(|e| !e.is_empty()).map(str::to_string).collect(),
+        level: builtin_level(category),
+        help: None,
+    };
+    let plain = |id: &str, category: &str, description: &str| Rule {
+        id: id.to_string(),
+        category: category.to_string(),
+        description: description.to_string(),
+        pattern: None,
+        extensions: vec![],
+        level: builtin_level(category),
+        help: None,
+    };
+
+    let mut rules: Vec<Rule> = vec![];
+    rules.extend(SECRET_PATTERNS.iter().map(|&(id, p, d)| pattern("security", (id, p, d, ""))));
+    rules.extend(VULN_PATTERNS.iter().map(|&r| pattern("security", r)));
+    rules.extend(QUALITY_PATTERNS.iter().map(|&r| pattern("quality", r)));
+    rules.extend(FILE_RULES.iter().map(|(id, d)| plain(id, "quality", d)));
+    rules.extend(STRUCTURE_RULES.iter().map(|(id, c, d)| plain(id, c, d)));
+    rules.extend(custom.iter().map(|r| Rule {
+        id: r.id.clone(),
+        category: r.category.clone(),
+        description: r.message.clone(),
+        pattern: None,
+        extensions: r.languages.clone(),
+        level: level(&r.severity),
+        help: r.fix_hint.clone(),
+    }));
+    for s in signals {
+        let id = signal_rule(s);
+        if !rules.iter().any(|r| r.id == id) {
+            rules.push(plain(&id, &s.category, &format!("Сигнал анализа: {}", s.category)));
+        }
+    }
+    rules
+}
+
+fn signal_rule(signal: &ProjectSignal) -> String {
+    format!("signal/{}", signal.category)
+}
+
+/// `high` | `warn` | `info` → уровень SARIF.
+fn level(severity: &str) -> &'static str {
+    match severity {
+        "high" => "error",
+        "warn" => "warning",
+        _ => "note",
+    }
+}
+
+/// Путь в URI: сегменты кодируются, разделитель — `/`.
+fn uri_path(path: &str) -> String {
+    let mut out = String::with_capacity(path.len());
+    for b in path.replace('\\', "/").bytes() {
+        match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' | b'/' => out.push(b as char),
+            _ => out.push_str(&format!("%{:02X}", b)),
+        }
+    }
+    out
+}
+
+/// `file:` URI папки проекта со слешем в конце.
+fn root_uri(path: &str) -> String {
+    let mut p = uri_path(path.trim_end_matches(['/', '\\']));
+    if !p.starts_with('/') {
+        p.insert(0, '/'); // C:/… в Windows
+    }
+    p = p.replacen("%3A", ":", 1);
+    format!("file://{}/", p)
+}
+
+fn location(f: &Finding) -> Option<Value> {
+    let path = f.path.as_ref()?;
+    let mut physical = json!({ "artifactLocation": { "uri": uri_path(path), "uriBaseId": SRCROOT } });
+    if let Some(start_line) = f.start_line {
+        let mut region = json!({ "startLine": start_line });
+        for (key, value) in [("startColumn", f.start_column), ("endLine", f.end_line), ("endColumn", f.end_column)] {
+            if let Some(v) = value {
+                region[key] = json!(v);
+            }
+        }
+        if let Some(snippet) = &f.snippet {
+            region["snippet"] = json!({ "text": snippet });
+        }
+        physical["region"] = region;
+    }
+    Some(json!({ "physicalLocation": physical }))
+}
+
+fn fingerprint(f: &Finding, rule_id: &str) -> String {
+    let key = format!("{}\n{}\n{}", rule_id, f.path.as_deref().unwrap_or(""), f.snippet.as_deref().unwrap_or(""));
+    format!("{:x}", Sha256::digest(key.as_bytes()))
+}
+
+/// SARIF-лог отчёта: один `run`, пути — относительно папки проекта (`%SRCROOT%`).
+pub fn to_sarif(report: &AnalyzeReport) -> Value {
+    let rules = catalog(&report.signals, &report.config.custom_rules);
+    let index = |id: &str| rules.iter().position(|r| r.id == id);
+
+    let mut results: Vec<Value> = vec![];
+    for f in &report.findings {
+        let message = if f.details.is_empty() { f.title.clone() } else { format!("{}: {}", f.title, f.details) };
+        let mut result = json!({ "level": level(&f.severity), "message": { "text": message } });
+        if let Some(id) = &f.rule_id {
+            result["ruleId"] = json!(id);
+            if let Some(i) = index(id) {
+                result["ruleIndex"] = json!(i);
+            }
+            result["partialFingerprints"] = json!({ FINGERPRINT: fingerprint(f, id) });
+        }
+        if let Some(loc) = location(f) {
+            result["locations"] = json!([loc]);
+        }
+        results.push(result);
+    }
+    for s in &report.signals {
+        let id = signal_rule(s);
+        let mut result = json!({ "ruleId": id, "level": level(&s.level), "message": { "text": s.message } });
+        if let Some(i) = index(&id) {
+            result["ruleIndex"] = json!(i);
+        }
+        results.push(result);
+    }
+
+    let rules: Vec<Value> = rules
+        .iter()
+        .map(|r| {
+            let mut properties = json!({ "tags": [r.category] });
+            if let Some(p) = r.pattern {
+                properties["pattern"] = json!(p);
+            }
+            if !r.extensions.is_empty() {
+                properties["extensions"] = json!(r.extensions);
+            }
+            let mut rule = json!({
+                "id": r.id,
+                "shortDescription": { "text": r.description },
+                "defaultConfiguration": { "level": r.level },
+                "properties": properties,
+            });
+            if let Some(help) = &r.help {
+                rule["help"] = json!({ "text": help });
+            }
+            rule
+        })
+        .collect();
+
+    json!({
+        "$schema": SARIF_SCHEMA,
+        "version": SARIF_VERSION,
+        "runs": [{
+            "tool": {
+                "driver": {
+                    "name": "PAPA YU",
+                    "version": env!("CARGO_PKG_VERSION"),
+                    "rules": rules,
+                }
+            },
+            "originalUriBaseIds": { SRCROOT: { "uri": root_uri(&report.path) } },
+            "columnKind": "unicodeCodePoints",
+            "invocations": [{ "executionSuccessful": true }],
+            "results": results,
+            "properties": {
+                "profile": report.config.profile,
+                "rules": report.config.rules,
+                "stopped": report.stopped,
+            },
+        }]
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SCHEMA: &str = include_str!("../tests/fixtures/sarif-schema-2.1.0.json");
+
+    fn report() -> AnalyzeReport {
+        let limits = json!({
+            "max_files": 1, "max_duration_secs": 1, "deep_max_files": 1, "deep_max_file_bytes": 1, "deep_max_depth": 1,
+            "context_max_bytes": 1, "context_max_file_bytes": 1, "context_max_files": 1, "context_max_depth": 1,
+        });
+        serde_json::from_value(json!({
+            "path": "/tmp/мой проект",
+            "narrative": "",
+            "stats": { "file_count": 2, "dir_count": 1, "total_size_bytes": 10, "top_extensions": [], "max_depth": 1 },
+            "structure": { "project_type": "", "architecture": "", "structure_notes": [] },
+            "signals": [
+                { "category": "security", "level": "high", "message": "Есть .env файл" },
+                { "category": "history", "level": "info", "message": "Коммитов: 3" },
+            ],
+            "findings": [
+                {
+                    "severity": "high", "title": "⚠️ eval()", "details": "src/a b.js:3:5", "rule_id": "security/eval",
+                    "category": "security", "path": "src/a b.js", "start_line": 3, "start_column": 5, "end_line": 3,
+                    "end_column": 10, "snippet": "x = eval(y)",
+                },
+                { "severity": "high", "title": "Риск секретов", "details": ".env", "rule_id": "structure/env-file", "category": "security", "path": ".env" },
+                { "severity": "warn", "title": "Много файлов в корне", "details": "", "rule_id": "structure/root-files" },
+                { "severity": "info", "title": "Старая находка", "details": "без правила" },
+            ],
+            "recommendations": [],
+            "actions": [],
+            "project_context": { "stack": [], "domain": "", "maturity": "MVP", "complexity": "Low", "risk_level": "High" },
+            "report_md": "",
+            "llm_context": { "concise_summary": "", "key_risks": [], "top_recommendations": [], "signals": [] },
+            "changes": null,
+            "stopped": "timeout",
+            "config": {
+                "profile": "standard", "source": null, "limits": limits, "rules": ["secrets"],
+                "exclude": [], "git_history": false, "warnings": [],
+            },
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn matches_schema() {
+        let schema: Value = serde_json::from_str(SCHEMA).unwrap();
+        let validator = jsonschema::JSONSchema::options().should_validate_formats(true).compile(&schema).unwrap();
+        let log = to_sarif(&report());
+        let errors: Vec<String> = match validator.validate(&log) {
+            Ok(()) => vec![],
+            Err(errors) => errors.map(|e| format!("{} at {}", e, e.instance_path)).collect(),
+        };
+        assert!(errors.is_empty(), "SARIF не проходит схему: {:#?}", errors);
+    }
+
+    #[test]
+    fn results_and_rules() {
+        let log = to_sarif(&report());
+        let run = &log["runs"][0];
+        let rules = run["tool"]["driver"]["rules"].as_array().unwrap();
+        let results = run["results"].as_array().unwrap();
+        assert_eq!(results.len(), 6);
+        for r in results.iter().filter(|r| r.get("ruleIndex").is_some()) {
+            assert_eq!(rules[r["ruleIndex"].as_u64().unwrap() as usize]["id"], r["ruleId"]);
+        }
+        for (id, ..) in SECRET_PATTERNS {
+            assert!(rules.iter().any(|r| r["id"] == *id), "{}", id);
+        }
+
+        let eval = &results[0];
+        assert_eq!(eval["level"], "error");
+        let loc = &eval["locations"][0]["physicalLocation"];
+        assert_eq!(loc["artifactLocation"]["uri"], "src/a%20b.js");
+        assert_eq!(loc["artifactLocation"]["uriBaseId"], SRCROOT);
+        assert_eq!(loc["region"], json!({ "startLine": 3, "startColumn": 5, "endLine": 3, "endColumn": 10, "snippet": { "text": "x = eval(y)" } }));
+        assert!(eval["partialFingerprints"][FINGERPRINT].is_string());
+        assert!(results[1]["locations"][0]["physicalLocation"].get("region").is_none());
+        assert!(results[2].get("locations").is_none());
+        assert!(results[3].get("ruleId").is_none());
+        assert_eq!(results[5]["ruleId"], "signal/history");
+        assert_eq!(run["originalUriBaseIds"][SRCROOT]["uri"], "file:///tmp/%D0%BC%D0%BE%D0%B9%20%D0%BF%D1%80%D0%BE%D0%B5%D0%BA%D1%82/");
+    }
+}
@@ -0,0 +1,823 @@
+//! История сессий apply: `app_data_dir/history/<session_id>/` со snapshot'ом
+//! и метаданными `session.json`. Сессии привязаны к корню проекта.
+
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU32, Ordering};
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use crate::actions;
+use crate::app_dirs::AppDirs;
+use crate::git;
+use crate::merge::merge3;
+use crate::types::{
+    Action, ActionKind, ApplyPayload, FileConflict, FileDigest, ManifestFile, SessionInfo, SessionManifest,
+    SessionStatus, UndoMode, UndoResult,
+};
+
+pub const MANIFEST_VERSION: u32 = 1;
+/// Сколько байт содержимого удаляемых папок можно сохранить в snapshot одной сессии.
+pub const MAX_SNAPSHOT_DIR_BYTES: u64 = 100 * 1024 * 1024;
+
+const HISTORY_DIR: &str = "history";
+const SESSION_META: &str = "session.json";
+const SESSION_MANIFEST: &str = "manifest.json";
+const SNAPSHOT_DIR: &str = "snapshot";
+const AFTER_DIR: &str = "after";
+const REDO_DIR: &str = "redo";
+const MISSING_DIR: &str = ".missing";
+
+pub fn history_dir(dirs: &AppDirs) -> Result<PathBuf, String> {
+    dirs.data_dir().map(|d| d.join(HISTORY_DIR))
+}
+
+/// Ключ проекта: канонический путь, чтобы `./proj` и `/abs/proj` совпадали.
+pub fn project_key(project_root: &Path) -> String {
+    fs::canonicalize(project_root)
+        .unwrap_or_else(|_| project_root.to_path_buf())
+        .to_string_lossy()
+        .to_string()
+}
+
+/// Создаёт папку новой сессии в `history` и возвращает её id: время в мс и счётчик.
+/// Папка создаётся `create_dir`, занятый id пропускается — два apply в одну
+/// миллисекунду (в том числе из приложения и CLI одновременно) не столкнутся.
+pub fn create_session_dir(history: &Path) -> Result<(String, PathBuf), String> {
+    static SEQ: AtomicU32 = AtomicU32::new(0);
+    fs::create_dir_all(history).map_err(|e| e.to_string())?;
+    loop {
+        let seq = SEQ.fetch_add(1, Ordering::Relaxed) % 1000;
+        let id = format!("{}{:03}", chrono::Utc::now().timestamp_millis(), seq);
+        let dir = session_dir(history, &id);
+        match fs::create_dir(&dir) {
+            Ok(()) => return Ok((id, dir)),
+            Err(e) if e.kind() == io::ErrorKind::AlreadyExists => continue,
+            Err(e) => return Err(e.to_string()),
+        }
+    }
+}
+
+pub fn now_rfc3339() -> String {
+    chrono::Utc::now().to_rfc3339_opts(chrono::SecondsFormat::Millis, true)
+}
+
+pub fn session_dir(history: &Path, session_id: &str) -> PathBuf {
+    history.join(session_id)
+}
+
+pub fn new_session_info(
+    session_id: &str,
+    project_root: &Path,
+    actions: &[Action],
+) -> SessionInfo {
+    SessionInfo {
+        session_id: session_id.to_string(),
+        project_root: project_key(project_root),
+        created_at: now_rfc3339(),
+        status: SessionStatus::Applied,
+        undone_at: None,
+        paths: action_paths(actions)
+            .iter()
+            .map(|p| p.to_string_lossy().to_string())
+            .collect(),
+        actions: actions.to_vec(),
+        git: None,
+    }
+}
+
+/// Пути, которые затрагивают действия (включая цели переноса и копии), без повторов.
+pub fn action_paths(actions: &[Action]) -> Vec<PathBuf> {
+    let mut out: Vec<PathBuf> = vec![];
+    for p in actions.iter().flat_map(actions::touched_paths) {
+        let p = PathBuf::from(p);
+        if !out.contains(&p) {
+            out.push(p);
+        }
+    }
+    out
+}
+
+pub fn write_session_info(session_dir: &Path, info: &SessionInfo) -> Result<(), String> {
+    let json = serde_json::to_string_pretty(info).map_err(|e| e.to_string())?;
+    fs::write(session_dir.join(SESSION_META), json).map_err(|e| e.to_string())
+}
+
+pub fn read_session_info(session_dir: &Path) -> Result<SessionInfo, String> {
+    let raw = fs::read_to_string(session_dir.join(SESSION_META))
+        .map_err(|_| "session_not_found".to_string())?;
+    serde_json::from_str(&raw).map_err(|e| format!("session_meta_invalid: {}", e))
+}
+
+pub fn file_digest(path: &Path) -> Option<FileDigest> {
+    if !path.is_file() {
+        return None;
+    }
+    let bytes = fs::read(path).ok()?;
+    Some(FileDigest {
+        sha256: format!("{:x}", Sha256::digest(&bytes)),
+        size: bytes.len() as u64,
+    })
+}
+
+/// Хэши целевых путей в текущем состоянии (по порядку `targets`).
+pub fn digest_paths(project_root: &Path, targets: &[PathBuf]) -> Vec<Option<FileDigest>> {
+    targets.iter().map(|t| file_digest(&project_root.join(t))).collect()
+}
+
+pub fn build_manifest(
+    info: &SessionInfo,
+    app_version: &str,
+    payload: &ApplyPayload,
+    targets: &[PathBuf],
+    before: Vec<Option<FileDigest>>,
+    after: Vec<Option<FileDigest>>,
+) -> SessionManifest {
+    let files = targets
+        .iter()
+        .zip(before.into_iter().zip(after))
+        .map(|(t, (before, after))| ManifestFile {
+            path: t.to_string_lossy().to_string(),
+            before,
+            after,
+        })
+        .collect();
+
+    SessionManifest {
+        version: MANIFEST_VERSION,
+        app_version: app_version.to_string(),
+        session_id: info.session_id.clone(),
+        project_root: info.project_root.clone(),
+        created_at: info.created_at.clone(),
+        payload: payload.clone(),
+        files,
+    }
+}
+
+/// Завершает успешный apply: копии `after/`, `manifest.json` и `session.json`.
+/// Действия из `skipped` (не применённые в режиме best effort) в сессию не попадают.
+/// Из snapshot'а убираются только пути действий из `untouched` — не начатых, потому
+/// что зависели от упавших: упавшее действие могло изменить свои пути частично,
+/// и undo должен их восстановить.
+#[allow(clippy::too_many_arguments)]
+pub fn finalize_session(
+    session_dir: &Path,
+    project_root: &Path,
+    session_id: &str,
+    app_version: &str,
+    payload: &ApplyPayload,
+    before: Vec<Option<FileDigest>>,
+    skipped: &[String],
+    untouched: &[String],
+) -> Result<(), String> {
+    let all_targets = action_paths(&payload.actions);
+    let applied = ApplyPayload {
+        path: payload.path.clone(),
+        mode: payload.mode,
+        dry_run: false,
+        analyze: false,
+        git: payload.git.clone(),
+        actions: payload
+            .actions
+            .iter()
+            .filter(|a| !skipped.contains(&a.id))
+            .cloned()
+            .collect(),
+    };
+    let touched: Vec<Action> = payload
+        .actions
+        .iter()
+        .filter(|a| !untouched.contains(&a.id))
+        .cloned()
+        .collect();
+    let targets = action_paths(&touched);
+    let (kept, dropped): (Vec<_>, Vec<_>) = all_targets
+        .into_iter()
+        .zip(before)
+        .partition(|(t, _)| targets.contains(t));
+    prune_snapshot(session_dir, &targets, dropped.into_iter().map(|(t, _)| t).collect())?;
+    let before = kept.into_iter().map(|(_, d)| d).collect();
+
+    let mut info = new_session_info(session_id, project_root, &applied.actions);
+    info.paths = targets.iter().map(|p| p.to_string_lossy().to_string()).collect();
+    let manifest = build_manifest(
+        &info,
+        app_version,
+        &applied,
+        &targets,
+        before,
+        digest_paths(project_root, &targets),
+    );
+    snapshot_after(session_dir, project_root, &targets)?;
+    write_manifest(session_dir, &manifest)?;
+    write_session_info(session_dir, &info)
+}
+
+/// Убирает из snapshot'а пути, которые не пересекаются ни с одним из `keep`.
+fn prune_snapshot(session_dir: &Path, keep: &[PathBuf], dropped: Vec<PathBuf>) -> Result<(), String> {
+    let dropped: Vec<String> = dropped
+        .into_iter()
+        .map(|p| p.to_string_lossy().to_string())
+        .filter(|p| !keep.iter().any(|k| paths_overlap(p, &k.to_string_lossy())))
+        .collect();
+    if dropped.is_empty() {
+        return Ok(());
+    }
+    let snap_dir = session_dir.join(SNAPSHOT_DIR);
+    for p in &dropped {
+        let _ = remove_path(&snap_dir.join(p));
+    }
+    let mut markers = read_markers(&snap_dir);
+    markers.missing.retain(|p| !dropped.contains(p));
+    markers.dirs.retain(|p| !dropped.contains(p));
+    write_markers(&snap_dir, &markers)
+}
+
+pub fn write_manifest(session_dir: &Path, manifest: &SessionManifest) -> Result<(), String> {
+    let json = serde_json::to_string_pretty(manifest).map_err(|e| e.to_string())?;
+    fs::write(session_dir.join(SESSION_MANIFEST), json).map_err(|e| e.to_string())
+}
+
+pub fn read_manifest(session_dir: &Path) -> Result<SessionManifest, String> {
+    let raw = fs::read_to_string(session_dir.join(SESSION_MANIFEST))
+        .map_err(|_| "manifest_not_found".to_string())?;
+    let manifest: SessionManifest =
+        serde_json::from_str(&raw).map_err(|e| format!("manifest_invalid: {}", e))?;
+    if manifest.version > MANIFEST_VERSION {
+        return Err(format!("manifest_version_unsupported: {}", manifest.version));
+    }
+    Ok(manifest)
+}
+
+pub fn is_valid_session_id(session_id: &str) -> bool {
+    !session_id.is_empty() && session_id.chars().all(|c| c.is_ascii_alphanumeric())
+}
+
+/// Все сессии (новые сначала); при `project_root` — только сессии этого проекта.
+pub fn list_sessions(history: &Path, project_root: Option<&Path>) -> Vec<SessionInfo> {
+    let key = project_root.map(project_key);
+    let entries = match fs::read_dir(history) {
+        Ok(e) => e,
+        Err(_) => return vec![],
+    };
+
+    let mut sessions: Vec<SessionInfo> = entries
+        .flatten()
+        .filter(|e| e.path().is_dir())
+        .filter_map(|e| read_session_info(&e.path()).ok())
+        .filter(|s| key.as_ref().map_or(true, |k| &s.project_root == k))
+        .collect();
+    sessions.sort_by(|a, b| b.created_at.cmp(&a.created_at));
+    sessions
+}
+
+pub fn latest_applied(history: &Path, project_root: &Path) -> Option<SessionInfo> {
+    list_sessions(history, Some(project_root))
+        .into_iter()
+        .find(|s| s.status == SessionStatus::Applied)
+}
+
+pub fn paths_overlap(a: &str, b: &str) -> bool {
+    let (a, b) = (Path::new(a), Path::new(b));
+    a.starts_with(b) || b.starts_with(a)
+}
+
+/// Более поздние применённые сессии того же проекта, затронувшие те же пути.
+pub fn find_conflicts(history: &Path, session: &SessionInfo) -> Vec<String> {
+    list_sessions(history, Some(Path::new(&session.project_root)))
+        .into_iter()
+        .filter(|s| s.session_id != session.session_id)
+        .filter(|s| s.status == SessionStatus::Applied && s.created_at > session.created_at)
+        .filter(|s| {
+            s.paths
+                .iter()
+                .any(|p| session.paths.iter().any(|q| paths_overlap(p, q)))
+        })
+        .map(|s| s.session_id)
+        .collect()
+}
+
+/// Путь для snapshot'а; `deep` — копировать папку целиком (нужно для удаления папок).
+pub struct SnapshotTarget {
+    pub path: PathBuf,
+    pub deep: bool,
+}
+
+/// Удаляемые и переносимые папки копируются целиком; цель переноса или копии
+/// до apply обычно не существует и при откате просто удаляется.
+pub fn snapshot_targets(actions: &[Action]) -> Vec<SnapshotTarget> {
+    actions
+        .iter()
+        .flat_map(|a| {
+            let source = SnapshotTarget {
+                path: PathBuf::from(&a.path),
+                deep: matches!(a.kind, ActionKind::DeleteDir | ActionKind::MoveDir),
+            };
+            let dest = a.to.as_ref().map(|to| SnapshotTarget {
+                path: PathBuf::from(to),
+                deep: false,
+            });
+            std::iter::once(source).chain(dest)
+        })
+        .collect()
+}
+
+/// Отметки snapshot'а (`<snapshot>.json` рядом с папкой, чтобы не пересекаться с файлами проекта).
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct SnapshotMarkers {
+    missing: Vec<String>, // путей не было — при откате удалить (файл или папку)
+    dirs: Vec<String>,    // путь был папкой — при откате создать заново
+}
+
+fn markers_path(snap_dir: &Path) -> PathBuf {
+    snap_dir.with_extension("json")
+}
+
+fn read_markers(snap_dir: &Path) -> SnapshotMarkers {
+    fs::read_to_string(markers_path(snap_dir))
+        .ok()
+        .and_then(|raw| serde_json::from_str(&raw).ok())
+        .unwrap_or_default()
+}
+
+/// Размер папки: (файлов, байт).
+pub fn dir_size(path: &Path) -> (u64, u64) {
+    walkdir::WalkDir::new(path)
+        .into_iter()
+        .filter_map(Result::ok)
+        .filter(|e| e.file_type().is_file())
+        .fold((0, 0), |(n, bytes), e| {
+            (n + 1, bytes + e.metadata().map(|m| m.len()).unwrap_or(0))
+        })
+}
+
+pub fn snapshot_paths(
+    session_dir: &Path,
+    project_root: &Path,
+    targets: &[SnapshotTarget],
+) -> Result<(), String> {
+    snapshot_into(&session_dir.join(SNAPSHOT_DIR), project_root, targets)
+}
+
+/// Копии файлов после apply — база для трёхстороннего слияния при undo.
+pub fn snapshot_after(
+    session_dir: &Path,
+    project_root: &Path,
+    targets: &[PathBuf],
+) -> Result<(), String> {
+    let targets: Vec<SnapshotTarget> = targets
+        .iter()
+        .map(|t| SnapshotTarget {
+            path: t.clone(),
+            deep: false,
+        })
+        .collect();
+    snapshot_into(&session_dir.join(AFTER_DIR), project_root, &targets)
+}
+
+fn copy_tree(src: &Path, dst: &Path, budget: &mut u64) -> Result<(), String> {
+    for entry in walkdir::WalkDir::new(src).into_iter().filter_map(Result::ok) {
+        let rel = entry.path().strip_prefix(src).map_err(|e| e.to_string())?;
+        let target = dst.join(rel);
+        let ft = entry.file_type();
+        if ft.is_dir() {
+            fs::create_dir_all(&target).map_err(|e| e.to_string())?;
+        } else if ft.is_file() {
+            let len = entry.metadata().map(|m| m.len()).unwrap_or(0);
+            *budget = budget
+                .checked_sub(len)
+                .ok_or_else(|| format!("snapshot_too_large: {}", src.display()))?;
+            fs::copy(entry.path(), &target).map_err(|e| e.to_string())?;
+        } else if ft.is_symlink() {
+            copy_symlink(entry.path(), &target)?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(unix)]
+fn copy_symlink(src: &Path, dst: &Path) -> Result<(), String> {
+    let link = fs::read_link(src).map_err(|e| e.to_string())?;
+    if dst.symlink_metadata().is_ok() {
+        fs::remove_file(dst).map_err(|e| e.to_string())?;
+    }
+    std::os::unix::fs::symlink(link, dst).map_err(|e| e.to_string())
+}
+
+#[cfg(not(unix))]
+fn copy_symlink(_src: &Path, _dst: &Path) -> Result<(), String> {
+    Ok(())
+}
+
+fn snapshot_into(
+    snap_dir: &Path,
+    project_root: &Path,
+    targets: &[SnapshotTarget],
+) -> Result<(), String> {
+    fs::create_dir_all(snap_dir).map_err(|e| e.to_string())?;
+
+    let mut markers = SnapshotMarkers::default();
+    let mut budget = MAX_SNAPSHOT_DIR_BYTES;
+
+    for t in targets {
+        let abs = project_root.join(&t.path);
+        let snap = snap_dir.join(&t.path);
+        let rel = t.path.to_string_lossy().to_string();
+
+        if abs.is_dir() {
+            markers.dirs.push(rel);
+            if t.deep {
+                copy_tree(&abs, &snap, &mut budget)?;
+            }
+            continue;
+        }
+
+        if abs.exists() {
+            if let Some(parent) = snap.parent() {
+                fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+            }
+            fs::copy(&abs, &snap).map_err(|e| e.to_string())?;
+        } else {
+            markers.missing.push(rel);
+        }
+    }
+
+    write_markers(snap_dir, &markers)
+}
+
+fn write_markers(snap_dir: &Path, markers: &SnapshotMarkers) -> Result<(), String> {
+    let json = serde_json::to_string_pretty(markers).map_err(|e| e.to_string())?;
+    fs::write(markers_path(snap_dir), json).map_err(|e| e.to_string())
+}
+
+pub fn revert_snapshot(session_dir: &Path, project_root: &Path) -> Result<Vec<String>, String> {
+    revert_from(&session_dir.join(SNAPSHOT_DIR), project_root, &[])
+}
+
+fn remove_path(abs: &Path) -> Result<(), String> {
+    if abs.is_dir() {
+        fs::remove_dir_all(abs).map_err(|e| e.to_string())
+    } else {
+        fs::remove_file(abs).map_err(|e| e.to_string())
+    }
+}
+
+/// Восстанавливает пути из snapshot'а, кроме перечисленных в `skip`.
+fn revert_from(snap_dir: &Path, project_root: &Path, skip: &[PathBuf]) -> Result<Vec<String>, String> {
+    if !snap_dir.exists() {
+        return Err("snapshot_missing".into());
+    }
+    let skipped = |rel: &Path| skip.iter().any(|p| p == rel);
+    // Сессии до появления `<snapshot>.json` хранили отметки в `.missing/`.
+    let markers = read_markers(snap_dir);
+
+    let mut restored = vec![];
+
+    for d in &markers.dirs {
+        fs::create_dir_all(project_root.join(d)).map_err(|e| e.to_string())?;
+    }
+
+    for entry in walkdir::WalkDir::new(snap_dir)
+        .min_depth(1)
+        .into_iter()
+        .filter_map(Result::ok)
+    {
+        let snap_path = entry.path().to_path_buf();
+        let rel = snap_path
+            .strip_prefix(snap_dir)
+            .map_err(|e| e.to_string())?;
+
+        if let Ok(orig) = rel.strip_prefix(MISSING_DIR) {
+            if entry.file_type().is_dir() || skipped(orig) {
+                continue;
+            }
+            let abs = project_root.join(orig);
+            if abs.exists() {
+                remove_path(&abs)?;
+                restored.push(orig.to_string_lossy().to_string());
+            }
+            continue;
+        }
+
+        if skipped(rel) {
+            continue;
+        }
+        let abs = project_root.join(rel);
+        let ft = entry.file_type();
+        if ft.is_dir() {
+            fs::create_dir_all(&abs).map_err(|e| e.to_string())?;
+            continue;
+        }
+        if let Some(parent) = abs.parent() {
+            fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+        }
+        if ft.is_symlink() {
+            copy_symlink(&snap_path, &abs)?;
+        } else {
+            fs::copy(&snap_path, &abs).map_err(|e| e.to_string())?;
+        }
+        restored.push(rel.to_string_lossy().to_string());
+    }
+
+    // Сначала самые глубокие пути: файлы внутри созданной папки, затем саму папку.
+    let mut missing: Vec<&String> = markers.missing.iter().collect();
+    missing.sort_by_key(|p| std::cmp::Reverse(Path::new(p.as_str()).components().count()));
+    for m in missing {
+        let abs = project_root.join(m);
+        if skipped(Path::new(m)) || abs.symlink_metadata().is_err() {
+            continue;
+        }
+        remove_path(&abs)?;
+        restored.push(m.clone());
+    }
+
+    Ok(restored)
+}
+
+struct SessionError {
+    error: String,
+    code: &'static str,
+    conflicts: Vec<String>,
+}
+
+impl SessionError {
+    fn new(error: impl Into<String>, code: &'static str) -> Self {
+        SessionError {
+            error: error.into(),
+            code,
+            conflicts: vec![],
+        }
+    }
+
+    fn into_result(self, session_id: &str) -> UndoResult {
+        UndoResult {
+            ok: false,
+            session_id: session_id.to_string(),
+            restored: vec![],
+            conflicts: self.conflicts,
+            file_conflicts: vec![],
+            error: Some(self.error),
+            error_code: Some(self.code.into()),
+        }
+    }
+}
+
+/// Загружает сессию и проверяет, что она принадлежит проекту и имеет статус `expected`.
+fn load_for(
+    history: &Path,
+    project_root: &Path,
+    session_id: &str,
+    expected: SessionStatus,
+) -> Result<(PathBuf, SessionInfo), SessionError> {
+    if !is_valid_session_id(session_id) {
+        return Err(SessionError::new("session_id_invalid", "SESSION_NOT_FOUND"));
+    }
+    let dir = session_dir(history, session_id);
+    let info = read_session_info(&dir).map_err(|e| SessionError::new(e, "SESSION_NOT_FOUND"))?;
+
+    if info.project_root != project_key(project_root) {
+        return Err(SessionError::new(
+            "session_belongs_to_other_project",
+            "SESSION_PROJECT_MISMATCH",
+        ));
+    }
+    if info.status != expected {
+        return Err(match expected {
+            SessionStatus::Applied => SessionError::new("session_already_undone", "UNDO_NOT_AVAILABLE"),
+            SessionStatus::Undone => SessionError::new("session_not_undone", "REDO_NOT_AVAILABLE"),
+        });
+    }
+
+    let conflicts = find_conflicts(history, &info);
+    if !conflicts.is_empty() {
+        return Err(SessionError {
+            error: "later_sessions_touch_same_paths".into(),
+            code: "SESSION_CONFLICT",
+            conflicts,
+        });
+    }
+
+    Ok((dir, info))
+}
+
+/// Пути, изменённые после apply: текущий хэш не совпадает с `after` из манифеста.
+pub fn detect_drift(manifest: &SessionManifest, project_root: &Path) -> Vec<FileConflict> {
+    manifest
+        .files
+        .iter()
+        .filter_map(|f| {
+            let current = file_digest(&project_root.join(&f.path));
+            if current == f.after {
+                return None;
+            }
+            let reason = match (&f.after, &current) {
+                (Some(_), None) => "deleted",
+                (None, Some(_)) => "created",
+                _ => "modified",
+            };
+            Some(FileConflict {
+                path: f.path.clone(),
+                reason: reason.into(),
+                resolution: None,
+            })
+        })
+        .collect()
+}
+
+/// Слияние правок пользователя (после apply) с содержимым до apply.
+/// Возвращает `merged` или `conflict` (записаны маркеры); `None` — слить нельзя
+/// (одной из версий нет или она не текст), файл не тронут.
+fn merge_drifted(dir: &Path, project_root: &Path, rel: &str, session_id: &str) -> Result<Option<&'static str>, String> {
+    let read = |p: PathBuf| fs::read(p).ok().and_then(|b| String::from_utf8(b).ok());
+    let abs = project_root.join(rel);
+    let (base, ours, theirs) = match (
+        read(dir.join(AFTER_DIR).join(rel)),
+        read(abs.clone()),
+        read(dir.join(SNAPSHOT_DIR).join(rel)),
+    ) {
+        (Some(b), Some(o), Some(t)) => (b, o, t),
+        _ => return Ok(None),
+    };
+
+    let outcome = merge3(
+        &base,
+        &ours,
+        &theirs,
+        "current",
+        &format!("before session {}", session_id),
+    );
+    fs::write(&abs, outcome.text).map_err(|e| e.to_string())?;
+    Ok(Some(if outcome.conflicts == 0 { "merged" } else { "conflict" }))
+}
+
+/// Откатывает сессию; текущее состояние её путей сохраняется в `redo/` для повтора.
+/// Файлы, изменённые после apply, обрабатываются согласно `mode`.
+pub fn undo_session(history: &Path, project_root: &Path, session_id: &str, mode: UndoMode) -> UndoResult {
+    let (dir, mut info) = match load_for(history, project_root, session_id, SessionStatus::Applied) {
+        Ok(v) => v,
+        Err(e) => return e.into_result(session_id),
+    };
+
+    // Закоммиченная сессия откатывается средствами git; правки после apply git защитит сам.
+    if let Some(g) = &info.git {
+        if let Err(e) = git::undo(project_root, g) {
+            return SessionError::new(e.error, e.code).into_result(session_id);
+        }
+        info.status = SessionStatus::Undone;
+        info.undone_at = Some(now_rfc3339());
+        if let Err(e) = write_session_info(&dir, &info) {
+            return SessionError::new(e, "SESSION_META_WRITE_FAILED").into_result(session_id);
+        }
+        return UndoResult {
+            ok: true,
+            session_id: session_id.to_string(),
+            restored: info.paths.clone(),
+            conflicts: vec![],
+            file_conflicts: vec![],
+            error: None,
+            error_code: None,
+        };
+    }
+
+    // Старые сессии без манифеста откатываются как раньше, без проверки.
+    let mut drift = read_manifest(&dir)
+        .map(|m| detect_drift(&m, project_root))
+        .unwrap_or_default();
+
+    if mode == UndoMode::Abort && !drift.is_empty() {
+        return UndoResult {
+            ok: false,
+            session_id: session_id.to_string(),
+            restored: vec![],
+            conflicts: vec![],
+            file_conflicts: drift,
+            error: Some("files_changed_after_apply".into()),
+            error_code: Some("UNDO_DRIFT".into()),
+        };
+    }
+
+    let redo_dir = dir.join(REDO_DIR);
+    let _ = fs::remove_dir_all(&redo_dir);
+    let _ = fs::remove_file(markers_path(&redo_dir));
+    if let Err(e) = snapshot_into(&redo_dir, project_root, &redo_targets(&dir, &info)) {
+        return SessionError::new(e, "SNAPSHOT_FAILED").into_result(session_id);
+    }
+
+    let skip: Vec<PathBuf> = match mode {
+        UndoMode::Merge => drift.iter().map(|c| PathBuf::from(&c.path)).collect(),
+        _ => vec![],
+    };
+    let mut restored = match revert_from(&dir.join(SNAPSHOT_DIR), project_root, &skip) {
+        Ok(r) => r,
+        Err(e) => return SessionError::new(e, "UNDO_FAILED").into_result(session_id),
+    };
+
+    for c in &mut drift {
+        let resolution = match mode {
+            UndoMode::Merge => match merge_drifted(&dir, project_root, &c.path, session_id) {
+                Ok(Some(r)) => {
+                    restored.push(c.path.clone());
+                    r
+                }
+                // Не слитый файл остаётся как есть — это конфликт, решать пользователю.
+                Ok(None) => "conflict",
+                Err(e) => return SessionError::new(e, "UNDO_FAILED").into_result(session_id),
+            },
+            _ => "overwritten",
+        };
+        c.resolution = Some(resolution.into());
+    }
+
+    info.status = SessionStatus::Undone;
+    info.undone_at = Some(now_rfc3339());
+    if let Err(e) = write_session_info(&dir, &info) {
+        return SessionError::new(e, "SESSION_META_WRITE_FAILED").into_result(session_id);
+    }
+
+    UndoResult {
+        ok: true,
+        session_id: session_id.to_string(),
+        restored,
+        conflicts: vec![],
+        file_conflicts: drift,
+        error: None,
+        error_code: None,
+    }
+}
+
+/// Для `redo/` целиком копируются пути, которых не было до apply: откат удалит их вместе с содержимым.
+fn redo_targets(dir: &Path, info: &SessionInfo) -> Vec<SnapshotTarget> {
+    let before = read_markers(&dir.join(SNAPSHOT_DIR));
+    info.paths
+        .iter()
+        .map(|p| SnapshotTarget {
+            path: PathBuf::from(p),
+            deep: before.missing.contains(p),
+        })
+        .collect()
+}
+
+/// Повторно применяет откатанную сессию из её `redo/` snapshot'а.
+pub fn redo_session(history: &Path, project_root: &Path, session_id: &str) -> UndoResult {
+    let (dir, mut info) = match load_for(history, project_root, session_id, SessionStatus::Undone) {
+        Ok(v) => v,
+        Err(e) => return e.into_result(session_id),
+    };
+
+    let restored = if let Some(g) = &info.git {
+        match git::redo(project_root, g) {
+            Ok(again) => {
+                info.git = Some(again);
+                info.paths.clone()
+            }
+            Err(e) => return SessionError::new(e.error, e.code).into_result(session_id),
+        }
+    } else {
+        let redo_dir = dir.join(REDO_DIR);
+        let restored = match revert_from(&redo_dir, project_root, &[]) {
+            Ok(r) => r,
+            Err(e) => return SessionError::new(e, "REDO_FAILED").into_result(session_id),
+        };
+        let _ = fs::remove_dir_all(&redo_dir);
+        let _ = fs::remove_file(markers_path(&redo_dir));
+        restored
+    };
+
+    info.status = SessionStatus::Applied;
+    info.undone_at = None;
+    if let Err(e) = write_session_info(&dir, &info) {
+        return SessionError::new(e, "SESSION_META_WRITE_FAILED").into_result(session_id);
+    }
+
+    UndoResult {
+        ok: true,
+        session_id: session_id.to_string(),
+        restored,
+        conflicts: vec![],
+        file_conflicts: vec![],
+        error: None,
+        error_code: None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn session_dirs_created_at_once_get_distinct_ids() {
+        let history = tempfile::tempdir().unwrap();
+        let mut ids: Vec<String> = (0..50)
+            .map(|_| create_session_dir(history.path()).unwrap())
+            .map(|(id, dir)| {
+                assert!(dir.is_dir());
+                id
+            })
+            .collect();
+        assert!(ids.iter().all(|id| is_valid_session_id(id)));
+        ids.sort();
+        ids.dedup();
+        assert_eq!(ids.len(), 50);
+    }
+}
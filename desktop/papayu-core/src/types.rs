@@ -0,0 +1,611 @@
+use serde::{Deserialize, Serialize};
+
+use crate::rules::{RuleMeta, RuleSet};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ActionKind {
+    CreateFile,
+    UpdateFile,
+    DeleteFile,
+    CreateDir,
+    DeleteDir,
+    ApplyPatch,    // content — unified diff
+    SearchReplace, // search → content
+    InsertAfter,   // content вставляется после anchor
+    MoveFile,      // path → to
+    MoveDir,       // path → to
+    CopyFile,      // path → to
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Action {
+    pub id: String,
+    pub title: String,
+    pub description: String,
+    pub kind: ActionKind,
+    pub path: String,
+    pub content: Option<String>, // для create/update и точечных правок
+    #[serde(default)]
+    pub search: Option<String>, // для search_replace
+    #[serde(default)]
+    pub anchor: Option<String>, // для insert_after
+    #[serde(default)]
+    pub to: Option<String>, // для move_file / move_dir / copy_file
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ApplyPayload {
+    pub path: String,
+    pub actions: Vec<Action>,
+    #[serde(default)]
+    pub mode: ApplyMode,
+    /// Пробный прогон: действия выполняются над деревом в памяти, проект не меняется.
+    #[serde(default)]
+    pub dry_run: bool,
+    /// При пробном прогоне — сравнить анализ проекта до и после.
+    #[serde(default)]
+    pub analyze: bool,
+    /// Закоммитить применённые действия в git (только локальный репозиторий в корне проекта).
+    #[serde(default)]
+    pub git: Option<GitApply>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct GitApply {
+    /// Коммит в новой ветке `papayu/<session_id>`, а не в текущей.
+    #[serde(default)]
+    pub branch: bool,
+    /// Разрешить apply при незакоммиченных изменениях (иначе — отказ `GIT_DIRTY`).
+    #[serde(default)]
+    pub allow_dirty: bool,
+}
+
+/// Как поступать с ошибкой одного действия.
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ApplyMode {
+    /// Любая ошибка откатывает весь набор.
+    #[default]
+    AllOrNothing,
+    /// Ошибочные действия пропускаются, остальные применяются.
+    BestEffort,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ActionStatus {
+    Applied,
+    Failed,
+    Skipped,    // не выполнялось: раньше упало зависимое действие или весь набор
+    RolledBack, // было применено, но откатано вместе с набором
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ActionResult {
+    pub action_id: String,
+    pub status: ActionStatus,
+    pub paths: Vec<String>,
+    pub error: Option<String>,
+    pub error_code: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ApplyResult {
+    pub ok: bool,
+    pub session_id: String,
+    pub applied: Vec<String>,
+    pub skipped: Vec<String>,
+    pub error: Option<String>,
+    pub error_code: Option<String>,
+    pub undo_available: bool,
+    #[serde(default)]
+    pub issues: Vec<ValidationIssue>,
+    #[serde(default)]
+    pub results: Vec<ActionResult>,
+    #[serde(default)]
+    pub dry_run: bool,
+    #[serde(default)]
+    pub analysis: Option<DryRunAnalysis>,
+    /// Результаты команд проверки после apply, по порядку (до первой неудачной).
+    #[serde(default)]
+    pub verification: Vec<VerifyResult>,
+    #[serde(default)]
+    pub git: Option<GitApplyResult>,
+}
+
+impl ApplyResult {
+    /// Неудачный apply: только ошибка и код, остальные поля пустые —
+    /// нужное дополняется через `..ApplyResult::failed(…)`.
+    pub fn failed(error: impl Into<String>, code: &str) -> Self {
+        ApplyResult {
+            ok: false,
+            session_id: String::new(),
+            applied: vec![],
+            skipped: vec![],
+            error: Some(error.into()),
+            error_code: Some(code.to_string()),
+            undo_available: false,
+            issues: vec![],
+            results: vec![],
+            dry_run: false,
+            analysis: None,
+            verification: vec![],
+            git: None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GitApplyResult {
+    /// `None` — коммит не создан (см. `error_code`), откат — по снимку.
+    pub commit: Option<String>,
+    pub branch: Option<String>,
+    /// Незакоммиченные изменения до apply (при `allow_dirty`).
+    pub dirty: Vec<String>,
+}
+
+/// Команда проверки проекта после apply (`verify.json`).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VerifyCommand {
+    /// Строка для shell (`sh -c` / `cmd /C`), выполняется в корне проекта.
+    pub run: String,
+    #[serde(default = "default_verify_timeout")]
+    pub timeout_secs: u64,
+}
+
+fn default_verify_timeout() -> u64 {
+    300
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VerifyResult {
+    pub command: String,
+    pub ok: bool,
+    pub exit_code: Option<i32>,
+    pub timed_out: bool,
+    pub duration_ms: u64,
+    /// Конец вывода (stdout и stderr вперемешку); целиком — в `verify.log` сессии.
+    pub output: String,
+    pub truncated: bool,
+    pub error: Option<String>,
+}
+
+/// Как изменится анализ проекта после apply (по пробному прогону).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DryRunAnalysis {
+    pub findings_before: usize,
+    pub findings_after: usize,
+    /// Находки, которых до apply не было.
+    pub new_findings: Vec<Finding>,
+    /// Находки, которые после apply исчезнут.
+    pub resolved_findings: Vec<Finding>,
+    pub new_signals: Vec<ProjectSignal>,
+    pub resolved_signals: Vec<ProjectSignal>,
+    pub risk_before: String,
+    pub risk_after: String,
+    pub stats_after: ReportStats,
+}
+
+/// Проблема, найденная при проверке действий до apply/preview.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ValidationIssue {
+    pub action_id: String,
+    pub path: String,
+    pub code: String, // CONTENT_MISSING | PATH_CONFLICT | ORDER_CYCLE | PATH_* | …
+    pub message: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UndoResult {
+    pub ok: bool,
+    pub session_id: String,
+    pub restored: Vec<String>,
+    pub conflicts: Vec<String>, // session_id более поздних сессий с теми же путями
+    pub file_conflicts: Vec<FileConflict>,
+    pub error: Option<String>,
+    pub error_code: Option<String>,
+}
+
+/// Что делать при undo с файлами, изменёнными после apply.
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum UndoMode {
+    #[default]
+    Abort,
+    Force,
+    Merge,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FileConflict {
+    pub path: String,
+    pub reason: String,             // modified | deleted | created
+    pub resolution: Option<String>, // overwritten | merged | conflict
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SessionStatus {
+    Applied,
+    Undone,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SessionInfo {
+    pub session_id: String,
+    pub project_root: String,
+    pub created_at: String, // RFC 3339
+    pub status: SessionStatus,
+    pub undone_at: Option<String>,
+    pub paths: Vec<String>,
+    pub actions: Vec<Action>,
+    /// Сессия закоммичена в git: undo/redo идут через git, а не через снимок.
+    #[serde(default)]
+    pub git: Option<GitSession>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GitSession {
+    pub commit: String,
+    /// HEAD до коммита сессии.
+    pub base: String,
+    /// Ветка `papayu/<session_id>`, если создавалась.
+    pub branch: Option<String>,
+    pub previous_branch: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FileDigest {
+    pub sha256: String,
+    pub size: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ManifestFile {
+    pub path: String,
+    pub before: Option<FileDigest>, // None — файла не было (или это папка)
+    pub after: Option<FileDigest>,
+}
+
+/// `manifest.json` сессии — неизменяемая запись о том, что сделал apply.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SessionManifest {
+    pub version: u32,
+    pub app_version: String,
+    pub session_id: String,
+    pub project_root: String,
+    pub created_at: String,
+    pub payload: ApplyPayload,
+    pub files: Vec<ManifestFile>,
+}
+
+/// Сессия, apply которой прервался (сбой, выключение питания).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RecoveryInfo {
+    pub session_id: String,
+    pub project_root: String,
+    pub started_at: String,
+    pub snapshot_complete: bool,
+    pub applied: Vec<String>, // id уже выполненных действий
+    pub pending: Vec<String>,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RecoveryStrategy {
+    RollForward,
+    RollBack,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RecoveryResult {
+    pub ok: bool,
+    pub session_id: String,
+    pub applied: Vec<String>,
+    pub restored: Vec<String>,
+    pub error: Option<String>,
+    pub error_code: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HistoryResult {
+    pub ok: bool,
+    pub sessions: Vec<SessionInfo>,
+    pub error: Option<String>,
+    pub error_code: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DiffItem {
+    pub path: String,
+    pub kind: String, // "create" | "update" | "delete" | "mkdir" | "rmdir" | "patch" | "replace" | "insert" | "move" | "movedir" | "copy"
+    pub before: Option<String>, // только для файлов до 200 КБ
+    pub after: Option<String>,
+    pub summary: String,
+    #[serde(default)]
+    pub to: Option<String>, // для move / movedir / copy
+    #[serde(default)]
+    pub hunks: Vec<DiffHunk>,
+    #[serde(default)]
+    pub added: usize,
+    #[serde(default)]
+    pub removed: usize,
+    #[serde(default)]
+    pub binary: bool,
+    #[serde(default)]
+    pub truncated: bool, // before/after или ханки отданы не полностью
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DiffHunk {
+    pub old_start: usize, // номера строк с 1
+    pub old_lines: usize,
+    pub new_start: usize,
+    pub new_lines: usize,
+    pub lines: Vec<DiffLine>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DiffLine {
+    pub kind: String, // context | add | remove
+    pub old_line: Option<usize>,
+    pub new_line: Option<usize>,
+    pub text: String,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PreviewSummary {
+    pub files_created: usize,
+    pub files_updated: usize,
+    pub files_deleted: usize,
+    pub moved: usize, // файлы и папки
+    pub copied: usize,
+    pub dirs_created: usize,
+    pub dirs_deleted: usize,
+    pub added: usize,
+    pub removed: usize,
+    pub text: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PreviewResult {
+    pub ok: bool,
+    pub diffs: Vec<DiffItem>,
+    #[serde(default)]
+    pub summary: PreviewSummary,
+    pub warnings: Vec<String>,
+    #[serde(default)]
+    pub issues: Vec<ValidationIssue>,
+    pub error: Option<String>,
+    pub error_code: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProjectContext {
+    pub stack: Vec<String>,
+    pub domain: String,
+    pub maturity: String,   // Prototype | MVP | Production-like
+    pub complexity: String, // Low | Medium | High
+    pub risk_level: String, // Low | Medium | High
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LlmContext {
+    pub concise_summary: String,
+    pub key_risks: Vec<String>,
+    pub top_recommendations: Vec<String>,
+    pub signals: Vec<ProjectSignal>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReportStats {
+    pub file_count: u64,
+    pub dir_count: u64,
+    pub total_size_bytes: u64,
+    pub top_extensions: Vec<(String, u64)>,
+    pub max_depth: u64,
+}
+
+/// Находка анализа. Поля после `details` необязательны: у находок по проекту в целом
+/// нет файла, у находок по файлу целиком — строк. Строки и колонки считаются с 1,
+/// колонки — в символах; `end_column` указывает на символ после совпадения.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Finding {
+    pub severity: String, // info|warn|high
+    pub title: String,
+    pub details: String,
+    /// Стабильный id правила: `secrets/aws-access-key`, `structure/env-file`.
+    #[serde(default)]
+    pub rule_id: Option<String>,
+    #[serde(default)]
+    pub category: Option<String>, // security|quality|structure|analysis
+    /// Путь от корня проекта через `/`.
+    #[serde(default)]
+    pub path: Option<String>,
+    #[serde(default)]
+    pub start_line: Option<u32>,
+    #[serde(default)]
+    pub start_column: Option<u32>,
+    #[serde(default)]
+    pub end_line: Option<u32>,
+    #[serde(default)]
+    pub end_column: Option<u32>,
+    /// Строка с совпадением, не длиннее 120 символов; значения секретов скрыты.
+    #[serde(default)]
+    pub snippet: Option<String>,
+    /// Как исправить — у правил пользователя (`rules.rs`).
+    #[serde(default)]
+    pub fix_hint: Option<String>,
+}
+
+impl Finding {
+    /// Та же проблема в том же месте кода, без учёта строк: правки выше по файлу
+    /// не делают находку новой.
+    pub fn same_issue(&self, other: &Finding) -> bool {
+        match (&self.rule_id, &other.rule_id) {
+            (Some(a), Some(b)) => a == b && self.path == other.path && self.snippet == other.snippet,
+            _ => self == other,
+        }
+    }
+
+    /// Находки из `xs`, которым нет пары в `ys` (каждая находка из `ys` — пара одной).
+    pub fn unmatched(xs: &[Finding], ys: &[Finding]) -> Vec<Finding> {
+        let mut left: Vec<&Finding> = ys.iter().collect();
+        xs.iter()
+            .filter(|x| match left.iter().position(|y| x.same_issue(y)) {
+                Some(i) => {
+                    left.swap_remove(i);
+                    false
+                }
+                None => true,
+            })
+            .cloned()
+            .collect()
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Recommendation {
+    pub title: String,
+    pub details: String,
+    pub priority: String, // high|medium|low
+    pub effort: String,   // low|medium|high
+    pub impact: String,   // low|medium|high
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProjectStructure {
+    pub project_type: String,
+    pub architecture: String,
+    pub structure_notes: Vec<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ProjectSignal {
+    pub category: String, // security|quality|structure|history
+    pub level: String,    // info|warn|high
+    pub message: String,
+}
+
+/// Необязательные проходы `analyze_project`.
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct AnalyzeOptions {
+    /// Анализ истории git: частота изменений, авторы, крупные файлы и секреты в прошлых коммитах.
+    #[serde(default)]
+    pub git_history: bool,
+    /// Профиль вместо указанного в `.papayu.toml`.
+    #[serde(default)]
+    pub profile: Option<AnalysisProfile>,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AnalysisProfile {
+    Quick,
+    #[default]
+    Standard,
+    Exhaustive,
+}
+
+/// Лимиты анализа и контекста LLM; значения — из профиля, поверх — из `.papayu.toml`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AnalysisLimits {
+    /// Обход: файлов и секунд на весь анализ.
+    pub max_files: u64,
+    pub max_duration_secs: u64,
+    /// Глубокий анализ: файлов, размер файла, глубина папки.
+    pub deep_max_files: usize,
+    pub deep_max_file_bytes: u64,
+    pub deep_max_depth: u32,
+    /// Контекст LLM (`collect_project_context`): всего байт, размер файла, файлов кода, глубина папки.
+    pub context_max_bytes: usize,
+    pub context_max_file_bytes: u64,
+    pub context_max_files: usize,
+    pub context_max_depth: u32,
+}
+
+/// Настройки, с которыми прошёл анализ.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AnalysisConfig {
+    pub profile: AnalysisProfile,
+    /// Путь к `.papayu.toml`, если он есть.
+    pub source: Option<String>,
+    pub limits: AnalysisLimits,
+    /// Включённые наборы правил глубокого анализа: `secrets`, `security`, `quality`.
+    pub rules: Vec<String>,
+    /// Исключения в синтаксисе gitignore.
+    pub exclude: Vec<String>,
+    pub git_history: bool,
+    /// Ошибки в `.papayu.toml`; ошибочные значения не применены.
+    pub warnings: Vec<String>,
+    /// Правила пользователя из `app_config_dir/rules/` и `.papayu/rules/` (`rules.rs`).
+    #[serde(default)]
+    pub custom_rules: Vec<RuleMeta>,
+    /// Ошибки в наборах правил; ошибочные правила не применены.
+    #[serde(default)]
+    pub rule_warnings: Vec<String>,
+    /// Те же правила, готовые к проверке.
+    #[serde(skip)]
+    pub rule_set: RuleSet,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AnalyzeReport {
+    pub path: String,
+    pub narrative: String,
+    pub stats: ReportStats,
+    pub structure: ProjectStructure,
+    pub signals: Vec<ProjectSignal>,
+    pub findings: Vec<Finding>,
+    pub recommendations: Vec<Recommendation>,
+    pub actions: Vec<Action>,
+    pub project_context: ProjectContext,
+    pub report_md: String,
+    pub llm_context: LlmContext,
+    /// Что изменилось с прошлого анализа этого проекта; `None` — анализ первый.
+    pub changes: Option<AnalysisDelta>,
+    /// Анализ остановлен раньше времени — отчёт по тому, что успели разобрать.
+    pub stopped: Option<AnalysisStop>,
+    pub config: AnalysisConfig,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AnalysisStop {
+    FileLimit,
+    Timeout,
+    Cancelled,
+}
+
+/// Ход анализа (`analysis_progress`).
+#[derive(Debug, Clone, Serialize)]
+pub struct AnalyzeProgress {
+    pub job_id: String,
+    pub phase: String, // walk|structure|deep|git_history|report
+    pub message: String,
+    /// Файлов обработано в текущей фазе.
+    pub files_scanned: u64,
+    /// Сколько всего файлов ожидается в фазе; `None` — неизвестно.
+    pub total_estimate: Option<u64>,
+    pub current_path: Option<String>,
+}
+
+/// Разница с прошлым анализом проекта на диске (`analysis_store.rs`).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AnalysisDelta {
+    /// Время прошлого анализа.
+    pub since: String,
+    pub added: Vec<String>,
+    pub removed: Vec<String>,
+    pub modified: Vec<String>,
+    /// Списки путей обрезаны.
+    pub truncated: bool,
+    /// Находки глубокого анализа в новых и изменённых файлах, которых не было.
+    pub new_findings: Vec<Finding>,
+    /// Находки глубокого анализа, которые ушли вместе с изменёнными и удалёнными файлами.
+    pub resolved_findings: Vec<Finding>,
+    /// Файлов глубокого анализа разобрано заново / взято из индекса.
+    pub rescanned: u32,
+    pub reused: u32,
+}
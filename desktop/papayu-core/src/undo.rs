@@ -0,0 +1,121 @@
+//! Откат и повторное применение сессий apply.
+
+use std::path::PathBuf;
+
+use serde::{Deserialize, Serialize};
+
+use crate::app_dirs::AppDirs;
+use crate::file_index;
+use crate::progress::ProgressSink;
+use crate::session;
+use crate::types::{UndoMode, UndoResult};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SessionPayload {
+    pub path: String,
+    pub session_id: String,
+    pub mode: Option<UndoMode>, // только для undo_session
+}
+
+fn resolve(dirs: &AppDirs, payload: &SessionPayload) -> Result<(PathBuf, PathBuf), (String, &'static str)> {
+    let project_root = PathBuf::from(&payload.path);
+    if !project_root.exists() || !project_root.is_dir() {
+        return Err(("path_invalid".into(), "PATH_INVALID"));
+    }
+    let history = session::history_dir(dirs).map_err(|e| (e, "APP_DATA_DIR"))?;
+    Ok((project_root, history))
+}
+
+fn failed(session_id: &str, error: String, code: &str) -> UndoResult {
+    UndoResult {
+        ok: false,
+        session_id: session_id.to_string(),
+        restored: vec![],
+        conflicts: vec![],
+        file_conflicts: vec![],
+        error: Some(error),
+        error_code: Some(code.into()),
+    }
+}
+
+/// Откат сессии `payload.session_id`.
+pub fn undo(sink: &dyn ProgressSink, dirs: &AppDirs, payload: SessionPayload) -> UndoResult {
+    let (project_root, history) = match resolve(dirs, &payload) {
+        Ok(v) => v,
+        Err((e, code)) => return failed(&payload.session_id, e, code),
+    };
+
+    sink.message("Откатываю сессию…");
+    file_index::forget();
+
+    session::undo_session(
+        &history,
+        &project_root,
+        &payload.session_id,
+        payload.mode.unwrap_or_default(),
+    )
+}
+
+/// Повторное применение откатанной сессии.
+pub fn redo(sink: &dyn ProgressSink, dirs: &AppDirs, payload: SessionPayload) -> UndoResult {
+    let (project_root, history) = match resolve(dirs, &payload) {
+        Ok(v) => v,
+        Err((e, code)) => return failed(&payload.session_id, e, code),
+    };
+
+    sink.message("Повторно применяю сессию…");
+    file_index::forget();
+
+    session::redo_session(&history, &project_root, &payload.session_id)
+}
+
+/// Откат последней применённой сессии проекта.
+pub fn undo_latest(sink: &dyn ProgressSink, dirs: &AppDirs, path: &str, mode: Option<UndoMode>) -> UndoResult {
+    let project_root = PathBuf::from(path);
+    if !project_root.exists() || !project_root.is_dir() {
+        return UndoResult {
+            ok: false,
+            session_id: String::new(),
+            restored: vec![],
+            conflicts: vec![],
+            file_conflicts: vec![],
+            error: Some("path_invalid".into()),
+            error_code: Some("PATH_INVALID".into()),
+        };
+    }
+
+    let history = match session::history_dir(dirs) {
+        Ok(d) => d,
+        Err(e) => {
+            return UndoResult {
+                ok: false,
+                session_id: String::new(),
+                restored: vec![],
+                conflicts: vec![],
+                file_conflicts: vec![],
+                error: Some(e),
+                error_code: Some("APP_DATA_DIR".into()),
+            };
+        }
+    };
+
+    let session_id = match session::latest_applied(&history, &project_root) {
+        Some(s) => s.session_id,
+        None => {
+            return UndoResult {
+                ok: false,
+                session_id: String::new(),
+                restored: vec![],
+                conflicts: vec![],
+                file_conflicts: vec![],
+                error: Some("no_undo_available".into()),
+                error_code: Some("UNDO_NOT_AVAILABLE".into()),
+            };
+        }
+    };
+
+    sink.message("Откатываю изменения…");
+    file_index::forget();
+
+    session::undo_session(&history, &project_root, &session_id, mode.unwrap_or_default())
+}
@@ -0,0 +1,325 @@
+//! Проверка набора действий до записи на диск (общая для preview и apply):
+//! обязательные поля, политика путей, повторы и конфликты по путям, наличие
+//! файлов с учётом предыдущих действий. Действия упорядочиваются по
+//! зависимостям (папка до файлов в ней, создание до правки, правка до переноса).
+
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+
+use crate::path_policy::PathPolicy;
+use crate::types::{Action, ActionKind, ValidationIssue};
+
+/// Какие пути действие создаёт, читает и убирает.
+#[derive(Default)]
+struct Roles {
+    produces: Vec<PathBuf>,
+    consumes: Vec<PathBuf>,
+    removes: Vec<PathBuf>,
+}
+
+fn issue(a: &Action, code: &str, message: String) -> ValidationIssue {
+    ValidationIssue {
+        action_id: a.id.clone(),
+        path: a.path.clone(),
+        code: code.to_string(),
+        message,
+    }
+}
+
+fn roles(a: &Action, src: &Path, dst: Option<&Path>) -> Roles {
+    let src = src.to_path_buf();
+    let dst: Vec<PathBuf> = dst.map(Path::to_path_buf).into_iter().collect();
+    match a.kind {
+        ActionKind::CreateFile | ActionKind::UpdateFile | ActionKind::CreateDir => Roles {
+            produces: vec![src],
+            ..Default::default()
+        },
+        ActionKind::ApplyPatch | ActionKind::SearchReplace | ActionKind::InsertAfter => Roles {
+            consumes: vec![src],
+            ..Default::default()
+        },
+        ActionKind::DeleteFile | ActionKind::DeleteDir => Roles {
+            removes: vec![src],
+            ..Default::default()
+        },
+        ActionKind::MoveFile | ActionKind::MoveDir => Roles {
+            produces: dst,
+            consumes: vec![src.clone()],
+            removes: vec![src],
+        },
+        ActionKind::CopyFile => Roles {
+            produces: dst,
+            consumes: vec![src],
+            removes: vec![],
+        },
+    }
+}
+
+/// Обязательные поля для каждого вида действия.
+fn check_fields(a: &Action) -> Option<ValidationIssue> {
+    let missing = |field: &str, code: &str| Some(issue(a, code, format!("{}: не задано поле {}", a.path, field)));
+    match a.kind {
+        ActionKind::CreateFile
+        | ActionKind::UpdateFile
+        | ActionKind::ApplyPatch
+        | ActionKind::SearchReplace
+        | ActionKind::InsertAfter
+            if a.content.is_none() =>
+        {
+            missing("content", "CONTENT_MISSING")
+        }
+        ActionKind::SearchReplace if a.search.as_deref().unwrap_or("").is_empty() => missing("search", "SEARCH_MISSING"),
+        ActionKind::InsertAfter if a.anchor.as_deref().unwrap_or("").trim().is_empty() => {
+            missing("anchor", "ANCHOR_MISSING")
+        }
+        ActionKind::MoveFile | ActionKind::MoveDir | ActionKind::CopyFile if a.to.is_none() => {
+            missing("to", "DESTINATION_MISSING")
+        }
+        _ => None,
+    }
+}
+
+fn same_action(a: &Action, b: &Action) -> bool {
+    std::mem::discriminant(&a.kind) == std::mem::discriminant(&b.kind)
+        && a.path == b.path
+        && a.to == b.to
+        && a.content == b.content
+        && a.search == b.search
+        && a.anchor == b.anchor
+}
+
+/// Состояние путей после уже проверенных действий поверх диска.
+#[derive(Default)]
+struct Overlay {
+    entries: HashMap<PathBuf, bool>,
+    moved: Vec<(PathBuf, PathBuf)>, // (куда, откуда) для перенесённых папок
+}
+
+impl Overlay {
+    fn exists(&self, p: &Path) -> bool {
+        for anc in p.ancestors() {
+            if let Some(&present) = self.entries.get(anc) {
+                if anc == p || !present {
+                    return present;
+                }
+                // Путь внутри перенесённой папки ищется по старому месту.
+                if let Some((dst, src)) = self.moved.iter().rev().find(|(d, _)| d == anc) {
+                    if let Ok(rest) = p.strip_prefix(dst) {
+                        return self.exists(&src.join(rest));
+                    }
+                }
+            }
+        }
+        p.symlink_metadata().is_ok()
+    }
+
+    fn set(&mut self, p: &Path, present: bool) {
+        self.entries.insert(p.to_path_buf(), present);
+    }
+}
+
+/// Стабильная топологическая сортировка: из готовых берётся самое раннее по исходному порядку.
+fn order(n: usize, edges: &HashSet<(usize, usize)>) -> (Vec<usize>, Vec<usize>) {
+    let mut indegree = vec![0usize; n];
+    for &(_, v) in edges {
+        indegree[v] += 1;
+    }
+    let mut done = vec![false; n];
+    let mut out = vec![];
+    while let Some(u) = (0..n).find(|&i| !done[i] && indegree[i] == 0) {
+        done[u] = true;
+        out.push(u);
+        for &(a, b) in edges {
+            if a == u {
+                indegree[b] -= 1;
+            }
+        }
+    }
+    let stuck = (0..n).filter(|&i| !done[i]).collect();
+    (out, stuck)
+}
+
+/// Проверяет действия и возвращает их в порядке выполнения либо все найденные проблемы.
+pub fn validate(project_root: &Path, policy: &PathPolicy, actions: &[Action]) -> Result<Vec<Action>, Vec<ValidationIssue>> {
+    let mut issues: Vec<ValidationIssue> = vec![];
+    let mut seen_ids: HashSet<&str> = HashSet::new();
+    // Действия, прошедшие проверку полей и путей: (индекс, роли).
+    let mut valid: Vec<(usize, Roles)> = vec![];
+
+    for (i, a) in actions.iter().enumerate() {
+        if !seen_ids.insert(a.id.as_str()) {
+            issues.push(issue(a, "DUPLICATE_ACTION_ID", format!("id {} встречается несколько раз", a.id)));
+            continue;
+        }
+        if let Some(dup) = actions[..i].iter().find(|b| same_action(a, b)) {
+            issues.push(issue(a, "DUPLICATE_ACTION", format!("{}: повторяет действие {}", a.path, dup.id)));
+            continue;
+        }
+        if let Some(problem) = check_fields(a) {
+            issues.push(problem);
+            continue;
+        }
+        let src = policy.resolve(project_root, &a.path);
+        let dst = a.to.as_deref().map(|to| policy.resolve(project_root, to)).transpose();
+        match (src, dst) {
+            (Ok(src), Ok(Some(dst))) if dst.starts_with(&src) => issues.push(issue(
+                a,
+                "DESTINATION_INSIDE_SOURCE",
+                format!("{}: нельзя перенести или скопировать внутрь себя", a.path),
+            )),
+            (Ok(src), Ok(dst)) => valid.push((i, roles(a, &src, dst.as_deref()))),
+            (Err(e), _) | (_, Err(e)) => issues.push(issue(a, e.code, e.error)),
+        }
+    }
+
+    // Конфликты: один путь создают или удаляют два действия; удаление и создание одного пути.
+    let rel = |p: &Path| p.strip_prefix(project_root).unwrap_or(p).display().to_string();
+    let mut producers: HashMap<&Path, usize> = HashMap::new();
+    let mut removers: HashMap<&Path, usize> = HashMap::new();
+    let mut conflicted: HashSet<usize> = HashSet::new();
+    for (i, r) in &valid {
+        let a = &actions[*i];
+        for p in &r.produces {
+            if let Some(&j) = producers.get(p.as_path()) {
+                issues.push(issue(a, "PATH_CONFLICT", format!("{}: путь уже создаёт или перезаписывает действие {}", rel(p), actions[j].id)));
+                conflicted.insert(*i);
+            } else {
+                producers.insert(p, *i);
+            }
+        }
+        for p in &r.removes {
+            if let Some(&j) = removers.get(p.as_path()) {
+                issues.push(issue(a, "PATH_CONFLICT", format!("{}: путь уже удаляет или переносит действие {}", rel(p), actions[j].id)));
+                conflicted.insert(*i);
+            } else {
+                removers.insert(p, *i);
+            }
+        }
+    }
+    for (i, r) in &valid {
+        let a = &actions[*i];
+        if !matches!(a.kind, ActionKind::DeleteFile | ActionKind::DeleteDir) {
+            continue;
+        }
+        for p in &r.removes {
+            if let Some(&j) = producers.get(p.as_path()) {
+                issues.push(issue(a, "PATH_CONFLICT", format!("{}: путь одновременно удаляется и создаётся действием {}", rel(p), actions[j].id)));
+                conflicted.insert(*i);
+            }
+        }
+    }
+    let valid: Vec<(usize, Roles)> = valid.into_iter().filter(|(i, _)| !conflicted.contains(i)).collect();
+
+    // Зависимости (u, v): u выполняется раньше v. Индексы — позиции в `valid`.
+    let mut edges: HashSet<(usize, usize)> = HashSet::new();
+    for (u, (_, ru)) in valid.iter().enumerate() {
+        for (v, (_, rv)) in valid.iter().enumerate() {
+            if u == v {
+                continue;
+            }
+            let v_paths = || rv.produces.iter().chain(&rv.consumes);
+            // Создание пути, которого ещё нет, — до его чтения и до всего, что внутри него.
+            // Существующий путь действия правят в порядке запроса: `update_file` после
+            // `apply_patch` того же файла не должен оказаться раньше него.
+            let makes = ru.produces.iter().any(|p| {
+                p.symlink_metadata().is_err()
+                    && (rv.consumes.contains(p) || v_paths().any(|q| q != p && q.starts_with(p)))
+            });
+            // Чтение и правка пути — до его удаления или переноса (в том числе родительской папки).
+            let before_removal = rv.removes.iter().any(|d| {
+                ru.consumes.iter().chain(&ru.produces).any(|q| q.starts_with(d)) && !ru.removes.contains(d)
+            });
+            if makes || before_removal {
+                edges.insert((u, v));
+            }
+        }
+    }
+
+    let (sorted, stuck) = order(valid.len(), &edges);
+    for k in stuck {
+        let a = &actions[valid[k].0];
+        issues.push(issue(a, "ORDER_CYCLE", format!("{}: действия зависят друг от друга по кругу", a.path)));
+    }
+
+    // Наличие путей на момент выполнения каждого действия.
+    let mut overlay = Overlay::default();
+    for &k in &sorted {
+        let (i, r) = &valid[k];
+        let a = &actions[*i];
+        match a.kind {
+            ActionKind::ApplyPatch | ActionKind::SearchReplace | ActionKind::InsertAfter
+                if !overlay.exists(&r.consumes[0]) =>
+            {
+                issues.push(issue(a, "TARGET_MISSING", format!("{}: файла нет — править нечего", a.path)));
+            }
+            ActionKind::MoveFile | ActionKind::MoveDir | ActionKind::CopyFile => {
+                if !overlay.exists(&r.consumes[0]) {
+                    issues.push(issue(a, "SOURCE_MISSING", format!("{}: источника нет", a.path)));
+                }
+                if r.produces.iter().any(|p| overlay.exists(p)) {
+                    let to = a.to.clone().unwrap_or_default();
+                    issues.push(issue(a, "DESTINATION_EXISTS", format!("{}: путь назначения уже существует", to)));
+                }
+            }
+            _ => {}
+        }
+        for p in &r.removes {
+            overlay.set(p, false);
+        }
+        for p in &r.produces {
+            overlay.set(p, true);
+        }
+        if let (ActionKind::MoveDir, Some(dst)) = (&a.kind, r.produces.first()) {
+            overlay.moved.push((dst.clone(), r.consumes[0].clone()));
+        }
+    }
+
+    if !issues.is_empty() {
+        return Err(issues);
+    }
+    Ok(sorted.into_iter().map(|k| actions[valid[k].0].clone()).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn action(id: &str, kind: ActionKind, path: &str) -> Action {
+        Action {
+            id: id.to_string(),
+            title: id.to_string(),
+            description: String::new(),
+            kind,
+            path: path.to_string(),
+            content: Some("x".to_string()),
+            search: Some("a".to_string()),
+            anchor: None,
+            to: None,
+        }
+    }
+
+    fn ids(actions: Vec<Action>) -> Vec<String> {
+        actions.into_iter().map(|a| a.id).collect()
+    }
+
+    #[test]
+    fn existing_file_keeps_requested_order_new_file_is_created_first() {
+        let root = tempfile::tempdir().unwrap();
+        std::fs::write(root.path().join("a.txt"), "a\n").unwrap();
+        let policy = PathPolicy::default();
+
+        let existing = vec![
+            action("patch", ActionKind::SearchReplace, "a.txt"),
+            action("update", ActionKind::UpdateFile, "a.txt"),
+        ];
+        assert_eq!(ids(validate(root.path(), &policy, &existing).unwrap()), ["patch", "update"]);
+
+        let new = vec![
+            action("patch", ActionKind::SearchReplace, "b.txt"),
+            action("create", ActionKind::CreateFile, "b.txt"),
+            action("inner", ActionKind::CreateFile, "dir/c.txt"),
+            action("dir", ActionKind::CreateDir, "dir"),
+        ];
+        assert_eq!(ids(validate(root.path(), &policy, &new).unwrap()), ["create", "patch", "dir", "inner"]);
+    }
+}
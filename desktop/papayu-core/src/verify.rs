@@ -0,0 +1,267 @@
+//! Проверка проекта после apply: команды сборки/тестов (`cargo check`, `npm test`, …)
+//! из `app_config_dir/verify.json`, у каждого проекта свои. Команда запускается
+//! через shell в корне проекта с таймаутом, вывод построчно уходит в progress
+//! и сохраняется в `verify.log` сессии.
+
+use std::collections::HashMap;
+use std::fs;
+use std::io::{BufRead, BufReader, Read};
+use std::path::Path;
+use std::process::{Child, Command, Stdio};
+use std::sync::mpsc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use serde::{Deserialize, Serialize};
+use crate::app_dirs::AppDirs;
+use crate::session;
+use crate::types::{VerifyCommand, VerifyResult};
+
+const VERIFY_FILE: &str = "verify.json";
+pub const LOG_FILE: &str = "verify.log";
+/// Сколько последних байт вывода команды возвращается в `ApplyResult` (в `verify.log` — всё).
+const MAX_RESULT_OUTPUT: usize = 64 * 1024;
+
+/// Команды по проектам; ключ — канонический путь корня (`session::project_key`).
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct VerifyConfig {
+    #[serde(default)]
+    projects: HashMap<String, Vec<VerifyCommand>>,
+}
+
+fn config_path(dirs: &AppDirs) -> Result<std::path::PathBuf, String> {
+    dirs.config_dir().map(|d| d.join(VERIFY_FILE))
+}
+
+fn read_config(dirs: &AppDirs) -> VerifyConfig {
+    let path = match config_path(dirs) {
+        Ok(p) => p,
+        Err(_) => return VerifyConfig::default(),
+    };
+    match fs::read_to_string(&path) {
+        Ok(s) => serde_json::from_str(&s).unwrap_or_else(|e| {
+            log::warn!("{}: {}, проверки после apply отключены", path.display(), e);
+            VerifyConfig::default()
+        }),
+        Err(_) => VerifyConfig::default(),
+    }
+}
+
+/// Команды проверки проекта; если не настроены — пустой список.
+pub fn load(dirs: &AppDirs, project_root: &Path) -> Vec<VerifyCommand> {
+    read_config(dirs)
+        .projects
+        .remove(&session::project_key(project_root))
+        .unwrap_or_default()
+}
+
+pub fn save(dirs: &AppDirs, project_root: &Path, commands: Vec<VerifyCommand>) -> Result<(), String> {
+    let path = config_path(dirs)?;
+    let mut config = read_config(dirs);
+    let key = session::project_key(project_root);
+    if commands.is_empty() {
+        config.projects.remove(&key);
+    } else {
+        config.projects.insert(key, commands);
+    }
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir).map_err(|e| e.to_string())?;
+    }
+    let json = serde_json::to_string_pretty(&config).map_err(|e| e.to_string())?;
+    crate::actions::write_atomic(&path, json.as_bytes())
+}
+
+fn shell(line: &str) -> Command {
+    #[cfg(unix)]
+    {
+        use std::os::unix::process::CommandExt;
+        let mut c = Command::new("sh");
+        c.arg("-c").arg(line);
+        // Своя группа процессов: по таймауту убивается вся сборка, а не только shell.
+        c.process_group(0);
+        c
+    }
+    #[cfg(not(unix))]
+    {
+        let mut c = Command::new("cmd");
+        c.arg("/C").arg(line);
+        c
+    }
+}
+
+fn kill_tree(child: &mut Child) {
+    #[cfg(unix)]
+    // SAFETY: отрицательный pid — сигнал группе, созданной для этой команды.
+    unsafe {
+        libc::kill(-(child.id() as libc::pid_t), libc::SIGKILL);
+    }
+    let _ = child.kill();
+}
+
+fn pipe_lines(stream: impl Read + Send + 'static, tx: mpsc::Sender<String>) -> thread::JoinHandle<()> {
+    thread::spawn(move || {
+        // Вывод не обязательно UTF-8: строка читается байтами, иначе поток встанет на первой ошибке.
+        let mut reader = BufReader::new(stream);
+        let mut buf = vec![];
+        loop {
+            buf.clear();
+            match reader.read_until(b'\n', &mut buf) {
+                Ok(0) | Err(_) => break,
+                Ok(_) => {
+                    let line = String::from_utf8_lossy(&buf).trim_end_matches(['\n', '\r']).to_string();
+                    if tx.send(line).is_err() {
+                        break;
+                    }
+                }
+            }
+        }
+    })
+}
+
+/// Последние `max` байт текста, по границе символа.
+fn tail(text: &str, max: usize) -> (String, bool) {
+    if text.len() <= max {
+        return (text.to_string(), false);
+    }
+    let mut start = text.len() - max;
+    while !text.is_char_boundary(start) {
+        start += 1;
+    }
+    (text[start..].to_string(), true)
+}
+
+/// Запускает команду в корне проекта и ждёт её не дольше `timeout_secs`.
+/// Каждая строка stdout/stderr передаётся в `progress`.
+pub fn run(cmd: &VerifyCommand, project_root: &Path, progress: &dyn Fn(&str)) -> (VerifyResult, String) {
+    let started = Instant::now();
+    let deadline = started + Duration::from_secs(cmd.timeout_secs);
+    let mut output = String::new();
+
+    let spawned = shell(&cmd.run)
+        .current_dir(project_root)
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn();
+    let mut child = match spawned {
+        Ok(c) => c,
+        Err(e) => {
+            let result = VerifyResult {
+                command: cmd.run.clone(),
+                ok: false,
+                exit_code: None,
+                timed_out: false,
+                duration_ms: 0,
+                output: String::new(),
+                truncated: false,
+                error: Some(format!("spawn_failed: {}", e)),
+            };
+            return (result, String::new());
+        }
+    };
+
+    let (tx, rx) = mpsc::channel();
+    let mut readers = vec![];
+    if let Some(out) = child.stdout.take() {
+        readers.push(pipe_lines(out, tx.clone()));
+    }
+    if let Some(err) = child.stderr.take() {
+        readers.push(pipe_lines(err, tx.clone()));
+    }
+    drop(tx);
+
+    let mut timed_out = false;
+    loop {
+        let left = deadline.saturating_duration_since(Instant::now());
+        if left.is_zero() {
+            timed_out = true;
+            kill_tree(&mut child);
+            break;
+        }
+        match rx.recv_timeout(left.min(Duration::from_millis(200))) {
+            Ok(line) => {
+                progress(&line);
+                output.push_str(&line);
+                output.push('\n');
+            }
+            Err(mpsc::RecvTimeoutError::Timeout) => {}
+            // Оба потока закрыты — команда завершилась (или закрыла вывод).
+            Err(mpsc::RecvTimeoutError::Disconnected) => break,
+        }
+    }
+
+    let status = loop {
+        match child.try_wait() {
+            Ok(Some(s)) => break Some(s),
+            Ok(None) if Instant::now() < deadline => thread::sleep(Duration::from_millis(50)),
+            Ok(None) => {
+                timed_out = true;
+                kill_tree(&mut child);
+                break child.wait().ok();
+            }
+            Err(_) => break None,
+        }
+    };
+    // После таймаута потоки чтения не ждём: вывод мог унаследовать кто-то вне группы.
+    if !timed_out {
+        for r in readers {
+            let _ = r.join();
+        }
+    }
+    output.extend(rx.try_iter().map(|l| l + "\n"));
+
+    let exit_code = status.and_then(|s| s.code());
+    let ok = !timed_out && status.is_some_and(|s| s.success());
+    let (tail_output, truncated) = tail(&output, MAX_RESULT_OUTPUT);
+    let error = if timed_out {
+        Some(format!("verify_timeout: {} s", cmd.timeout_secs))
+    } else if !ok {
+        Some(match exit_code {
+            Some(code) => format!("verify_failed: exit code {}", code),
+            None => "verify_failed: killed by signal".to_string(),
+        })
+    } else {
+        None
+    };
+    let result = VerifyResult {
+        command: cmd.run.clone(),
+        ok,
+        exit_code,
+        timed_out,
+        duration_ms: started.elapsed().as_millis() as u64,
+        output: tail_output,
+        truncated,
+        error,
+    };
+    (result, output)
+}
+
+/// Выполняет команды по порядку до первой неудачной; полный вывод пишется в `verify.log` сессии.
+pub fn run_all(
+    commands: &[VerifyCommand],
+    project_root: &Path,
+    session_dir: &Path,
+    progress: &dyn Fn(&str),
+) -> Vec<VerifyResult> {
+    let mut results = vec![];
+    let mut text = String::new();
+    for cmd in commands {
+        progress(&format!("$ {}", cmd.run));
+        let (result, output) = run(cmd, project_root, progress);
+        text.push_str(&format!("$ {}\n{}", cmd.run, output));
+        text.push_str(&format!(
+            "# {} ({} мс)\n\n",
+            result.error.as_deref().unwrap_or("ok"),
+            result.duration_ms
+        ));
+        let failed = !result.ok;
+        results.push(result);
+        if failed {
+            break;
+        }
+    }
+    if let Err(e) = fs::write(session_dir.join(LOG_FILE), text) {
+        log::warn!("{}: {}", LOG_FILE, e);
+    }
+    results
+}
@@ -0,0 +1,135 @@
+//! Общий обход проекта для анализа, глубокого анализа и контекста LLM — чтобы все
+//! три видели одни и те же файлы. Пропускаются symlink'и, стандартные папки
+//! зависимостей и сборки (`EXCLUDED_DIRS`) и всё, что исключено в `.gitignore`
+//! (в любой папке), `.git/info/exclude`, `.papayuignore` и `exclude` в
+//! `.papayu.toml`. Чтение идёт через `ProjectFs`, поэтому обход работает и для
+//! дерева пробного apply. Подпапки обходятся параллельно; лимиты, отмена и
+//! прогресс — через задание анализа.
+
+use std::borrow::Cow;
+use std::path::Path;
+
+use ignore::gitignore::{Gitignore, GitignoreBuilder};
+use ignore::Match;
+use rayon::prelude::*;
+
+use crate::analysis_job::Job;
+use crate::project_fs::{Entry, ProjectFs};
+use crate::types::AnalysisStop;
+
+pub const PAPAYU_IGNORE: &str = ".papayuignore";
+
+/// Пропускаются всегда, если `.papayuignore` или `.gitignore` явно не вернули их (`!vendor/`).
+pub const EXCLUDED_DIRS: &[&str] = &[
+    ".git", "node_modules", "dist", "build", ".next", "target", ".cache", "coverage",
+    "__pycache__", ".venv", "venv", "vendor", ".cargo",
+];
+
+/// Запись и глубина папки, в которой она лежит (корень — 0).
+pub struct Walked {
+    pub entry: Entry,
+    pub depth: u32,
+}
+
+/// Записи проекта (порядок не определён) и причина остановки, если обход не дошёл до конца.
+pub struct Walk {
+    pub entries: Vec<Walked>,
+    pub stopped: Option<AnalysisStop>,
+}
+
+/// Обходит `root` целиком, пока задание не остановлено (лимит файлов, время, отмена).
+/// `exclude` — шаблоны gitignore от корня, сильнее файлов правил в корне.
+pub fn walk(fs: &dyn ProjectFs, root: &Path, job: &Job, exclude: &[String]) -> Walk {
+    let rules: Vec<Gitignore> = load_rules(fs, root, &root.join(".git").join("info").join("exclude"))
+        .into_iter()
+        .collect();
+    let entries = walk_dir(fs, root, 0, &rules, job, config_rules(root, exclude).as_ref());
+    Walk {
+        entries,
+        stopped: job.stopped(),
+    }
+}
+
+fn load_rules(fs: &dyn ProjectFs, dir: &Path, file: &Path) -> Option<Gitignore> {
+    let text = fs.read_to_string(file)?;
+    let mut builder = GitignoreBuilder::new(dir);
+    for line in text.lines() {
+        if let Err(e) = builder.add_line(Some(file.to_path_buf()), line) {
+            log::warn!("{}: {}", file.display(), e);
+        }
+    }
+    builder.build().ok()
+}
+
+fn config_rules(root: &Path, patterns: &[String]) -> Option<Gitignore> {
+    if patterns.is_empty() {
+        return None;
+    }
+    let mut builder = GitignoreBuilder::new(root);
+    for p in patterns {
+        if let Err(e) = builder.add_line(None, p) {
+            log::warn!("exclude: {}", e);
+        }
+    }
+    builder.build().ok()
+}
+
+/// Правила проверяются от самой глубокой папки к корню; первое совпадение решает.
+fn ignored(rules: &[Gitignore], entry: &Entry) -> bool {
+    for r in rules.iter().rev() {
+        match r.matched(&entry.path, entry.is_dir) {
+            Match::Ignore(_) => return true,
+            Match::Whitelist(_) => return false,
+            Match::None => {}
+        }
+    }
+    entry.is_dir
+        && entry
+            .path
+            .file_name()
+            .and_then(|n| n.to_str())
+            .is_some_and(|n| EXCLUDED_DIRS.contains(&n))
+}
+
+fn walk_dir(
+    fs: &dyn ProjectFs,
+    dir: &Path,
+    depth: u32,
+    outer: &[Gitignore],
+    job: &Job,
+    exclude: Option<&Gitignore>,
+) -> Vec<Walked> {
+    if job.walk_should_stop() {
+        return vec![];
+    }
+
+    // `.papayuignore` добавляется последним — он сильнее `.gitignore` той же папки.
+    let own: Vec<Gitignore> = [".gitignore", PAPAYU_IGNORE]
+        .into_iter()
+        .filter_map(|name| load_rules(fs, dir, &dir.join(name)))
+        .chain(exclude.cloned())
+        .collect();
+    // Правила родителей копируются, только если у папки есть свои.
+    let rules: Cow<[Gitignore]> = if own.is_empty() {
+        Cow::Borrowed(outer)
+    } else {
+        Cow::Owned(outer.iter().cloned().chain(own).collect())
+    };
+
+    let entries: Vec<Entry> = fs
+        .read_dir(dir)
+        .into_iter()
+        .filter(|e| !e.is_symlink && !ignored(&rules, e))
+        .collect();
+    job.walked_dir(dir, entries.iter().filter(|e| !e.is_dir).count() as u64);
+
+    let nested: Vec<Vec<Walked>> = entries
+        .par_iter()
+        .filter(|e| e.is_dir)
+        .map(|e| walk_dir(fs, &e.path, depth + 1, &rules, job, None))
+        .collect();
+
+    let mut out: Vec<Walked> = entries.into_iter().map(|entry| Walked { entry, depth }).collect();
+    out.extend(nested.into_iter().flatten());
+    out
+}
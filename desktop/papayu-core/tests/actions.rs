@@ -0,0 +1,248 @@
+mod common;
+
+use common::Fixture;
+use papayu_core::preview::{self, PreviewPayload};
+use papayu_core::progress::NoProgress;
+use papayu_core::types::{Action, ActionKind, ApplyMode, ApplyPayload, UndoMode};
+use papayu_core::{apply, session, undo};
+
+fn action(id: &str, kind: ActionKind, path: &str, content: Option<&str>) -> Action {
+    Action {
+        id: id.to_string(),
+        title: id.to_string(),
+        description: String::new(),
+        kind,
+        path: path.to_string(),
+        content: content.map(str::to_string),
+        search: None,
+        anchor: None,
+        to: None,
+    }
+}
+
+fn fix_eval() -> Vec<Action> {
+    vec![Action {
+        search: Some("return eval(input);".to_string()),
+        ..action("no-eval", ActionKind::SearchReplace, "src/index.js", Some("return Number(input);"))
+    }]
+}
+
+#[test]
+fn preview_leaves_project_untouched() {
+    let fx = Fixture::new("node-app");
+    let before = fx.read("src/index.js");
+
+    let mut actions = fix_eval();
+    actions.push(action("readme", ActionKind::CreateFile, "README.md", Some("# node-app\n")));
+    let result = preview::preview(&NoProgress, &fx.dirs(), PreviewPayload { path: fx.path().to_string(), actions });
+
+    assert!(result.ok, "{:?}", result.error);
+    assert_eq!(result.summary.files_created, 1);
+    assert_eq!(result.summary.files_updated, 1);
+    assert_eq!((result.summary.added, result.summary.removed), (2, 1));
+    assert_eq!(fx.read("src/index.js"), before);
+    assert!(fx.read("README.md").is_none());
+}
+
+#[test]
+fn suggested_actions_apply_and_undo() {
+    let fx = Fixture::new("node-app");
+    let report = fx.analyze();
+
+    let applied = apply::apply(
+        &NoProgress,
+        &fx.dirs(),
+        ApplyPayload {
+            path: fx.path().to_string(),
+            actions: report.actions.clone(),
+            ..Default::default()
+        },
+    );
+    assert!(applied.ok, "{:?} {:?}", applied.error, applied.issues);
+    assert!(applied.undo_available);
+    assert!(fx.read("README.md").is_some());
+    assert!(fx.project.path().join("tests").is_dir());
+    assert!(!fx.analyze().signals.iter().any(|s| s.message.contains("README")));
+
+    let history = session::history_dir(&fx.dirs()).unwrap();
+    assert_eq!(session::list_sessions(&history, Some(fx.project.path())).len(), 1);
+
+    let undone = undo::undo_latest(&NoProgress, &fx.dirs(), fx.path(), None);
+    assert!(undone.ok, "{:?}", undone.error);
+    assert_eq!(undone.session_id, applied.session_id);
+    assert!(fx.read("README.md").is_none());
+    assert!(!fx.project.path().join("tests").exists());
+}
+
+#[test]
+fn dry_run_predicts_resolved_findings() {
+    let fx = Fixture::new("node-app");
+    let before = fx.read("src/index.js");
+
+    let result = apply::apply(
+        &NoProgress,
+        &fx.dirs(),
+        ApplyPayload {
+            path: fx.path().to_string(),
+            actions: fix_eval(),
+            dry_run: true,
+            analyze: true,
+            ..Default::default()
+        },
+    );
+
+    assert!(result.ok, "{:?}", result.error);
+    assert!(result.dry_run);
+    assert!(!result.undo_available);
+    let analysis = result.analysis.expect("dry-run analysis");
+    assert_eq!(analysis.findings_after, analysis.findings_before - 1);
+    assert!(analysis.resolved_findings.iter().any(|f| f.rule_id.as_deref() == Some("security/eval")));
+    assert!(analysis.new_findings.is_empty());
+    assert_eq!(fx.read("src/index.js"), before);
+}
+
+#[test]
+fn actions_outside_project_are_rejected() {
+    let fx = Fixture::new("rust-lib");
+
+    let result = apply::apply(
+        &NoProgress,
+        &fx.dirs(),
+        ApplyPayload {
+            path: fx.path().to_string(),
+            actions: vec![action("escape", ActionKind::CreateFile, "../escape.txt", Some("x"))],
+            ..Default::default()
+        },
+    );
+
+    assert!(!result.ok);
+    assert!(result.applied.is_empty());
+    assert!(result.issues.iter().any(|i| i.action_id == "escape"), "{:?}", result.issues);
+    assert!(!fx.project.path().parent().unwrap().join("escape.txt").exists());
+}
+
+#[test]
+fn user_rule_fixes_resolve_their_findings() {
+    let fx = Fixture::new("custom-rules");
+    let report = fx.analyze();
+    let fixes: Vec<Action> = report.actions.into_iter().filter(|a| a.id.starts_with("team/")).collect();
+    assert_eq!(fixes.len(), 2);
+
+    let applied = apply::apply(
+        &NoProgress,
+        &fx.dirs(),
+        ApplyPayload {
+            path: fx.path().to_string(),
+            actions: fixes,
+            ..Default::default()
+        },
+    );
+
+    assert!(applied.ok, "{:?} {:?}", applied.error, applied.issues);
+    assert_eq!(fx.read("CHANGELOG.md").as_deref(), Some("# Changelog\n"));
+    assert!(fx.read("src/main.rs").unwrap().contains("log::info!(\"hello, {}\", name);"));
+    let after = fx.analyze();
+    let ids: Vec<_> = after.findings.iter().filter_map(|f| f.rule_id.as_deref()).collect();
+    assert_eq!(ids, ["team/rust-version"]);
+}
+
+#[test]
+fn dry_run_and_apply_report_same_error_codes() {
+    let cases = [
+        (action("nested", ActionKind::CreateFile, "package.json/x.txt", Some("x")), "PARENT_NOT_DIRECTORY"),
+        (action("mkdir", ActionKind::CreateDir, "package.json", None), "NOT_A_DIRECTORY"),
+        (action("rmdir", ActionKind::DeleteDir, "package.json", None), "NOT_A_DIRECTORY"),
+        (action("rm", ActionKind::DeleteFile, "src", None), "IS_A_DIRECTORY"),
+        (action("write", ActionKind::UpdateFile, "src", Some("x")), "IS_A_DIRECTORY"),
+    ];
+    for (a, expected) in cases {
+        let fx = Fixture::new("node-app");
+        let run = |dry_run| {
+            let result = apply::apply(
+                &NoProgress,
+                &fx.dirs(),
+                ApplyPayload {
+                    path: fx.path().to_string(),
+                    actions: vec![a.clone()],
+                    dry_run,
+                    ..Default::default()
+                },
+            );
+            assert!(!result.ok, "{}: {:?}", a.id, result.results);
+            result.results[0].error_code.clone()
+        };
+        assert_eq!(run(true).as_deref(), Some(expected), "dry-run {}", a.id);
+        assert_eq!(run(false).as_deref(), Some(expected), "apply {}", a.id);
+        assert!(fx.project.path().join("package.json").is_file());
+        assert!(fx.project.path().join("src").is_dir());
+    }
+}
+
+#[test]
+fn best_effort_keeps_snapshot_of_failed_action_paths() {
+    let fx = Fixture::new("node-app");
+    let original = fx.read("src/index.js");
+    let actions = vec![
+        action("readme", ActionKind::CreateFile, "README.md", Some("# node-app\n")),
+        Action {
+            search: Some("no such line".to_string()),
+            ..action("broken", ActionKind::SearchReplace, "src/index.js", Some("x"))
+        },
+        Action {
+            to: Some("src/copy.js".to_string()),
+            ..action("copy", ActionKind::CopyFile, "src/index.js", None)
+        },
+    ];
+
+    let result = apply::apply(
+        &NoProgress,
+        &fx.dirs(),
+        ApplyPayload {
+            path: fx.path().to_string(),
+            actions,
+            mode: ApplyMode::BestEffort,
+            ..Default::default()
+        },
+    );
+    assert!(result.ok, "{:?}", result.error);
+    assert_eq!(result.applied, ["readme"]);
+    assert_eq!(result.error_code.as_deref(), Some("APPLY_PARTIAL"));
+
+    // Путь упавшего действия остаётся в сессии, путь не начатого — нет.
+    let history = session::history_dir(&fx.dirs()).unwrap();
+    let manifest = session::read_manifest(&session::session_dir(&history, &result.session_id)).unwrap();
+    let files: Vec<&str> = manifest.files.iter().map(|f| f.path.as_str()).collect();
+    assert_eq!(files, ["README.md", "src/index.js"]);
+    assert_eq!(manifest.payload.actions.len(), 1);
+
+    let undone = undo::undo_latest(&NoProgress, &fx.dirs(), fx.path(), None);
+    assert!(undone.ok, "{:?}", undone.error);
+    assert!(fx.read("README.md").is_none());
+    assert_eq!(fx.read("src/index.js"), original);
+}
+
+#[test]
+fn merge_undo_reports_unmergeable_files_as_conflicts() {
+    let fx = Fixture::new("node-app");
+    let applied = apply::apply(
+        &NoProgress,
+        &fx.dirs(),
+        ApplyPayload {
+            path: fx.path().to_string(),
+            actions: fix_eval(),
+            ..Default::default()
+        },
+    );
+    assert!(applied.ok, "{:?}", applied.error);
+    // После apply пользователь удалил файл: сливать не с чем.
+    std::fs::remove_file(fx.project.path().join("src/index.js")).unwrap();
+
+    let undone = undo::undo_latest(&NoProgress, &fx.dirs(), fx.path(), Some(UndoMode::Merge));
+
+    assert!(undone.ok, "{:?}", undone.error);
+    assert_eq!(undone.file_conflicts.len(), 1);
+    assert_eq!(undone.file_conflicts[0].path, "src/index.js");
+    assert_eq!(undone.file_conflicts[0].resolution.as_deref(), Some("conflict"));
+    assert!(!undone.restored.contains(&"src/index.js".to_string()));
+    assert!(fx.read("src/index.js").is_none());
+}
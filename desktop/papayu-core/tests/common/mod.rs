@@ -0,0 +1,61 @@
+//! Проекты из `tests/fixtures/projects` копируются во временную папку: тесты меняют их
+//! и не должны видеть `.gitignore` репозитория. Папки приложения — тоже временные.
+
+// Каждый файл тестов берёт свою часть помощников.
+#![allow(dead_code)]
+
+use std::fs;
+use std::path::Path;
+use std::sync::Arc;
+
+use papayu_core::analyze;
+use papayu_core::app_dirs::AppDirs;
+use papayu_core::progress::NoProgress;
+use papayu_core::types::{AnalyzeOptions, AnalyzeReport};
+use tempfile::TempDir;
+
+pub struct Fixture {
+    pub project: TempDir,
+    pub app: TempDir,
+}
+
+impl Fixture {
+    pub fn new(name: &str) -> Fixture {
+        let project = TempDir::new().unwrap();
+        let src = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/projects").join(name);
+        copy_dir(&src, project.path());
+        Fixture {
+            project,
+            app: TempDir::new().unwrap(),
+        }
+    }
+
+    pub fn path(&self) -> &str {
+        self.project.path().to_str().unwrap()
+    }
+
+    pub fn dirs(&self) -> AppDirs {
+        AppDirs::new(self.app.path().join("data"), self.app.path().join("config"))
+    }
+
+    pub fn analyze(&self) -> AnalyzeReport {
+        analyze::run_analysis(Arc::new(NoProgress), &self.dirs(), self.path(), &AnalyzeOptions::default(), None).unwrap()
+    }
+
+    pub fn read(&self, rel: &str) -> Option<String> {
+        fs::read_to_string(self.project.path().join(rel)).ok()
+    }
+}
+
+fn copy_dir(src: &Path, dst: &Path) {
+    for entry in fs::read_dir(src).unwrap() {
+        let entry = entry.unwrap();
+        let to = dst.join(entry.file_name());
+        if entry.file_type().unwrap().is_dir() {
+            fs::create_dir_all(&to).unwrap();
+            copy_dir(&entry.path(), &to);
+        } else {
+            fs::copy(entry.path(), &to).unwrap();
+        }
+    }
+}
@@ -0,0 +1,2 @@
+// TODO: не попадает в отчёт — набор quality выключен
+document.write("<p>hello</p>");
@@ -0,0 +1,8 @@
+const config = require("./config");
+
+function run(input) {
+  // TODO: validate input
+  return eval(input);
+}
+
+console.log(run(config.expression));
@@ -0,0 +1,4 @@
+#[test]
+fn adds() {
+    assert_eq!(rust_lib::add(2, 2), 4);
+}
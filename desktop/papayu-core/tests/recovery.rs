@@ -0,0 +1,128 @@
+//! Восстановление прерванного apply: журнал и snapshot пишутся так же, как их пишет
+//! apply, а «сбой» — это просто конец записей в журнале.
+
+mod common;
+
+use std::fs;
+
+use common::Fixture;
+use papayu_core::journal::{self, Journal, JournalEntry};
+use papayu_core::path_policy::PathPolicy;
+use papayu_core::session;
+use papayu_core::types::{Action, ActionKind, ApplyPayload, RecoveryStrategy};
+
+const SESSION: &str = "1000";
+
+fn actions() -> Vec<Action> {
+    let action = |id: &str, kind, path: &str, content: &str| Action {
+        id: id.to_string(),
+        title: id.to_string(),
+        description: String::new(),
+        kind,
+        path: path.to_string(),
+        content: Some(content.to_string()),
+        search: None,
+        anchor: None,
+        to: None,
+    };
+    vec![
+        Action {
+            search: Some("return eval(input);".to_string()),
+            ..action("no-eval", ActionKind::SearchReplace, "src/index.js", "return Number(input);")
+        },
+        action("notes", ActionKind::CreateFile, "NOTES.md", "# Notes\n"),
+    ]
+}
+
+/// Apply, оборванный после snapshot'а и `applied` первых действий.
+fn crash(fx: &Fixture, applied: usize) {
+    let root = fx.project.path();
+    let payload = ApplyPayload {
+        path: fx.path().to_string(),
+        actions: actions(),
+        ..Default::default()
+    };
+    let dir = session::session_dir(&session::history_dir(&fx.dirs()).unwrap(), SESSION);
+    fs::create_dir_all(&dir).unwrap();
+    let mut j = Journal::create(&dir).unwrap();
+    j.append(&JournalEntry::Begin {
+        project_root: session::project_key(root),
+        started_at: session::now_rfc3339(),
+        payload: payload.clone(),
+    })
+    .unwrap();
+    let before = session::digest_paths(root, &session::action_paths(&payload.actions));
+    session::snapshot_paths(&dir, root, &session::snapshot_targets(&payload.actions)).unwrap();
+    j.append(&JournalEntry::Snapshot { before }).unwrap();
+    for a in &payload.actions[..applied] {
+        papayu_core::actions::execute(root, &PathPolicy::default(), a).unwrap();
+        j.append(&JournalEntry::Applied { action_id: a.id.clone() }).unwrap();
+    }
+}
+
+fn roll_forward(fx: &Fixture) -> papayu_core::types::RecoveryResult {
+    let history = session::history_dir(&fx.dirs()).unwrap();
+    assert_eq!(journal::startup_check(&history).len(), 1);
+    journal::recover(&history, SESSION, RecoveryStrategy::RollForward, &PathPolicy::default(), "test")
+}
+
+#[test]
+fn crash_after_snapshot_rolls_forward() {
+    let fx = Fixture::new("node-app");
+    crash(&fx, 0);
+
+    let result = roll_forward(&fx);
+
+    assert!(result.ok, "{:?}", result.error);
+    assert_eq!(result.error_code, None);
+    assert_eq!(result.applied, ["no-eval", "notes"]);
+    assert!(fx.read("src/index.js").unwrap().contains("return Number(input);"));
+    assert_eq!(fx.read("NOTES.md").as_deref(), Some("# Notes\n"));
+}
+
+#[test]
+fn crash_mid_action_after_snapshot_rolls_back() {
+    let fx = Fixture::new("node-app");
+    let original = fx.read("src/index.js");
+    crash(&fx, 0);
+    // Первое действие успело записать файл, но не отметилось в журнале.
+    let partial = original.as_deref().unwrap().replace("return eval(input);", "return Number(input);");
+    fs::write(fx.project.path().join("src/index.js"), partial).unwrap();
+
+    let result = roll_forward(&fx);
+
+    assert!(result.ok, "{:?}", result.error);
+    assert_eq!(result.error_code.as_deref(), Some("ROLL_FORWARD_UNSAFE_ROLLED_BACK"));
+    assert!(result.error.unwrap().contains("src/index.js"));
+    assert_eq!(fx.read("src/index.js"), original);
+    assert!(fx.read("NOTES.md").is_none());
+}
+
+#[test]
+fn crash_after_applied_finishes_the_rest_once() {
+    let fx = Fixture::new("node-app");
+    crash(&fx, 1);
+
+    let result = roll_forward(&fx);
+
+    assert!(result.ok, "{:?}", result.error);
+    assert_eq!(result.error_code, None);
+    assert_eq!(result.applied, ["no-eval", "notes"]);
+    assert_eq!(fx.read("src/index.js").unwrap().matches("Number(input)").count(), 1);
+    assert_eq!(fx.read("NOTES.md").as_deref(), Some("# Notes\n"));
+}
+
+#[test]
+fn crash_mid_action_after_applied_rolls_back() {
+    let fx = Fixture::new("node-app");
+    let original = fx.read("src/index.js");
+    crash(&fx, 1);
+    fs::write(fx.project.path().join("NOTES.md"), "# No").unwrap();
+
+    let result = roll_forward(&fx);
+
+    assert!(result.ok, "{:?}", result.error);
+    assert_eq!(result.error_code.as_deref(), Some("ROLL_FORWARD_UNSAFE_ROLLED_BACK"));
+    assert_eq!(fx.read("src/index.js"), original);
+    assert!(fx.read("NOTES.md").is_none());
+}
@@ -0,0 +1,15 @@
+use std::path::Path;
+
+use papayu_core::sarif;
+use papayu_core::types::AnalyzeReport;
+
+/// Записывает отчёт `analyze_project` в SARIF 2.1.0 по пути `out_path` (папки создаются).
+#[tauri::command]
+pub fn export_sarif(report: AnalyzeReport, out_path: String) -> Result<(), String> {
+    let out = Path::new(&out_path);
+    if out_path.trim().is_empty() || out.is_dir() {
+        return Err("path_invalid".into());
+    }
+    let json = serde_json::to_string_pretty(&sarif::to_sarif(&report)).map_err(|e| e.to_string())?;
+    papayu_core::actions::write_atomic(out, json.as_bytes())
+}
@@ -0,0 +1,14 @@
+use papayu_core::session;
+use papayu_core::types::SessionManifest;
+use tauri::AppHandle;
+
+use crate::host;
+
+#[tauri::command]
+pub fn get_session_manifest(app: AppHandle, session_id: String) -> Result<SessionManifest, String> {
+    if !session::is_valid_session_id(&session_id) {
+        return Err("session_id_invalid".into());
+    }
+    let history = session::history_dir(&host::app_dirs(&app))?;
+    session::read_manifest(&session::session_dir(&history, &session_id))
+}
@@ -0,0 +1,29 @@
+use std::path::Path;
+
+use papayu_core::session;
+use papayu_core::types::HistoryResult;
+use tauri::AppHandle;
+
+use crate::host;
+
+#[tauri::command]
+pub fn list_sessions(app: AppHandle, path: Option<String>) -> HistoryResult {
+    let history = match session::history_dir(&host::app_dirs(&app)) {
+        Ok(d) => d,
+        Err(e) => {
+            return HistoryResult {
+                ok: false,
+                sessions: vec![],
+                error: Some(e),
+                error_code: Some("APP_DATA_DIR".into()),
+            };
+        }
+    };
+
+    HistoryResult {
+        ok: true,
+        sessions: session::list_sessions(&history, path.as_deref().map(Path::new)),
+        error: None,
+        error_code: None,
+    }
+}
@@ -1,17 +1,32 @@
+//! Команды Tauri — тонкие обёртки над `papayu_core`: папки приложения и ход работы
+//! в окно, остальное делает ядро.
+
 mod analyze_project;
 mod apply_actions;
-pub mod ask_llm;
+mod export_sarif;
+mod ask_llm;
+mod collect_context;
 mod generate_ai_actions;
 mod get_app_info;
+mod get_session_manifest;
+mod list_sessions;
 mod preview_actions;
+mod recover_session;
 mod undo_last;
+mod undo_session;
+mod verify_commands;
 
-pub use analyze_project::analyze_project;
+pub use analyze_project::{analyze_project, cancel_analysis};
 pub use apply_actions::apply_actions;
+pub use export_sarif::export_sarif;
 pub use ask_llm::ask_llm;
+pub use collect_context::collect_project_context;
 pub use generate_ai_actions::generate_ai_actions;
 pub use get_app_info::get_app_info;
+pub use get_session_manifest::get_session_manifest;
+pub use list_sessions::list_sessions;
 pub use preview_actions::preview_actions;
+pub use recover_session::{get_pending_recovery, list_incomplete_sessions, recover_session};
 pub use undo_last::undo_last;
-mod collect_context;
-pub use collect_context::collect_project_context;
+pub use undo_session::{redo_session, undo_session};
+pub use verify_commands::{get_verify_commands, set_verify_commands};
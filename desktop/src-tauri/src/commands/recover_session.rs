@@ -0,0 +1,78 @@
+use papayu_core::file_index;
+use papayu_core::journal;
+use papayu_core::path_policy;
+use papayu_core::progress::ProgressSink;
+use papayu_core::session;
+use papayu_core::types::{RecoveryInfo, RecoveryResult, RecoveryStrategy};
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Manager, State, Window};
+
+use crate::host::{self, PendingRecovery, WindowProgress};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RecoverPayload {
+    pub session_id: String,
+    pub strategy: RecoveryStrategy,
+}
+
+#[tauri::command]
+pub fn list_incomplete_sessions(app: AppHandle) -> Result<Vec<RecoveryInfo>, String> {
+    let history = session::history_dir(&host::app_dirs(&app))?;
+    Ok(journal::incomplete_sessions(&history))
+}
+
+/// Сессии, прерванные до запуска приложения и ещё не восстановленные; UI спрашивает
+/// при старте и предлагает довести или откатить каждую.
+#[tauri::command]
+pub fn get_pending_recovery(pending: State<'_, PendingRecovery>) -> Vec<RecoveryInfo> {
+    pending.0.lock().map(|p| p.clone()).unwrap_or_default()
+}
+
+#[tauri::command]
+pub async fn recover_session(
+    window: Window,
+    app: AppHandle,
+    payload: RecoverPayload,
+) -> Result<RecoveryResult, String> {
+    let dirs = host::app_dirs(&app);
+    let history = match session::history_dir(&dirs) {
+        Ok(d) => d,
+        Err(e) => {
+            return Ok(RecoveryResult {
+                ok: false,
+                session_id: payload.session_id,
+                applied: vec![],
+                restored: vec![],
+                error: Some(e),
+                error_code: Some("APP_DATA_DIR".into()),
+            });
+        }
+    };
+
+    let msg = match payload.strategy {
+        RecoveryStrategy::RollForward => "Довожу прерванное применение…",
+        RecoveryStrategy::RollBack => "Откатываю прерванное применение…",
+    };
+    WindowProgress(window).message(msg);
+    file_index::forget();
+
+    let version = app.package_info().version.to_string();
+    let session_id = payload.session_id.clone();
+    let result = tauri::async_runtime::spawn_blocking(move || {
+        journal::recover(
+            &history,
+            &payload.session_id,
+            payload.strategy,
+            &path_policy::load(&dirs),
+            &version,
+        )
+    })
+    .await
+    .map_err(|e| e.to_string())?;
+    if result.ok {
+        if let Ok(mut pending) = app.state::<PendingRecovery>().0.lock() {
+            pending.retain(|r| r.session_id != session_id);
+        }
+    }
+    Ok(result)
+}
@@ -0,0 +1,33 @@
+use papayu_core::types::UndoResult;
+use papayu_core::undo::{self, SessionPayload};
+use tauri::{AppHandle, Window};
+
+use crate::host::{self, WindowProgress};
+
+#[tauri::command]
+pub async fn undo_session(
+    window: Window,
+    app: AppHandle,
+    payload: SessionPayload,
+) -> Result<UndoResult, String> {
+    let dirs = host::app_dirs(&app);
+    tauri::async_runtime::spawn_blocking(move || {
+        undo::undo(&WindowProgress(window), &dirs, payload)
+    })
+    .await
+    .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn redo_session(
+    window: Window,
+    app: AppHandle,
+    payload: SessionPayload,
+) -> Result<UndoResult, String> {
+    let dirs = host::app_dirs(&app);
+    tauri::async_runtime::spawn_blocking(move || {
+        undo::redo(&WindowProgress(window), &dirs, payload)
+    })
+    .await
+    .map_err(|e| e.to_string())
+}
@@ -0,0 +1,25 @@
+use std::path::Path;
+
+use papayu_core::types::VerifyCommand;
+use papayu_core::verify;
+use tauri::AppHandle;
+
+use crate::host;
+
+#[tauri::command]
+pub fn get_verify_commands(app: AppHandle, path: String) -> Vec<VerifyCommand> {
+    verify::load(&host::app_dirs(&app), Path::new(&path))
+}
+
+/// Пустой список отключает проверку для проекта.
+#[tauri::command]
+pub fn set_verify_commands(app: AppHandle, path: String, commands: Vec<VerifyCommand>) -> Result<(), String> {
+    let root = Path::new(&path);
+    if !root.is_dir() {
+        return Err("path_invalid".into());
+    }
+    if commands.iter().any(|c| c.run.trim().is_empty() || c.timeout_secs == 0) {
+        return Err("verify_command_invalid".into());
+    }
+    verify::save(&host::app_dirs(&app), root, commands)
+}
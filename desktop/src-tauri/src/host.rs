@@ -0,0 +1,34 @@
+//! Связь ядра с окном приложения: папки приложения от Tauri и ход работы событиями.
+
+use std::sync::Mutex;
+
+use papayu_core::app_dirs::AppDirs;
+use papayu_core::progress::ProgressSink;
+use papayu_core::types::{AnalyzeProgress, RecoveryInfo};
+use tauri::{AppHandle, Emitter, Manager, Window};
+
+/// Ход анализа (`AnalyzeProgress`).
+pub const ANALYSIS_EVENT: &str = "analysis_progress";
+/// Текст этапа apply, предпросмотра и отката.
+pub const MESSAGE_EVENT: &str = "analyze_progress";
+
+pub fn app_dirs(app: &AppHandle) -> AppDirs {
+    AppDirs::from_parts(app.path().app_data_dir().ok(), app.path().app_config_dir().ok())
+}
+
+/// Прерванные apply, найденные при запуске. Событие из `setup` пришло бы раньше, чем
+/// окно подпишется, поэтому UI забирает их сам (`get_pending_recovery`).
+#[derive(Default)]
+pub struct PendingRecovery(pub Mutex<Vec<RecoveryInfo>>);
+
+pub struct WindowProgress(pub Window);
+
+impl ProgressSink for WindowProgress {
+    fn analysis(&self, progress: &AnalyzeProgress) {
+        let _ = self.0.emit(ANALYSIS_EVENT, progress);
+    }
+
+    fn message(&self, text: &str) {
+        let _ = self.0.emit(MESSAGE_EVENT, text);
+    }
+}
@@ -1,8 +1,15 @@
-mod deep_analysis;
 mod commands;
-mod types;
+mod host;
 
-use commands::{analyze_project, apply_actions, ask_llm, generate_ai_actions, collect_project_context, get_app_info, preview_actions, undo_last};
+use commands::{
+  analyze_project, cancel_analysis, export_sarif, apply_actions, ask_llm, generate_ai_actions, collect_project_context, get_app_info,
+  get_pending_recovery, get_session_manifest, list_incomplete_sessions, list_sessions, preview_actions, recover_session, redo_session,
+  undo_last, undo_session, get_verify_commands, set_verify_commands,
+};
+use std::sync::Mutex;
+
+use papayu_core::{journal, session};
+use tauri::Manager;
 
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
@@ -18,17 +25,37 @@ pub fn run() {
             .build(),
         )?;
       }
+      // Прерванные apply (сбой, выключение питания): UI предложит довести или откатить.
+      let pending = match session::history_dir(&host::app_dirs(app.handle())) {
+        Ok(history) => journal::startup_check(&history),
+        Err(_) => vec![],
+      };
+      if !pending.is_empty() {
+        log::warn!("incomplete apply sessions: {}", pending.len());
+      }
+      app.manage(host::PendingRecovery(Mutex::new(pending)));
       Ok(())
     })
     .invoke_handler(tauri::generate_handler![
       analyze_project,
+      cancel_analysis,
+      export_sarif,
       preview_actions,
       apply_actions,
       undo_last,
+      list_sessions,
+      undo_session,
+      redo_session,
+      get_session_manifest,
+      list_incomplete_sessions,
+      get_pending_recovery,
+      recover_session,
       get_app_info,
       ask_llm,
       generate_ai_actions,
       collect_project_context,
+      get_verify_commands,
+      set_verify_commands,
     ])
     .run(tauri::generate_context!())
     .expect("error while running tauri application");
@@ -11,6 +11,7 @@ import { LlmSettingsPage } from './pages/LlmSettings';
 import { Layout } from './components/layout/Layout';
 import { ErrorBoundary } from './components/ErrorBoundary';
 import { ErrorDisplay } from './components/ErrorDisplay';
+import { RecoveryDialog } from './components/RecoveryDialog';
 import { NotFound } from './pages/NotFound';
 import { ROUTES } from './config/routes';
 import { useAppStore } from './store/app-store';
@@ -31,6 +32,7 @@ function App() {
       <HashRouter>
         <RouteTracker />
         <ErrorDisplay />
+        <RecoveryDialog />
         <Layout>
           <Routes>
             <Route path={ROUTES.TASKS.path} element={<Tasks />} />
@@ -0,0 +1,78 @@
+import { useEffect, useState } from 'react';
+import { getPendingRecovery, recoverSession, type RecoveryInfo, type RecoveryStrategy } from '../lib/analyze';
+
+/** Прерванные apply (сбой, выключение питания): довести каждую сессию или откатить её. */
+export function RecoveryDialog() {
+  const [sessions, setSessions] = useState<RecoveryInfo[]>([]);
+  const [busy, setBusy] = useState<string | null>(null);
+  const [error, setError] = useState<string | null>(null);
+
+  useEffect(() => {
+    getPendingRecovery().then(setSessions).catch(() => setSessions([]));
+  }, []);
+
+  if (sessions.length === 0) return null;
+
+  const recover = async (s: RecoveryInfo, strategy: RecoveryStrategy) => {
+    setBusy(s.session_id);
+    setError(null);
+    try {
+      const res = await recoverSession(s.session_id, strategy);
+      if (!res.ok) {
+        setError(res.error ?? res.error_code ?? 'Не удалось восстановить сессию');
+        return;
+      }
+      if (res.error_code) setError(res.error ?? res.error_code);
+      setSessions((prev) => prev.filter((p) => p.session_id !== s.session_id));
+    } catch (e) {
+      setError(e instanceof Error ? e.message : String(e));
+    } finally {
+      setBusy(null);
+    }
+  };
+
+  return (
+    <div className="fixed inset-0 z-50 flex items-center justify-center bg-black/40 p-4">
+      <div className="bg-card border rounded-xl shadow-lg max-w-lg w-full max-h-[80vh] overflow-hidden flex flex-col">
+        <div className="p-4 border-b shrink-0">
+          <h3 className="font-semibold">Применение изменений было прервано</h3>
+          <p className="text-sm text-muted-foreground mt-1">
+            Довести — выполнить оставшиеся действия, если файлы не менялись после сбоя (иначе изменения будут откачены). Откатить — вернуть проект к состоянию до применения.
+          </p>
+        </div>
+        <div className="p-4 overflow-auto space-y-2 flex-1 min-h-0">
+          {sessions.map((s) => (
+            <div key={s.session_id} className="p-3 rounded-lg border bg-background/50 text-sm space-y-1">
+              <p className="font-mono text-xs truncate" title={s.project_root}>{s.project_root}</p>
+              <p className="text-muted-foreground">
+                {new Date(s.started_at).toLocaleString()} · выполнено {s.applied.length}, осталось {s.pending.length}
+              </p>
+              <div className="flex gap-2 mt-2">
+                <button
+                  onClick={() => recover(s, 'roll_forward')}
+                  disabled={busy !== null}
+                  className="text-xs px-2 py-1 rounded border hover:bg-muted disabled:opacity-50"
+                >
+                  Довести
+                </button>
+                <button
+                  onClick={() => recover(s, 'roll_back')}
+                  disabled={busy !== null}
+                  className="text-xs px-2 py-1 rounded border hover:bg-muted disabled:opacity-50"
+                >
+                  Откатить
+                </button>
+              </div>
+            </div>
+          ))}
+          {error && <p className="text-sm text-destructive">{error}</p>}
+        </div>
+        <div className="p-4 border-t flex justify-end shrink-0">
+          <button onClick={() => setSessions([])} disabled={busy !== null} className="px-4 py-2 rounded-lg border font-medium hover:bg-muted disabled:opacity-50">
+            Позже
+          </button>
+        </div>
+      </div>
+    </div>
+  );
+}
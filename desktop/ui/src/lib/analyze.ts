@@ -5,7 +5,13 @@ export type ActionKind =
   | 'update_file'
   | 'delete_file'
   | 'create_dir'
-  | 'delete_dir';
+  | 'delete_dir'
+  | 'apply_patch'
+  | 'search_replace'
+  | 'insert_after'
+  | 'move_file'
+  | 'move_dir'
+  | 'copy_file';
 
 export interface Action {
   id: string;
@@ -14,6 +20,12 @@ export interface Action {
   kind: ActionKind;
   path: string;
   content?: string | null;
+  /** для search_replace */
+  search?: string | null;
+  /** для insert_after */
+  anchor?: string | null;
+  /** для move_file / move_dir / copy_file: путь назначения */
+  to?: string | null;
 }
 
 export interface ApplyResult {
@@ -24,27 +36,215 @@ export interface ApplyResult {
   error?: string | null;
   error_code?: string | null;
   undo_available: boolean;
+  issues: ValidationIssue[];
+  /** статус каждого действия в порядке выполнения */
+  results: ActionResult[];
+  /** ответ пробного прогона: проект не менялся */
+  dry_run: boolean;
+  analysis?: DryRunAnalysis | null;
+  /** команды проверки после apply, до первой неудачной */
+  verification: VerifyResult[];
+  git?: GitApplyResult | null;
+}
+
+export interface GitApplyResult {
+  commit: string | null;
+  branch: string | null;
+  dirty: string[];
+}
+
+export interface VerifyCommand {
+  /** строка для shell, выполняется в корне проекта */
+  run: string;
+  timeout_secs?: number;
+}
+
+export interface VerifyResult {
+  command: string;
+  ok: boolean;
+  exit_code?: number | null;
+  timed_out: boolean;
+  duration_ms: number;
+  /** конец вывода; целиком — в verify.log сессии */
+  output: string;
+  truncated: boolean;
+  error?: string | null;
+}
+
+export interface ApplyPayload {
+  path: string;
+  actions: Action[];
+  mode?: ApplyMode;
+  dry_run?: boolean;
+  /** вместе с dry_run: сравнить анализ проекта до и после */
+  analyze?: boolean;
+  git?: GitApply;
+}
+
+export interface GitApply {
+  branch?: boolean;
+  allow_dirty?: boolean;
+}
+
+export interface DryRunAnalysis {
+  findings_before: number;
+  findings_after: number;
+  new_findings: Finding[];
+  resolved_findings: Finding[];
+  new_signals: ProjectSignal[];
+  resolved_signals: ProjectSignal[];
+  risk_before: string;
+  risk_after: string;
+  stats_after: ReportStats;
+}
+
+/** all_or_nothing — при первой ошибке откатить всё; best_effort — применить всё, что получится */
+export type ApplyMode = 'all_or_nothing' | 'best_effort';
+
+export type ActionStatus = 'applied' | 'failed' | 'skipped' | 'rolled_back';
+
+export interface ActionResult {
+  action_id: string;
+  status: ActionStatus;
+  paths: string[];
+  error?: string | null;
+  error_code?: string | null;
+}
+
+export interface ValidationIssue {
+  action_id: string;
+  path: string;
+  code: string;
+  message: string;
 }
 
 export interface UndoResult {
   ok: boolean;
   session_id: string;
   restored: string[];
+  conflicts: string[];
+  file_conflicts: FileConflict[];
+  error?: string | null;
+  error_code?: string | null;
+}
+
+export type UndoMode = 'abort' | 'force' | 'merge';
+
+export interface FileConflict {
+  path: string;
+  reason: 'modified' | 'deleted' | 'created';
+  resolution?: 'overwritten' | 'merged' | 'conflict' | null;
+}
+
+export interface SessionInfo {
+  session_id: string;
+  project_root: string;
+  created_at: string;
+  status: 'applied' | 'undone';
+  undone_at?: string | null;
+  paths: string[];
+  actions: Action[];
+  git?: GitSession | null;
+}
+
+export interface GitSession {
+  commit: string;
+  base: string;
+  branch: string | null;
+  previous_branch: string | null;
+}
+
+export interface FileDigest {
+  sha256: string;
+  size: number;
+}
+
+export interface SessionManifest {
+  version: number;
+  app_version: string;
+  session_id: string;
+  project_root: string;
+  created_at: string;
+  payload: { path: string; actions: Action[] };
+  files: { path: string; before?: FileDigest | null; after?: FileDigest | null }[];
+}
+
+export interface RecoveryInfo {
+  session_id: string;
+  project_root: string;
+  started_at: string;
+  snapshot_complete: boolean;
+  applied: string[];
+  pending: string[];
+}
+
+export type RecoveryStrategy = 'roll_forward' | 'roll_back';
+
+export interface RecoveryResult {
+  ok: boolean;
+  session_id: string;
+  applied: string[];
+  restored: string[];
+  error?: string | null;
+  error_code?: string | null;
+}
+
+export interface HistoryResult {
+  ok: boolean;
+  sessions: SessionInfo[];
   error?: string | null;
   error_code?: string | null;
 }
 
 export type DiffItem = {
   path: string;
-  kind: 'create' | 'update' | 'delete' | 'mkdir' | 'rmdir' | string;
+  kind: 'create' | 'update' | 'delete' | 'mkdir' | 'rmdir' | 'patch' | 'replace' | 'insert' | 'move' | 'movedir' | 'copy' | string;
+  /** только для файлов до 200 КБ */
   before?: string | null;
   after?: string | null;
   summary: string;
+  to?: string | null;
+  hunks: DiffHunk[];
+  added: number;
+  removed: number;
+  binary: boolean;
+  truncated: boolean;
+};
+
+export type DiffHunk = {
+  old_start: number;
+  old_lines: number;
+  new_start: number;
+  new_lines: number;
+  lines: DiffLine[];
+};
+
+export type DiffLine = {
+  kind: 'context' | 'add' | 'remove';
+  old_line?: number | null;
+  new_line?: number | null;
+  text: string;
+};
+
+export type PreviewSummary = {
+  files_created: number;
+  files_updated: number;
+  files_deleted: number;
+  moved: number;
+  copied: number;
+  dirs_created: number;
+  dirs_deleted: number;
+  added: number;
+  removed: number;
+  text: string;
 };
 
 export type PreviewResult = {
   ok: boolean;
   diffs: DiffItem[];
+  summary: PreviewSummary;
+  warnings: string[];
+  issues: ValidationIssue[];
   error?: string | null;
   error_code?: string | null;
 };
@@ -71,7 +271,7 @@ export interface LlmContext {
 }
 
 export interface ProjectSignal {
-  category: string;
+  category: string; // security | quality | structure | history
   level: string;
   message: string;
 }
@@ -84,28 +284,166 @@ export interface Recommendation {
   impact: string;
 }
 
+export interface ReportStats {
+  file_count: number;
+  dir_count: number;
+  total_size_bytes: number;
+  top_extensions: [string, number][];
+  max_depth: number;
+}
+
+export interface Finding {
+  severity: string;
+  title: string;
+  details: string;
+  /** Стабильный id правила: `secrets/aws-access-key`, `structure/env-file`. */
+  rule_id?: string | null;
+  category?: 'security' | 'quality' | 'structure' | 'analysis' | null;
+  path?: string | null;
+  /** С 1; колонки в символах, end_column — символ после совпадения. */
+  start_line?: number | null;
+  start_column?: number | null;
+  end_line?: number | null;
+  end_column?: number | null;
+  /** Строка с совпадением; значения секретов скрыты. */
+  snippet?: string | null;
+  /** Как исправить — у правил пользователя. */
+  fix_hint?: string | null;
+}
+
 export interface AnalyzeReport {
   path: string;
   narrative: string;
-  stats: {
-    file_count: number;
-    dir_count: number;
-    total_size_bytes: number;
-    top_extensions: [string, number][];
-    max_depth: number;
-  };
+  stats: ReportStats;
   structure: ProjectStructure;
   project_context: ProjectContext;
-  findings: { severity: string; title: string; details: string }[];
+  findings: Finding[];
   recommendations: Recommendation[];
   actions?: Action[];
   signals: ProjectSignal[];
   report_md: string;
   llm_context: LlmContext;
+  /** что изменилось с прошлого анализа; null — анализ первый */
+  changes?: AnalysisDelta | null;
+  /** анализ остановлен раньше времени — отчёт по разобранной части */
+  stopped?: AnalysisStop | null;
+  /** настройки, с которыми прошёл анализ */
+  config: AnalysisConfig;
+}
+
+export type AnalysisProfile = 'quick' | 'standard' | 'exhaustive';
+
+export interface AnalysisLimits {
+  max_files: number;
+  max_duration_secs: number;
+  deep_max_files: number;
+  deep_max_file_bytes: number;
+  deep_max_depth: number;
+  context_max_bytes: number;
+  context_max_file_bytes: number;
+  context_max_files: number;
+  context_max_depth: number;
+}
+
+export interface AnalysisConfig {
+  profile: AnalysisProfile;
+  /** путь к .papayu.toml, если он есть */
+  source?: string | null;
+  limits: AnalysisLimits;
+  /** secrets | security | quality */
+  rules: string[];
+  exclude: string[];
+  git_history: boolean;
+  /** ошибки в .papayu.toml */
+  warnings: string[];
+  /** правила пользователя из app_config_dir/rules/ и .papayu/rules/ */
+  custom_rules?: CustomRule[];
+  /** ошибки в наборах правил пользователя */
+  rule_warnings?: string[];
+}
+
+export interface CustomRule {
+  id: string;
+  kind: 'regex' | 'file' | 'manifest';
+  severity: string;
+  category: string;
+  languages: string[];
+  message: string;
+  fix_hint?: string | null;
+  fix?: ActionTemplate | null;
+  /** файл набора */
+  source: string;
+}
+
+/** Исправление находки; `{path}` в строках — файл находки. */
+export interface ActionTemplate {
+  kind: ActionKind;
+  title?: string | null;
+  /** по умолчанию `{path}` */
+  path?: string | null;
+  content?: string | null;
+  search?: string | null;
+  anchor?: string | null;
+  to?: string | null;
+}
+
+export type AnalysisStop = 'file_limit' | 'timeout' | 'cancelled';
+
+/** событие analysis_progress */
+export interface AnalyzeProgress {
+  job_id: string;
+  phase: 'walk' | 'structure' | 'deep' | 'git_history' | 'report' | string;
+  message: string;
+  /** файлов обработано в текущем этапе */
+  files_scanned: number;
+  total_estimate?: number | null;
+  current_path?: string | null;
+}
+
+export interface AnalysisDelta {
+  /** время прошлого анализа */
+  since: string;
+  added: string[];
+  removed: string[];
+  modified: string[];
+  /** списки путей обрезаны (до 200) */
+  truncated: boolean;
+  new_findings: Finding[];
+  resolved_findings: Finding[];
+  /** файлов глубокого анализа разобрано заново / взято из индекса */
+  rescanned: number;
+  reused: number;
+}
+
+export interface AnalyzeOptions {
+  git_history?: boolean;
+  /** вместо профиля из .papayu.toml */
+  profile?: AnalysisProfile;
+}
+
+/** jobId — чтобы остановить анализ через cancelAnalysis */
+export async function analyzeProject(path: string, options?: AnalyzeOptions, jobId?: string): Promise<AnalyzeReport> {
+  return invoke<AnalyzeReport>('analyze_project', { path, options, jobId });
+}
+
+/** Прерванные apply, найденные при запуске приложения. */
+export async function getPendingRecovery(): Promise<RecoveryInfo[]> {
+  return invoke<RecoveryInfo[]>('get_pending_recovery');
+}
+
+/** Довести прерванный apply или откатить его по snapshot. */
+export async function recoverSession(sessionId: string, strategy: RecoveryStrategy): Promise<RecoveryResult> {
+  return invoke<RecoveryResult>('recover_session', { payload: { session_id: sessionId, strategy } });
+}
+
+/** false — анализа с таким id нет (уже завершился) */
+export async function cancelAnalysis(jobId: string): Promise<boolean> {
+  return invoke<boolean>('cancel_analysis', { jobId });
 }
 
-export async function analyzeProject(path: string): Promise<AnalyzeReport> {
-  return invoke<AnalyzeReport>('analyze_project', { path });
+/** Отчёт в SARIF 2.1.0 по пути outPath */
+export async function exportSarif(report: AnalyzeReport, outPath: string): Promise<void> {
+  return invoke<void>('export_sarif', { report, outPath });
 }
 
 // ---- LLM Integration ----
@@ -234,9 +572,10 @@ export interface ProjectContextResponse {
 
 export async function collectProjectContext(
   path: string,
+  profile?: AnalysisProfile,
 ): Promise<ProjectContextResponse> {
   return invoke<ProjectContextResponse>('collect_project_context', {
-    request: { path },
+    request: { path, profile },
   });
 }
 
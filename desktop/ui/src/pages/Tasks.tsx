@@ -1,5 +1,5 @@
 import { useState, useRef, useEffect } from 'react';
-import { open } from '@tauri-apps/plugin-dialog';
+import { open, save } from '@tauri-apps/plugin-dialog';
 import { listen } from '@tauri-apps/api/event';
 import {
   MessageSquare,
@@ -19,7 +19,7 @@ import {
   X,
 } from 'lucide-react';
 import { invoke } from '@tauri-apps/api/core';
-import { analyzeProject, askLlm, generateAiActions, collectProjectContext, chatWithProject, type AnalyzeReport, type Action, type ApplyResult, type UndoResult, type PreviewResult, type DiffItem, type LlmSettings, type ProjectContextResponse, DEFAULT_LLM_SETTINGS } from '../lib/analyze';
+import { analyzeProject, cancelAnalysis, exportSarif, askLlm, generateAiActions, collectProjectContext, chatWithProject, type AnalyzeReport, type AnalyzeProgress, type Action, type ApplyResult, type UndoResult, type PreviewResult, type DiffItem, type DiffHunk, type LlmSettings, type ProjectContextResponse, DEFAULT_LLM_SETTINGS } from '../lib/analyze';
 import { animateFadeInUp } from '../lib/anime-utils';
 import { useAppStore } from '../store/app-store';
 
@@ -46,6 +46,7 @@ export function Tasks() {
   const [messages, setMessages] = useState<Message[]>([]);
   const [input, setInput] = useState('');
   const [isAnalyzing, setIsAnalyzing] = useState(false);
+  const [analysisJobId, setAnalysisJobId] = useState<string | null>(null);
   const [lastReport, setLastReport] = useState<AnalyzeReport | null>(null);
   const [lastPath, setLastPath] = useState<string | null>(null);
   const [previousReport, setPreviousReport] = useState<AnalyzeReport | null>(null);
@@ -60,6 +61,7 @@ export function Tasks() {
   } | null>(null);
   const [isPreviewing, setIsPreviewing] = useState(false);
   const messagesEndRef = useRef<HTMLDivElement>(null);
+  const lastProgressMessage = useRef('');
   const containerRef = useRef<HTMLDivElement>(null);
   const messagesListRef = useRef<HTMLDivElement>(null);
   const storeSetLastReport = useAppStore((s) => s.setLastReport);
@@ -213,6 +215,20 @@ export function Tasks() {
     };
   }, []);
 
+  useEffect(() => {
+    // В чат — только смена этапа; счётчики файлов приходят часто.
+    const unlisten = listen<AnalyzeProgress>('analysis_progress', (e) => {
+      const { message } = e.payload;
+      if (message && message !== lastProgressMessage.current) {
+        lastProgressMessage.current = message;
+        setMessages((prev) => [...prev, { role: 'system', text: message }]);
+      }
+    });
+    return () => {
+      unlisten.then((fn) => fn());
+    };
+  }, []);
+
   const handleClearChat = () => {
     setMessages([]);
   };
@@ -297,7 +313,10 @@ export function Tasks() {
     ]);
 
     try {
-      const report = await analyzeProject(pathStr);
+      const jobId = `ui-${Date.now()}`;
+      lastProgressMessage.current = '';
+      setAnalysisJobId(jobId);
+      const report = await analyzeProject(pathStr, undefined, jobId);
       setPreviousReport(lastReport);
       setProjectContext(null);
       setLastReport(report);
@@ -353,6 +372,7 @@ export function Tasks() {
       });
     } finally {
       setIsAnalyzing(false);
+      setAnalysisJobId(null);
     }
   };
 
@@ -431,6 +451,17 @@ export function Tasks() {
     URL.revokeObjectURL(url);
   };
 
+  const handleDownloadSARIF = async (report: AnalyzeReport) => {
+    const outPath = await save({ defaultPath: 'papa-yu-report.sarif', filters: [{ name: 'SARIF', extensions: ['sarif', 'json'] }] });
+    if (!outPath) return;
+    try {
+      await exportSarif(report, outPath);
+      pushSystem(`SARIF сохранён: ${outPath}`);
+    } catch (e) {
+      pushSystem(`Не удалось сохранить SARIF: ${String(e)}`);
+    }
+  };
+
   const pushSystem = (text: string) => {
     setMessages((p) => [...p, { role: 'system', text }]);
   };
@@ -439,6 +470,16 @@ export function Tasks() {
     setMessages((p) => [...p, { role: 'assistant', text }]);
   };
 
+  function hunksToText(hunks: DiffHunk[]) {
+    return hunks
+      .map((h) =>
+        [`@@ -${h.old_start},${h.old_lines} +${h.new_start},${h.new_lines} @@`]
+          .concat(h.lines.map((l) => (l.kind === 'add' ? '+' : l.kind === 'remove' ? '-' : ' ') + l.text))
+          .join('\n'),
+      )
+      .join('\n');
+  }
+
   function clip(s: string, n = 1200) {
     if (!s) return '';
     return s.length > n ? s.slice(0, n) + '\n…(обрезано)…' : s;
@@ -449,7 +490,9 @@ export function Tasks() {
     lines.push('Вот что изменится:\n\n');
     diffs.forEach((d, i) => {
       lines.push(`${i + 1}. ${d.summary}`);
-      if (d.kind === 'create' || d.kind === 'update') {
+      if (d.hunks?.length) {
+        lines.push(`\`\`\`diff\n${clip(hunksToText(d.hunks))}\n\`\`\``);
+      } else if (d.kind === 'create' || d.kind === 'update') {
         if (d.before != null) {
           lines.push(`— До:\n\`\`\`\n${clip(d.before)}\n\`\`\``);
         }
@@ -480,7 +523,7 @@ export function Tasks() {
         return;
       }
       setPendingPreview({ path: projectPath, actions: selected, diffs: res.diffs });
-      pushSystem('Подготовил предпросмотр изменений.');
+      pushSystem(`Подготовил предпросмотр изменений. ${res.summary?.text ?? ''}`.trim());
       pushAssistant(renderPreviewText(res.diffs));
     } catch (e) {
       setIsPreviewing(false);
@@ -496,7 +539,12 @@ export function Tasks() {
         payload: { path, actions },
       });
       if (res.ok) {
-        pushSystem('Изменения применены.');
+        const failed = (res.results ?? []).filter((r) => r.status !== 'applied');
+        pushSystem(
+          failed.length
+            ? `Применено ${res.applied.length} из ${res.results.length} изменений. Не применены: ${failed.map((r) => `${r.action_id} (${r.error_code ?? r.status})`).join(', ')}.`
+            : 'Изменения применены.'
+        );
         setUndoAvailable(true);
         addAuditEvent({
           id: `apply-${Date.now()}`,
@@ -579,6 +627,16 @@ export function Tasks() {
             <FolderPlus className="w-4 h-4" />
             Выбрать папки
           </button>
+          {isAnalyzing && analysisJobId && (
+            <button
+              onClick={() => cancelAnalysis(analysisJobId)}
+              className="inline-flex items-center gap-2 px-3 py-1.5 rounded-lg border text-sm font-medium hover:bg-muted"
+              title="Остановить анализ — отчёт будет по уже разобранной части"
+            >
+              <X className="w-4 h-4" />
+              Остановить
+            </button>
+          )}
           {lastPath && (
             <button
               onClick={handleRepeatAnalysis}
@@ -707,6 +765,7 @@ export function Tasks() {
                         error={(m as Message & { error?: string }).error}
                         onDownload={handleDownloadReport}
                         onDownloadMD={handleDownloadMD}
+                        onDownloadSARIF={handleDownloadSARIF}
                         isCurrentReport={lastReport === m.report}
                         selectedActions={selectedActions}
                         setSelectedActions={setSelectedActions}
@@ -837,13 +896,28 @@ function PreviewDialog({
                   </button>
                   {expanded[i] && (
                     <div className="px-3 pb-3 space-y-2 text-xs font-mono bg-muted/30 border-t">
-                      {d.before != null && (
+                      {d.hunks?.length > 0 && (
+                        <pre className="whitespace-pre-wrap break-words max-h-80 overflow-auto rounded p-2 bg-background">
+                          {d.hunks.flatMap((h, hi) => [
+                            <div key={`h${hi}`} className="text-muted-foreground">{`@@ -${h.old_start},${h.old_lines} +${h.new_start},${h.new_lines} @@`}</div>,
+                            ...h.lines.map((l, li) => (
+                              <div
+                                key={`h${hi}-${li}`}
+                                className={l.kind === 'add' ? 'text-green-600' : l.kind === 'remove' ? 'text-red-600' : undefined}
+                              >
+                                {(l.kind === 'add' ? '+' : l.kind === 'remove' ? '-' : ' ') + l.text}
+                              </div>
+                            )),
+                          ])}
+                        </pre>
+                      )}
+                      {!d.hunks?.length && d.before != null && (
                         <div>
                           <p className="text-muted-foreground mb-1">До:</p>
                           <pre className="whitespace-pre-wrap break-words max-h-40 overflow-auto rounded p-2 bg-background">{d.before}</pre>
                         </div>
                       )}
-                      {d.after != null && (
+                      {!d.hunks?.length && d.after != null && (
                         <div>
                           <p className="text-muted-foreground mb-1">После:</p>
                           <pre className="whitespace-pre-wrap break-words max-h-40 overflow-auto rounded p-2 bg-background">{d.after}</pre>
@@ -890,6 +964,7 @@ function ReportBlock({
   error,
   onDownload,
   onDownloadMD,
+  onDownloadSARIF,
   isCurrentReport,
   selectedActions,
   setSelectedActions,
@@ -907,6 +982,7 @@ function ReportBlock({
   error?: string;
   onDownload: (r: AnalyzeReport) => void;
   onDownloadMD: (r: AnalyzeReport) => void;
+  onDownloadSARIF: (r: AnalyzeReport) => void;
   isCurrentReport: boolean;
   selectedActions: Record<string, boolean>;
   setSelectedActions: React.Dispatch<React.SetStateAction<Record<string, boolean>>>;
@@ -969,6 +1045,8 @@ function ReportBlock({
                   <li key={i}>
                     <span className={f.severity === 'high' ? 'text-destructive' : ''}>{f.title}</span>
                     {f.details && ` — ${f.details}`}
+                    {f.snippet && <code className="block ml-5 text-xs text-muted-foreground truncate">{f.snippet}</code>}
+                    {f.fix_hint && <span className="block ml-5 text-xs text-muted-foreground">{f.fix_hint}</span>}
                   </li>
                 ))}
               </ul>
@@ -1095,6 +1173,14 @@ function ReportBlock({
                 Скачать MD
               </button>
             )}
+            <button
+              type="button"
+              onClick={() => onDownloadSARIF(r)}
+              className="inline-flex items-center gap-2 px-3 py-1.5 rounded-lg border bg-background/80 text-sm font-medium hover:bg-muted"
+            >
+              <FileDown className="w-4 h-4" />
+              Скачать SARIF
+            </button>
           </div>
         </>
       )}